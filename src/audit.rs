@@ -0,0 +1,111 @@
+use crate::csv::custom_date_format;
+use crate::person::Person;
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDate, SecondsFormat};
+use serde::Serialize;
+
+/// A line of the audit log.
+#[derive(Serialize)]
+struct Entry<'a> {
+    /// The local time the persons were shown or notified about.
+    time: String,
+    /// `displayed` or `notified`.
+    event: &'a str,
+    /// The notifier that was told about the persons.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channel: Option<&'a str>,
+    /// The date whose birthdays were announced.
+    date: NaiveDate,
+    persons: Vec<AuditedPerson>,
+}
+
+#[derive(Serialize)]
+struct AuditedPerson {
+    name: String,
+    /// Tells apart persons with the same name.
+    birthday: String,
+}
+
+impl AuditedPerson {
+    fn new(person: &Person) -> Self {
+        Self {
+            name: person.name(),
+            birthday: person
+                .birthday
+                .format(custom_date_format::FORMAT)
+                .to_string(),
+        }
+    }
+}
+
+/// Appends `event` about `persons` on `date` as a line of json to the audit log at `path`.
+///
+/// The log is only ever appended to, so it can answer whether the birthday of somebody
+/// was announced later on. It always contains the names regardless of `--log-pii`.
+pub fn record<'a>(
+    path: &Path,
+    event: &str,
+    channel: Option<&str>,
+    date: NaiveDate,
+    persons: impl IntoIterator<Item = &'a Person>,
+) {
+    let entry = Entry {
+        time: Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+        event,
+        channel,
+        date,
+        persons: persons.into_iter().map(AuditedPerson::new).collect(),
+    };
+    write(path, &entry);
+}
+
+fn write(path: &Path, entry: &Entry<'_>) {
+    let result = serde_json::to_vec(entry)
+        .map_err(std::io::Error::from)
+        .and_then(|mut line| {
+            line.push(b'\n');
+            // a single write keeps the lines of the display and the daemon apart
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?
+                .write_all(&line)
+        });
+    if let Err(error) = result {
+        println!("error writing audit log {}: {error}", path.display());
+    }
+}
+
+/// Records the cards shown by the display, every set of persons only once per date so
+/// reloading the persons does not repeat it.
+#[derive(Debug)]
+pub struct DisplayAudit {
+    path: PathBuf,
+    /// The date and names of the last recorded cards.
+    last: Option<(NaiveDate, Vec<String>)>,
+}
+
+impl DisplayAudit {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, last: None }
+    }
+
+    /// Records that the cards of `persons` on `date` are shown unless they already were.
+    pub fn displayed<'a>(
+        &mut self,
+        date: NaiveDate,
+        persons: impl IntoIterator<Item = &'a Person>,
+    ) {
+        let persons: Vec<&Person> = persons.into_iter().collect();
+        let names: Vec<String> = persons.iter().map(|person| person.name()).collect();
+        if persons.is_empty() || self.last.as_ref() == Some(&(date, names.clone())) {
+            return;
+        }
+        record(&self.path, "displayed", None, date, persons);
+        self.last = Some((date, names));
+    }
+}
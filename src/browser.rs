@@ -0,0 +1,35 @@
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::process::{Command, Stdio};
+
+/// Opens `url` in a new tab of the browser running the display.
+#[cfg(target_arch = "wasm32")]
+pub fn open(url: &str) -> io::Result<()> {
+    web_sys::window()
+        .and_then(|window| window.open_with_url_and_target(url, "_blank").ok())
+        .map(drop)
+        .ok_or_else(|| io::Error::other("could not open a new tab"))
+}
+
+/// Opens `url` with the default application of the platform without waiting for it.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open(url: &str) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = Command::new("xdg-open");
+
+    command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
@@ -0,0 +1,229 @@
+use crate::config;
+use crate::error::BirthdayError;
+#[cfg(all(feature = "network", feature = "gui"))]
+use crate::image_budget;
+use crate::source::PersonSource;
+use crate::Cli;
+
+use std::fs;
+use std::path::{Component, Path};
+
+/// The file extension of bundles, which are started directly instead of a csv file.
+const EXTENSION: &str = "bday";
+
+/// The size of the blocks of a tar archive.
+const BLOCK: usize = 512;
+
+/// The megabytes of `--image-memory` when a bundle with photos is started without it,
+/// as the photos are only read from the image cache with a budget.
+#[cfg(all(feature = "network", feature = "gui"))]
+const IMAGE_MEMORY: usize = 256;
+
+/// Returns whether `path` is a bundle written by [`write`].
+pub fn is_bundle(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == EXTENSION)
+}
+
+/// Packs the file of the persons, the config file, the font and the cached photos of
+/// the persons of `cli` into a tar archive at `path`.
+///
+/// The key to decrypt the persons is left out so it has to be given again.
+pub fn write(cli: &Cli, source: &mut dyn PersonSource, path: &Path) -> Result<(), BirthdayError> {
+    let mut entries = vec![(entry("persons", &cli.file)?, read(&cli.file)?)];
+    if let Some(config) = &cli.config {
+        entries.push((String::from("config.toml"), read(config)?));
+    }
+    if let Some(font) = &cli.font {
+        entries.push((entry("font", font)?, read(font)?));
+    }
+
+    let persons = source.load(cli.quiet)?;
+    #[cfg(all(feature = "network", feature = "gui"))]
+    if let Some(dir) = cli.image_cache.clone().or_else(image_budget::default_dir) {
+        for person in &persons {
+            for url in person.image_urls() {
+                let file = image_budget::cache_file(&dir, url);
+                let name = format!("images/{}", file.file_name().unwrap().to_string_lossy());
+                if entries.iter().all(|(entry, _)| *entry != name) {
+                    if let Ok(bytes) = fs::read(&file) {
+                        entries.push((name, bytes));
+                    }
+                }
+            }
+        }
+    }
+
+    let archive = pack(&entries).map_err(BirthdayError::Data)?;
+    fs::write(path, archive).map_err(|error| BirthdayError::io(path, error))?;
+    if !cli.quiet {
+        let photos = entries
+            .iter()
+            .filter(|(name, _)| name.starts_with("images/"))
+            .count();
+        println!(
+            "wrote {} with {} persons and {photos} photos",
+            path.display(),
+            persons.len()
+        );
+    }
+    Ok(())
+}
+
+/// Unpacks the bundle at `cli.file` into the cache directory of the user and points
+/// `cli` to its files, keeping the config file and font given on the command line.
+pub fn open(cli: &mut Cli) -> Result<(), BirthdayError> {
+    let bundle = cli.file.clone();
+    let entries = unpack(&read(&bundle)?).map_err(|error| {
+        BirthdayError::Data(format!("error reading {}: {error}", bundle.display()))
+    })?;
+    let stem = bundle.file_stem().unwrap_or_default().to_string_lossy();
+    let dir = config::cache_dir()
+        .ok_or_else(|| format!("no cache directory found to unpack {}", bundle.display()))?
+        .join(format!("bundle-{stem}"));
+    // photos of an older version of the bundle are not kept
+    let _ = fs::remove_dir_all(&dir);
+    config::create_private_dir(&dir)?;
+
+    let mut file = None;
+    for (name, bytes) in entries {
+        let path = dir.join(&name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| BirthdayError::io(parent, error))?;
+        }
+        fs::write(&path, bytes).map_err(|error| BirthdayError::io(&path, error))?;
+        match name.split_once('/') {
+            Some(("persons", _)) => file = Some(path),
+            Some(("font", _)) => {
+                cli.font.get_or_insert(path);
+            }
+            None if name == "config.toml" => {
+                cli.config.get_or_insert(path);
+            }
+            _ => {}
+        }
+    }
+    cli.file = file
+        .ok_or_else(|| BirthdayError::Data(format!("{} contains no persons", bundle.display())))?;
+
+    #[cfg(all(feature = "network", feature = "gui"))]
+    if dir.join("images").is_dir() && cli.image_cache.is_none() {
+        cli.image_cache = Some(dir.join("images"));
+        cli.image_memory.get_or_insert(IMAGE_MEMORY);
+    }
+    Ok(())
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, BirthdayError> {
+    fs::read(path).map_err(|error| BirthdayError::io(path, error))
+}
+
+/// Returns the name of the file at `path` in the directory `dir` of the bundle.
+fn entry(dir: &str, path: &Path) -> Result<String, BirthdayError> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| BirthdayError::Data(format!("{} is no file", path.display())))?;
+    Ok(format!("{dir}/{}", name.to_string_lossy()))
+}
+
+/// Writes `entries` of names and contents as a tar archive, so a bundle can also be
+/// looked at with `tar -tf`.
+fn pack(entries: &[(String, Vec<u8>)]) -> Result<Vec<u8>, String> {
+    let mut archive = Vec::new();
+    for (name, content) in entries {
+        let mut header = [0; BLOCK];
+        if name.len() >= 100 {
+            return Err(format!("the file name {name} is too long for a bundle"));
+        }
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..108].copy_from_slice(b"0000644\0");
+        header[108..116].copy_from_slice(b"0000000\0");
+        header[116..124].copy_from_slice(b"0000000\0");
+        header[124..136].copy_from_slice(format!("{:011o}\0", content.len()).as_bytes());
+        header[136..148].copy_from_slice(b"00000000000\0");
+        header[156] = b'0';
+        header[257..265].copy_from_slice(b"ustar\x0000");
+        // the checksum is computed with its own field filled with spaces
+        header[148..156].fill(b' ');
+        let checksum: u32 = header.iter().map(|&byte| u32::from(byte)).sum();
+        header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+
+        archive.extend_from_slice(&header);
+        archive.extend_from_slice(content);
+        archive.resize(archive.len().next_multiple_of(BLOCK), 0);
+    }
+    // two empty blocks end the archive
+    archive.resize(archive.len() + 2 * BLOCK, 0);
+    Ok(archive)
+}
+
+/// Reads the regular files of a tar archive written by [`pack`].
+///
+/// Names that would be written outside of the directory of the bundle are rejected.
+fn unpack(archive: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while let Some(header) = archive.get(offset..offset + BLOCK) {
+        if header.iter().all(|&byte| byte == 0) {
+            break;
+        }
+        let field = |range: std::ops::Range<usize>| {
+            let field = &header[range];
+            let end = field
+                .iter()
+                .position(|&byte| byte == 0)
+                .unwrap_or(field.len());
+            String::from_utf8_lossy(&field[..end]).trim().to_string()
+        };
+        let name = field(0..100);
+        let size = usize::from_str_radix(&field(124..136), 8)
+            .map_err(|_| format!("invalid size of {name}"))?;
+        let start = offset + BLOCK;
+        let content = start
+            .checked_add(size)
+            .and_then(|end| archive.get(start..end))
+            .ok_or_else(|| format!("{name} is cut off"))?;
+        offset = start + size.next_multiple_of(BLOCK);
+
+        // directories and links are not written by `pack`
+        if !matches!(header[156], b'0' | 0) {
+            continue;
+        }
+        if !Path::new(&name)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(format!("invalid file name {name}"));
+        }
+        entries.push((name, content.to_vec()));
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpacks_what_was_packed() {
+        let entries = vec![
+            (
+                String::from("persons/birthdays.csv"),
+                b"Muster,Anna,10.03.1990,f\n".to_vec(),
+            ),
+            (String::from("config.toml"), Vec::new()),
+            (String::from("images/photo"), vec![0xff; BLOCK + 1]),
+        ];
+
+        assert_eq!(unpack(&pack(&entries).unwrap()).unwrap(), entries);
+    }
+
+    #[test]
+    fn rejects_names_outside_of_the_bundle() {
+        for name in ["../persons.csv", "/etc/passwd", "images/../../photo"] {
+            let archive = pack(&[(String::from(name), Vec::new())]).unwrap();
+
+            assert!(unpack(&archive).is_err(), "{name}");
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use crate::index::{BirthdayIndex, MilestoneIndex};
+use crate::person::Occasion;
+
+use chrono::{Days, NaiveDate};
+
+/// How many days are searched for the next or previous date somebody celebrates on.
+const BROWSE_DAYS: usize = 366;
+
+/// Decides which date is shown and who celebrates on it, apart from the gui so it can
+/// be tested without a window.
+#[derive(Debug, Clone, Copy)]
+pub struct Calendar<'a> {
+    pub birthdays: &'a BirthdayIndex,
+    pub half_birthdays: &'a BirthdayIndex,
+    pub milestones: &'a MilestoneIndex,
+}
+
+impl Calendar<'_> {
+    /// Returns whether somebody has birthday, half birthday or a milestone on `date`.
+    pub fn celebrates(&self, date: NaiveDate) -> bool {
+        self.birthdays.contains(date)
+            || self.half_birthdays.contains(date)
+            || self.milestones.contains(date)
+    }
+
+    /// Returns the date whose cards are shown on `today`, `shown` if one was chosen and
+    /// otherwise the next one within `look_ahead` days if nobody celebrates today.
+    pub fn displayed_date(
+        &self,
+        today: NaiveDate,
+        shown: Option<NaiveDate>,
+        look_ahead: u64,
+    ) -> NaiveDate {
+        if let Some(date) = shown {
+            return date;
+        }
+        if self.celebrates(today) {
+            return today;
+        }
+        (1..=look_ahead)
+            .filter_map(|days| today.checked_add_days(Days::new(days)))
+            .find(|date| self.celebrates(*date))
+            .unwrap_or(today)
+    }
+
+    /// Returns the persons celebrating on `date` and what they celebrate, the birthdays
+    /// first followed by the half birthdays and milestones.
+    pub fn occasions(&self, date: NaiveDate) -> Vec<(usize, Occasion)> {
+        self.birthdays
+            .on(date)
+            .iter()
+            .map(|&index| (index, Occasion::Birthday))
+            .chain(
+                self.half_birthdays
+                    .on(date)
+                    .iter()
+                    .map(|&index| (index, Occasion::HalfBirthday)),
+            )
+            .chain(
+                self.milestones
+                    .on(date)
+                    .into_iter()
+                    .map(|(index, days)| (index, Occasion::Milestone(days))),
+            )
+            .collect()
+    }
+
+    /// Returns the next date somebody celebrates on after `from` if `forward` and the
+    /// previous one otherwise, but never a date before `today`.
+    pub fn browse(&self, from: NaiveDate, today: NaiveDate, forward: bool) -> NaiveDate {
+        let mut date = from;
+        for _ in 0..BROWSE_DAYS {
+            let next = if forward {
+                date.succ_opt()
+            } else {
+                date.pred_opt()
+            };
+            date = next.unwrap_or(today);
+            if date <= today {
+                return today;
+            }
+            if self.celebrates(date) {
+                return date;
+            }
+        }
+        date
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{date, person};
+    use crate::person::Person;
+
+    /// The indices of `persons` with half birthdays and milestones every 100 days.
+    fn indices(persons: &[Person]) -> (BirthdayIndex, BirthdayIndex, MilestoneIndex) {
+        (
+            BirthdayIndex::new(persons),
+            BirthdayIndex::half_birthdays(persons),
+            MilestoneIndex::new(persons, Some(100)),
+        )
+    }
+
+    #[test]
+    fn shows_the_next_date_within_the_look_ahead() {
+        let persons = [person("Anna", date(1990, 3, 10))];
+        let (birthdays, half_birthdays, milestones) = indices(&persons);
+        let calendar = Calendar {
+            birthdays: &birthdays,
+            half_birthdays: &half_birthdays,
+            milestones: &milestones,
+        };
+        let today = date(2024, 3, 7);
+
+        assert_eq!(calendar.displayed_date(today, None, 7), date(2024, 3, 10));
+        assert_eq!(calendar.displayed_date(today, None, 2), today);
+        assert_eq!(calendar.displayed_date(today, None, 0), today);
+        let shown = Some(date(2024, 5, 1));
+        assert_eq!(calendar.displayed_date(today, shown, 7), date(2024, 5, 1));
+    }
+
+    #[test]
+    fn lists_birthdays_before_half_birthdays_and_milestones() {
+        let persons = [
+            person("Milestone", date(2024, 4, 1) - Days::new(300)),
+            person("Half", date(1990, 10, 1)),
+            person("Birthday", date(1990, 4, 1)),
+        ];
+        let (birthdays, half_birthdays, milestones) = indices(&persons);
+        let calendar = Calendar {
+            birthdays: &birthdays,
+            half_birthdays: &half_birthdays,
+            milestones: &milestones,
+        };
+
+        assert_eq!(
+            calendar.occasions(date(2024, 4, 1)),
+            vec![
+                (2, Occasion::Birthday),
+                (1, Occasion::HalfBirthday),
+                (0, Occasion::Milestone(300)),
+            ]
+        );
+        assert!(calendar.occasions(date(2024, 4, 2)).is_empty());
+    }
+
+    #[test]
+    fn browses_to_dates_with_birthdays_but_not_into_the_past() {
+        let persons = [
+            person("Anna", date(1990, 3, 10)),
+            person("Berta", date(1985, 6, 1)),
+        ];
+        let birthdays = BirthdayIndex::new(&persons);
+        let empty = BirthdayIndex::default();
+        let milestones = MilestoneIndex::default();
+        let calendar = Calendar {
+            birthdays: &birthdays,
+            half_birthdays: &empty,
+            milestones: &milestones,
+        };
+        let today = date(2024, 3, 1);
+
+        assert_eq!(calendar.browse(today, today, true), date(2024, 3, 10));
+        assert_eq!(
+            calendar.browse(date(2024, 3, 10), today, true),
+            date(2024, 6, 1)
+        );
+        assert_eq!(
+            calendar.browse(date(2024, 6, 1), today, false),
+            date(2024, 3, 10)
+        );
+        assert_eq!(calendar.browse(date(2024, 3, 10), today, false), today);
+    }
+}
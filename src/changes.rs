@@ -0,0 +1,82 @@
+use crate::person::Person;
+
+use std::collections::{HashMap, HashSet};
+
+/// The persons added to, removed from and changed in a source since it was loaded the
+/// last time.
+///
+/// Persons are told apart by their first and last name like when files are merged.
+#[derive(Debug, Default)]
+pub struct Changes<'a> {
+    pub added: Vec<&'a Person>,
+    pub removed: Vec<&'a Person>,
+    /// The persons as loaded now whose birthday or other fields changed.
+    pub changed: Vec<&'a Person>,
+}
+
+impl<'a> Changes<'a> {
+    /// Compares the persons loaded before with the ones loaded now.
+    pub fn between(before: &'a [Person], now: &'a [Person]) -> Self {
+        let known: HashMap<(&str, &str), &Person> =
+            before.iter().map(|person| (name(person), person)).collect();
+        let current: HashSet<(&str, &str)> = now.iter().map(name).collect();
+        Self {
+            added: now
+                .iter()
+                .filter(|person| !known.contains_key(&name(person)))
+                .collect(),
+            removed: before
+                .iter()
+                .filter(|person| !current.contains(&name(person)))
+                .collect(),
+            changed: now
+                .iter()
+                .filter(|person| {
+                    known
+                        .get(&name(person))
+                        .is_some_and(|known| !same(known, person))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Describes the changes in a sentence like `3 Personen hinzugefügt, 1 entfernt`.
+    pub fn summary(&self) -> String {
+        let counts = [
+            (self.added.len(), "hinzugefügt"),
+            (self.removed.len(), "entfernt"),
+            (self.changed.len(), "geändert"),
+        ];
+        counts
+            .into_iter()
+            .filter(|(count, _)| *count > 0)
+            .enumerate()
+            .map(|(position, (count, what))| match (position, count) {
+                (0, 1) => format!("1 Person {what}"),
+                (0, _) => format!("{count} Personen {what}"),
+                _ => format!("{count} {what}"),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn name(person: &Person) -> (&str, &str) {
+    (&person.first_name, &person.last_name)
+}
+
+/// Returns whether the fields read from the source are the same.
+fn same(a: &Person, b: &Person) -> bool {
+    a.birthday == b.birthday
+        && a.gender == b.gender
+        && a.image_url == b.image_url
+        && a.contact_url == b.contact_url
+        && a.color == b.color
+        && a.notes == b.notes
+        && a.department == b.department
+        && a.opt_out == b.opt_out
+}
@@ -0,0 +1,38 @@
+use crate::error::BirthdayError;
+use crate::person::Person;
+use crate::photo;
+use crate::redact::{Redacted, RedactedUrl};
+
+/// Reports the photos of `persons` on the disk or in the file that can not be read,
+/// failing if any can not or `skipped` rows of the source were invalid.
+///
+/// Photos on servers are not downloaded, the display reports them when it shows them.
+pub fn run(persons: &[Person], skipped: usize, quiet: bool) -> Result<(), BirthdayError> {
+    let mut unreadable = 0;
+    for person in persons {
+        for url in person.image_urls() {
+            let bytes = photo::decode_data_url(url).or_else(|| photo::read_file_url(url));
+            if let Some(Err(error)) = bytes {
+                unreadable += 1;
+                if !quiet {
+                    println!(
+                        "the photo {} of {} can not be read: {error}",
+                        RedactedUrl(url),
+                        Redacted(person)
+                    );
+                }
+            }
+        }
+    }
+
+    if !quiet {
+        println!(
+            "{} persons, {skipped} skipped rows, {unreadable} unreadable photos",
+            persons.len()
+        );
+    }
+    match skipped + unreadable {
+        0 => Ok(()),
+        problems => Err(BirthdayError::Data(format!("found {problems} problems"))),
+    }
+}
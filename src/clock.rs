@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+#[cfg(feature = "gui")]
+use chrono::NaiveDateTime;
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+
+/// The source of the current time of the window and the notifiers, replaced to show
+/// or notify the birthdays as if it was another day.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+
+    fn local(&self) -> DateTime<Local> {
+        self.now().with_timezone(&Local)
+    }
+}
+
+/// The clock of the computer.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Runs like the clock of the computer but on another day, so days still change at
+/// midnight.
+#[derive(Debug, Clone, Copy)]
+pub struct ShiftedClock {
+    offset: Duration,
+}
+
+impl ShiftedClock {
+    /// Returns a clock on which today is `date`.
+    pub fn starting_on(date: NaiveDate) -> Self {
+        Self {
+            offset: date - Utc::now().date_naive(),
+        }
+    }
+}
+
+impl Clock for ShiftedClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.offset
+    }
+}
+
+/// A clock that always returns the same time.
+#[cfg(test)]
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// Returns how long it takes until the next local or utc midnight.
+///
+/// The dates of the birthdays change at utc midnight while the schedules of the screen
+/// use the local time.
+#[cfg(feature = "gui")]
+pub fn until_midnight(clock: &dyn Clock) -> std::time::Duration {
+    let next = |now: NaiveDateTime| {
+        let midnight = now
+            .date()
+            .succ_opt()
+            .and_then(|tomorrow| tomorrow.and_hms_opt(0, 0, 0))
+            .expect("the end of time is not reached yet");
+        midnight - now
+    };
+    next(clock.now().naive_utc())
+        .min(next(clock.local().naive_local()))
+        .to_std()
+        .unwrap_or(std::time::Duration::ZERO)
+}
+
+/// Returns a clock on which today is `date` given with `--date`, the one of the
+/// computer without.
+pub fn for_date(date: Option<NaiveDate>) -> Arc<dyn Clock> {
+    match date {
+        Some(date) => Arc::new(ShiftedClock::starting_on(date)),
+        None => Arc::new(SystemClock),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::date;
+
+    use chrono::TimeZone;
+
+    #[test]
+    fn today_is_the_date_in_utc() {
+        let clock = FixedClock(Utc.with_ymd_and_hms(2024, 2, 29, 23, 30, 0).unwrap());
+        assert_eq!(clock.today(), date(2024, 2, 29));
+    }
+
+    #[test]
+    fn shifted_clock_starts_on_the_date() {
+        let day = date(2024, 2, 29);
+        assert_eq!(ShiftedClock::starting_on(day).today(), day);
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn counts_down_to_midnight() {
+        let clock = FixedClock(Utc.with_ymd_and_hms(2024, 2, 28, 23, 59, 0).unwrap());
+        let remaining = until_midnight(&clock);
+        assert!(remaining > std::time::Duration::ZERO);
+        assert!(remaining <= std::time::Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,78 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Returns `text` composed to NFC, so an "ü" typed as "u" and a combining diaeresis
+/// equals the single character "ü".
+pub fn normalize(text: &str) -> String {
+    text.nfc().collect()
+}
+
+/// Returns `text` in lower case without accents and with "ß" as "ss", the way German
+/// dictionaries (DIN 5007) compare words.
+fn fold(text: &str) -> String {
+    text.nfd()
+        .filter(|&c| !is_combining_mark(c))
+        .flat_map(char::to_lowercase)
+        .map(|c| {
+            if c == 'ß' {
+                String::from("ss")
+            } else {
+                c.into()
+            }
+        })
+        .collect()
+}
+
+/// Sorts names like a German phone book: "Ärzte" before "Bernd", ties broken by the
+/// accents and the case.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SortKey {
+    folded: String,
+    text: String,
+}
+
+impl SortKey {
+    pub fn new(text: &str) -> Self {
+        let text = normalize(text);
+        Self {
+            folded: fold(&text),
+            text,
+        }
+    }
+}
+
+/// Returns whether `text` contains `query` ignoring the case, accents and the
+/// normalization of both, e.g. "muller" is found in "Müller".
+#[cfg(feature = "gui")]
+pub fn contains(text: &str, query: &str) -> bool {
+    fold(text).contains(&fold(query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decomposed_names_are_composed() {
+        assert_eq!(normalize("Mu\u{308}ller"), "Müller");
+        assert_eq!(SortKey::new("Mu\u{308}ller"), SortKey::new("Müller"));
+    }
+
+    #[test]
+    fn sorts_umlauts_with_their_base_letter() {
+        let mut names = ["Zoe", "Bernd", "Ärzte", "Anna", "Strauß", "Strauss"];
+        names.sort_by_key(|name| SortKey::new(name));
+        assert_eq!(
+            names,
+            ["Anna", "Ärzte", "Bernd", "Strauss", "Strauß", "Zoe"]
+        );
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn finds_names_without_accents() {
+        assert!(contains("Jürgen Mu\u{308}ller", "müller"));
+        assert!(contains("Jürgen Müller", "MULLER"));
+        assert!(!contains("Jürgen Müller", "miller"));
+    }
+}
@@ -0,0 +1,414 @@
+use crate::error::BirthdayError;
+use crate::keymap::{Action, Binding};
+use crate::person::{Layout, PhotoFit};
+use crate::source;
+
+use std::collections::HashMap;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+use serde::Deserialize;
+use toml::value::Table;
+use toml::Value;
+
+/// Parses the time of day a notifier fires at like "08:00".
+mod daily_time {
+    use chrono::NaiveTime;
+    use serde::{self, Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<NaiveTime>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        NaiveTime::parse_from_str(&s, "%H:%M")
+            .map(Some)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Settings read from the toml file given with `--config`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub outlook: Option<OutlookConfig>,
+    pub sms: Option<SmsConfig>,
+    pub signal: Option<SignalConfig>,
+    pub home_assistant: Option<HomeAssistantConfig>,
+    pub weekly: Option<WeeklyConfig>,
+    pub workdays: Option<WorkdaysConfig>,
+    pub display: DisplayConfig,
+    /// Replaces the default keys of these actions.
+    pub keys: HashMap<Action, Binding>,
+    /// Only the persons of these departments are shown and notified about, all if empty.
+    pub departments: Vec<String>,
+    /// Settings replacing the ones above for the screens started with `--profile`.
+    pub profile: HashMap<String, toml::Value>,
+}
+
+/// Settings of the gui overriding the command line that are applied again when the file changes.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    pub theme: Option<ThemeName>,
+    /// The template of `--greeting-url`.
+    pub greeting_url: Option<String>,
+    /// The days of `--look-ahead`.
+    pub look_ahead: Option<u64>,
+    /// Whether the next birthday is shown like with `--hero`.
+    pub hero: Option<bool>,
+    /// The factor of `--scale`.
+    pub scale: Option<f64>,
+    /// Whether text and cards fill the window like with `--auto-scale`.
+    pub auto_scale: Option<bool>,
+    /// The smallest factor of `auto_scale`, 0.5 by default.
+    pub min_scale: Option<f64>,
+    /// The largest factor of `auto_scale`, 4 by default.
+    pub max_scale: Option<f64>,
+    /// The layout of `--layout`.
+    pub layout: Option<Layout>,
+    /// The fit of `--photo-fit`.
+    pub photo_fit: Option<PhotoFit>,
+    /// The fraction of `--photo-height`.
+    pub photo_height: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    Light,
+    Dark,
+    Eink,
+    HighContrast,
+}
+
+/// App registration used to synchronize the birthdays to an Outlook calendar.
+///
+/// The section is also read without the network feature so the same config file works.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(not(feature = "network"), allow(dead_code))]
+pub struct OutlookConfig {
+    pub tenant_id: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// The user or shared mailbox owning the calendar.
+    pub user: String,
+    /// The id of the calendar, the default calendar of the user is used if missing.
+    pub calendar: Option<String>,
+}
+
+/// Account of a Twilio compatible sms gateway for the daily notifications.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(not(feature = "network"), allow(dead_code))]
+pub struct SmsConfig {
+    /// The url messages are posted to, defaults to the Twilio api of the account.
+    pub endpoint: Option<String>,
+    pub account_sid: String,
+    pub auth_token: String,
+    /// The phone number the messages are sent from.
+    pub from: String,
+    pub recipients: Vec<SmsRecipient>,
+    /// The local time of day `notify --daemon` fires at, right after midnight if missing.
+    #[serde(default, deserialize_with = "daily_time::deserialize")]
+    pub at: Option<NaiveTime>,
+    /// How often a failed notification is sent again by the daemon.
+    pub retries: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(not(feature = "network"), allow(dead_code))]
+pub struct SmsRecipient {
+    /// The phone number of the recipient.
+    pub to: String,
+    /// Only notify about these persons given by first and last name, e.g. the direct reports.
+    #[serde(default)]
+    pub persons: Vec<String>,
+}
+
+/// Signal group the daily notifications are sent to with signal-cli.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignalConfig {
+    /// The phone number of the account registered with signal-cli.
+    pub account: String,
+    /// The base64 id of the group as listed by `signal-cli listGroups`.
+    pub group: String,
+    /// The signal-cli executable, searched in the path by default.
+    pub command: Option<String>,
+    /// The local time of day `notify --daemon` fires at, right after midnight if missing.
+    #[serde(default, deserialize_with = "daily_time::deserialize")]
+    pub at: Option<NaiveTime>,
+    /// How often a failed notification is sent again by the daemon.
+    pub retries: Option<u32>,
+}
+
+/// MQTT broker of Home Assistant the birthdays of the day are published to.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HomeAssistantConfig {
+    pub host: String,
+    /// Defaults to 1883, connections are not encrypted.
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Defaults to "homeassistant" like in Home Assistant.
+    pub discovery_prefix: Option<String>,
+    /// The local time of day `notify --daemon` fires at, right after midnight if missing.
+    #[serde(default, deserialize_with = "daily_time::deserialize")]
+    pub at: Option<NaiveTime>,
+    /// How often a failed notification is sent again by the daemon.
+    pub retries: Option<u32>,
+}
+
+/// Mail relay and recipients of the weekly digest of the birthdays of the coming week.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeeklyConfig {
+    pub host: String,
+    /// Defaults to 25 without encryption, 587 with STARTTLS and 465 with TLS.
+    pub port: Option<u16>,
+    /// How the connection to the relay is encrypted, credentials need encryption.
+    #[serde(default)]
+    pub encryption: Encryption,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// The address the digest is sent from.
+    pub from: String,
+    pub to: Vec<String>,
+    /// Defaults to "Geburtstage nächste Woche".
+    pub subject: Option<String>,
+    /// The text of the mail, "{birthdays}" is replaced by a line per birthday.
+    pub template: Option<String>,
+    /// The day the digest is sent on, monday if missing.
+    pub weekday: Option<Weekday>,
+    /// The local time of day `notify --daemon` fires at, right after midnight if missing.
+    #[serde(default, deserialize_with = "daily_time::deserialize")]
+    pub at: Option<NaiveTime>,
+    /// How often a failed notification is sent again by the daemon.
+    pub retries: Option<u32>,
+}
+
+/// The encryption of the connection to a mail relay.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Encryption {
+    /// Plain text, e.g. for the relay of the office network.
+    #[default]
+    None,
+    /// Upgrades the connection with STARTTLS and fails if the relay does not offer it.
+    Starttls,
+    /// Connects with TLS from the start.
+    Tls,
+}
+
+/// Moves the notifications of birthdays on weekends and holidays to a workday, the
+/// display still shows them on the real date.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkdaysConfig {
+    pub shift: Shift,
+    /// Days off besides saturday and sunday, "dd.mm." every year or "dd.mm.YYYY" once.
+    #[serde(default)]
+    pub holidays: Vec<Holiday>,
+}
+
+/// The workday birthdays on days off are announced on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Shift {
+    /// The last workday before, e.g. on friday for the weekend.
+    Preceding,
+    /// The first workday after, e.g. on monday for the weekend.
+    Following,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Holiday {
+    /// The day and month of a holiday every year.
+    Yearly(u32, u32),
+    Once(NaiveDate),
+}
+
+impl TryFrom<String> for Holiday {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if let Ok(date) = NaiveDate::parse_from_str(&value, "%d.%m.%Y") {
+            return Ok(Self::Once(date));
+        }
+        // any leap year accepts every day and month
+        NaiveDate::parse_from_str(&format!("{value}2000"), "%d.%m.%Y")
+            .map(|date| Self::Yearly(date.day(), date.month()))
+            .map_err(|_| format!("invalid holiday \"{value}\", expected dd.mm. or dd.mm.YYYY"))
+    }
+}
+
+impl Holiday {
+    pub fn is(self, date: NaiveDate) -> bool {
+        match self {
+            Self::Yearly(day, month) => date.day() == day && date.month() == month,
+            Self::Once(holiday) => date == holiday,
+        }
+    }
+}
+
+/// Reads the configuration at `path` with the settings of the section `[profile.<name>]`
+/// of `profile` replacing the ones of the file.
+pub fn load(path: &Path, profile: Option<&str>) -> Result<Config, BirthdayError> {
+    let content = fs::read_to_string(path).map_err(|error| BirthdayError::io(path, error))?;
+    // the whole file is checked first so errors have a line number
+    let config: Config = toml::from_str(&content)
+        .map_err(|error| BirthdayError::Data(format!("error in {}: {error}", path.display())))?;
+    let Some(name) = profile else {
+        return Ok(config);
+    };
+
+    let mut table: Table = toml::from_str(&content)
+        .map_err(|error| BirthdayError::Data(format!("error in {}: {error}", path.display())))?;
+    table.remove("profile");
+    let overrides = config
+        .profile
+        .get(name)
+        .and_then(Value::as_table)
+        .ok_or_else(|| {
+            BirthdayError::Data(format!("profile {name} not found in {}", path.display()))
+        })?;
+    merge(&mut table, overrides.clone());
+    Value::Table(table).try_into().map_err(|error| {
+        BirthdayError::Data(format!(
+            "error in profile {name} of {}: {error}",
+            path.display()
+        ))
+    })
+}
+
+/// Replaces the values of `table` with the ones of `overrides`, tables key by key.
+fn merge(table: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(value)) => merge(base, value),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Returns whether the config file given with `--config` is downloaded from a web server.
+pub fn is_url(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// Returns the directory of the user the display keeps its cached files in, e.g. the
+/// downloaded config, the photos and the unpacked bundles.
+pub fn cache_dir() -> Option<PathBuf> {
+    let directory = std::env::var_os("XDG_CACHE_HOME")
+        .or_else(|| std::env::var_os("LOCALAPPDATA"))
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    Some(directory.join("birthday_display"))
+}
+
+/// Creates `dir` with its parents so only the user can open it, so other users can
+/// neither read nor replace the files in it.
+pub fn create_private_dir(dir: &Path) -> Result<(), BirthdayError> {
+    #[cfg(unix)]
+    let result = {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .and_then(|()| fs::set_permissions(dir, fs::Permissions::from_mode(0o700)))
+    };
+    #[cfg(not(unix))]
+    let result = fs::create_dir_all(dir);
+    result.map_err(|error| BirthdayError::io(dir, error))
+}
+
+/// Returns the file the config downloaded from a web server is kept in without
+/// `--config-cache`, in the [`cache_dir`] of the user so other users can not replace it.
+#[cfg(feature = "network")]
+pub fn default_cache() -> Result<PathBuf, BirthdayError> {
+    cache_dir()
+        .map(|directory| directory.join("config.toml"))
+        .ok_or_else(|| {
+            BirthdayError::Other(String::from(
+                "no cache directory found for the downloaded config, pass --config-cache",
+            ))
+        })
+}
+
+/// Downloads the config file at `url` into `cache`, keeping the copy of the last start
+/// if the server can not be reached or the file has errors.
+#[cfg(feature = "network")]
+pub fn fetch(url: &str, cache: &Path, quiet: bool) -> Result<(), BirthdayError> {
+    let download = async {
+        let response = reqwest::Client::new()
+            .get(url)
+            .timeout(std::time::Duration::from_secs(30))
+            .send()
+            .await?
+            .error_for_status()?;
+        response.text().await
+    };
+    let result = crate::runtime::current_thread()?
+        .block_on(download)
+        .map_err(BirthdayError::from)
+        .and_then(|content| {
+            // a broken file on the server must not replace a working copy
+            toml::from_str::<Config>(&content)
+                .map(|_| content)
+                .map_err(|error| BirthdayError::Data(format!("error in {url}: {error}")))
+        });
+    match result {
+        Ok(content) => {
+            if let Some(directory) = cache.parent().filter(|parent| !parent.exists()) {
+                create_private_dir(directory)?;
+            }
+            source::replace_file(cache, content.as_bytes())
+        }
+        Err(error) if cache.exists() => {
+            if !quiet {
+                println!("{error}, using the copy in {}", cache.display());
+            }
+            Ok(())
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// A config file that is read again after it was changed.
+pub struct Watch {
+    path: PathBuf,
+    profile: Option<String>,
+    modified: Option<SystemTime>,
+}
+
+impl Watch {
+    pub fn new(path: &Path, profile: Option<&str>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            profile: profile.map(str::to_string),
+            modified: None,
+        }
+    }
+
+    pub fn changed(&self) -> bool {
+        source::modified(&self.path) != self.modified
+    }
+
+    pub fn load(&mut self) -> Result<Config, BirthdayError> {
+        self.modified = source::modified(&self.path);
+        load(&self.path, self.profile.as_deref())
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{instance, Message};
+
+use std::io;
+use std::net::{Ipv4Addr, TcpListener};
+use std::sync::Arc;
+
+use iced::{subscription, Subscription};
+
+/// Blocks until a command is received on `listener`.
+fn receive_command(listener: &TcpListener) -> Option<Message> {
+    let (stream, _) = listener.accept().ok()?;
+    stream
+        .set_read_timeout(Some(instance::COMMAND_TIMEOUT))
+        .ok()?;
+    stream
+        .set_write_timeout(Some(instance::COMMAND_TIMEOUT))
+        .ok()?;
+    instance::answer(stream)
+}
+
+/// Local tcp port receiving the same commands as the socket of the instance.
+#[derive(Clone)]
+pub struct ControlPort(Arc<TcpListener>);
+
+impl ControlPort {
+    /// Listens on `port` of the loopback interface only.
+    pub fn bind(port: u16) -> io::Result<Self> {
+        Ok(Self(Arc::new(TcpListener::bind((
+            Ipv4Addr::LOCALHOST,
+            port,
+        ))?)))
+    }
+
+    /// Subscribes to the commands sent to the port.
+    pub fn commands(&self) -> Subscription<Message> {
+        struct Commands;
+
+        subscription::unfold(
+            std::any::TypeId::of::<Commands>(),
+            self.0.clone(),
+            |listener| async move {
+                let blocking_listener = listener.clone();
+                let message =
+                    tokio::task::spawn_blocking(move || receive_command(&blocking_listener))
+                        .await
+                        .ok()
+                        .flatten();
+                (message, listener)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataMessage;
+
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    #[test]
+    fn a_silent_client_does_not_block_the_next_one() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let _silent = TcpStream::connect(address).unwrap();
+        let mut client = TcpStream::connect(address).unwrap();
+        client.write_all(b"reload\n").unwrap();
+
+        assert!(receive_command(&listener).is_none());
+        assert!(matches!(
+            receive_command(&listener),
+            Some(Message::Data(DataMessage::Reload))
+        ));
+        let mut reply = String::new();
+        client.read_to_string(&mut reply).unwrap();
+        assert_eq!(reply, "ok\n");
+    }
+}
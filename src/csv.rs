@@ -1,7 +1,22 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::BirthdayError;
 use crate::person::Person;
+use crate::redact;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::source::{self, PersonSource};
 
-use std::error::Error;
-use std::path::PathBuf;
+use std::borrow::Cow;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+use csv::ByteRecord;
 
 // add parsing for custom date format
 // https://serde.rs/custom-date-format.html
@@ -9,7 +24,7 @@ pub mod custom_date_format {
     use chrono::NaiveDate;
     use serde::{self, Deserialize, Deserializer};
 
-    const FORMAT: &str = "%d.%m.%Y";
+    pub const FORMAT: &str = "%d.%m.%Y";
 
     pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
     where
@@ -20,22 +35,537 @@ pub mod custom_date_format {
     }
 }
 
-pub fn get_persons(path: &PathBuf, quiet: bool) -> Result<Vec<Person>, Box<dyn Error>> {
+/// The number of columns that are interpreted as fields of [`Person`].
+const KNOWN_COLUMNS: usize = 10;
+
+/// The number of columns that are always written, optional columns after them are
+/// only written if they are not empty.
+#[cfg(not(target_arch = "wasm32"))]
+const REQUIRED_COLUMNS: usize = 5;
+
+/// The characters of the bytes 0x80 to 0x9f in Windows-1252, the other bytes are the
+/// same as in Latin-1.
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+];
+
+/// The byte order mark of utf-8, e.g. written by Excel.
+const BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// The number of records of the file that is parsed at the moment or was parsed last.
+static PARSED: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns how many records of the file that is parsed at the moment were read so far,
+/// e.g. to show the progress of loading a large file.
+pub fn parsed() -> usize {
+    PARSED.load(Ordering::Relaxed)
+}
+
+/// Everything needed to write a parsed record back unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct SourceRecord {
+    /// The verbatim bytes of the record or `None` if the person was changed.
+    pub(crate) text: Option<Vec<u8>>,
+    /// The line terminator following the record.
+    pub(crate) terminator: Vec<u8>,
+    /// Fields after the known columns that are not interpreted.
+    pub(crate) extra_fields: Vec<Vec<u8>>,
+}
+
+/// Parts of a csv file that could not be parsed as a [`Person`].
+///
+/// Every part is stored with the index of the person it precedes so the
+/// file can be written back with the original order.
+#[derive(Debug, Default)]
+pub struct UnparsedRecords(Vec<(usize, Vec<u8>)>);
+
+impl UnparsedRecords {
+    /// Returns the number of parts that are not just empty lines.
+    pub fn skipped(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|(_, bytes)| !bytes.iter().all(u8::is_ascii_whitespace))
+            .count()
+    }
+}
+
+fn split_terminator(bytes: &[u8]) -> usize {
+    bytes.len()
+        - bytes
+            .iter()
+            .rev()
+            .take_while(|&&byte| byte == b'\n' || byte == b'\r')
+            .count()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn get_persons(
+    path: &Path,
+    quiet: bool,
+) -> Result<(Vec<Person>, UnparsedRecords, Encoding), BirthdayError> {
+    let content = fs::read(path).map_err(|error| BirthdayError::io(path, error))?;
+    let (persons, unparsed) =
+        parse_persons(&content, quiet).map_err(|error| BirthdayError::csv(path, error))?;
+    Ok((persons, unparsed, Encoding::detect(&content)))
+}
+
+/// How a csv file is encoded, so it is written back the way it was read.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Encoding {
+    /// The file starts with the byte order mark of utf-8.
+    bom: bool,
+    /// The file is no valid utf-8 and was read as Windows-1252.
+    windows_1252: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Encoding {
+    /// Detects the encoding of `content` the same way as [`to_utf8`] converts it.
+    fn detect(content: &[u8]) -> Self {
+        let without_bom = content.strip_prefix(BOM);
+        Self {
+            bom: without_bom.is_some(),
+            windows_1252: std::str::from_utf8(without_bom.unwrap_or(content)).is_err(),
+        }
+    }
+
+    /// Encodes the utf-8 `content` like the file, returns the first character that
+    /// Windows-1252 does not have.
+    fn encode(self, content: Vec<u8>) -> Result<Vec<u8>, char> {
+        let mut encoded = if self.bom { BOM.to_vec() } else { Vec::new() };
+        if !self.windows_1252 {
+            encoded.extend(content);
+            return Ok(encoded);
+        }
+        for character in String::from_utf8_lossy(&content).chars() {
+            let byte = match WINDOWS_1252.iter().position(|&other| other == character) {
+                Some(index) => 0x80 + index as u8,
+                None => match u8::try_from(u32::from(character)) {
+                    Ok(byte) if !(0x80..=0x9f).contains(&byte) => byte,
+                    _ => return Err(character),
+                },
+            };
+            encoded.push(byte);
+        }
+        Ok(encoded)
+    }
+}
+
+/// Returns `content` without the byte order mark of utf-8, converting it from
+/// Windows-1252 if it is no valid utf-8, e.g. a file saved by Excel on Windows.
+///
+/// [`CsvFile`] writes converted files back in their encoding.
+fn to_utf8(content: &[u8]) -> Cow<'_, [u8]> {
+    let content = content.strip_prefix(BOM).unwrap_or(content);
+    if std::str::from_utf8(content).is_ok() {
+        return Cow::Borrowed(content);
+    }
+    let text: String = content
+        .iter()
+        .map(|&byte| match byte {
+            0x80..=0x9f => WINDOWS_1252[usize::from(byte - 0x80)],
+            _ => char::from(byte),
+        })
+        .collect();
+    Cow::Owned(text.into_bytes())
+}
+
+/// Parses the csv `content` the same way as [`get_persons`] reads a file.
+pub fn parse_persons(
+    content: &[u8],
+    quiet: bool,
+) -> Result<(Vec<Person>, UnparsedRecords), csv::Error> {
+    let content = to_utf8(content);
+    let content: &[u8] = &content;
     let mut reader = csv::ReaderBuilder::new()
         .has_headers(false)
-        .from_path(path)?;
+        .flexible(true)
+        .from_reader(content);
+
+    // the records are passed on as soon as the next one starts, so the file is read in
+    // a single pass without keeping another copy of it
+    let mut persons = Vec::new();
+    let mut unparsed = UnparsedRecords::default();
+    let mut pending: Option<(Option<Person>, Vec<u8>)> = None;
+    let mut record = ByteRecord::new();
+    let mut start = 0;
+    PARSED.store(0, Ordering::Relaxed);
+    while reader.read_byte_record(&mut record)? {
+        let end = reader.position().byte() as usize;
+        PARSED.fetch_add(1, Ordering::Relaxed);
 
-    Ok(reader
-        .deserialize()
-        .filter_map(|result| {
-            if let Err(error) = result {
+        // optional columns may be missing at the end of the record
+        while record.len() < KNOWN_COLUMNS {
+            record.push_field(b"");
+        }
+
+        let person = match record.deserialize::<Person>(None) {
+            Ok(mut person) => {
+                person.source.extra_fields = record
+                    .iter()
+                    .skip(KNOWN_COLUMNS)
+                    .map(<[u8]>::to_vec)
+                    .collect();
+                Some(person)
+            }
+            Err(error) => {
                 if !quiet {
-                    eprintln!("error reading line: {error:?}");
+                    if redact::log_pii() {
+                        eprintln!("error reading line: {error:?}");
+                    } else {
+                        // the message of the error may contain the invalid value
+                        let line = record.position().map_or(0, csv::Position::line);
+                        eprintln!("error reading line {line}, pass --log-pii for details");
+                    }
                 };
                 None
-            } else {
-                result.ok()
             }
-        })
-        .collect())
+        };
+
+        // the reader splits "\r\n" after the "\r" so line terminators are moved
+        // to the end of the preceding record
+        let mut bytes = &content[start..end];
+        if let Some((previous, mut previous_bytes)) = pending.take() {
+            let leading = bytes
+                .iter()
+                .take_while(|&&byte| byte == b'\n' || byte == b'\r')
+                .count();
+            previous_bytes.extend_from_slice(&bytes[..leading]);
+            bytes = &bytes[leading..];
+            add_record(&mut persons, &mut unparsed, previous, previous_bytes);
+        }
+        pending = Some((person, bytes.to_vec()));
+        start = end;
+    }
+    let (person, mut bytes) = pending.unwrap_or_default();
+    bytes.extend_from_slice(&content[start..]);
+    add_record(&mut persons, &mut unparsed, person, bytes);
+
+    Ok((persons, unparsed))
+}
+
+/// Adds the verbatim `bytes` of a record to `persons` or `unparsed` if it is no `person`.
+fn add_record(
+    persons: &mut Vec<Person>,
+    unparsed: &mut UnparsedRecords,
+    person: Option<Person>,
+    bytes: Vec<u8>,
+) {
+    if bytes.is_empty() {
+        return;
+    }
+    match person {
+        Some(mut person) => {
+            person.source.terminator = bytes[split_terminator(&bytes)..].to_vec();
+            person.source.text = Some(bytes);
+            persons.push(person);
+        }
+        None => unparsed.0.push((persons.len(), bytes)),
+    }
+}
+
+/// Serializes a changed or new `person` including its extra fields.
+#[cfg(not(target_arch = "wasm32"))]
+fn serialize_person(person: &Person) -> Result<Vec<u8>, csv::Error> {
+    let birthday = person
+        .birthday
+        .format(custom_date_format::FORMAT)
+        .to_string();
+    let gender = person.gender.to_string();
+    let opt_out: &[u8] = if person.opt_out { b"x" } else { b"" };
+    let mut fields = vec![
+        person.last_name.as_bytes(),
+        person.first_name.as_bytes(),
+        birthday.as_bytes(),
+        gender.as_bytes(),
+        person.image_url.as_deref().unwrap_or_default().as_bytes(),
+        person.contact_url.as_deref().unwrap_or_default().as_bytes(),
+        person.color.as_deref().unwrap_or_default().as_bytes(),
+        person.notes.as_deref().unwrap_or_default().as_bytes(),
+        person.department.as_deref().unwrap_or_default().as_bytes(),
+        opt_out,
+    ];
+    if person.source.extra_fields.is_empty() {
+        while fields.len() > REQUIRED_COLUMNS && fields.last().is_some_and(|field| field.is_empty())
+        {
+            fields.pop();
+        }
+    }
+
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_writer(Vec::new());
+    writer.write_record(
+        fields
+            .into_iter()
+            .chain(person.source.extra_fields.iter().map(Vec::as_slice)),
+    )?;
+
+    let mut bytes = writer
+        .into_inner()
+        .map_err(|error| io::Error::new(error.error().kind(), error.error().to_string()))?;
+    bytes.truncate(split_terminator(&bytes));
+    Ok(bytes)
+}
+
+/// Serializes `persons` in the same format that is read by [`parse_persons`].
+///
+/// Unchanged persons and `unparsed` records are written back verbatim so the
+/// quoting, unknown columns and order of the original file are preserved.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn serialize_persons(
+    persons: &[Person],
+    unparsed: &UnparsedRecords,
+) -> Result<Vec<u8>, csv::Error> {
+    let mut output: Vec<u8> = Vec::new();
+    let default_terminator: &[u8] = match persons
+        .iter()
+        .find(|person| !person.source.terminator.is_empty())
+    {
+        Some(person) => &person.source.terminator,
+        None => b"\n",
+    };
+
+    let mut push = |bytes: &[u8]| {
+        if split_terminator(&output) == output.len() && !output.is_empty() {
+            output.extend_from_slice(default_terminator);
+        }
+        output.extend_from_slice(bytes);
+    };
+
+    let mut remaining = unparsed.0.iter().peekable();
+    for (index, person) in persons.iter().enumerate() {
+        while let Some((_, bytes)) = remaining.next_if(|(before, _)| *before <= index) {
+            push(bytes);
+        }
+
+        match &person.source.text {
+            Some(text) => push(text),
+            None => {
+                let mut bytes = serialize_person(person)?;
+                if person.source.terminator.is_empty() {
+                    bytes.extend_from_slice(default_terminator);
+                } else {
+                    bytes.extend_from_slice(&person.source.terminator);
+                }
+                push(&bytes);
+            }
+        }
+    }
+    for (_, bytes) in remaining {
+        push(bytes);
+    }
+
+    Ok(output)
+}
+
+/// A csv file that is written back without losing the records that could not be parsed.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct CsvFile {
+    path: PathBuf,
+    unparsed: UnparsedRecords,
+    encoding: Encoding,
+    modified: Option<SystemTime>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CsvFile {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            unparsed: UnparsedRecords::default(),
+            encoding: Encoding::default(),
+            modified: None,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PersonSource for CsvFile {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&mut self, quiet: bool) -> Result<Vec<Person>, BirthdayError> {
+        self.modified = source::modified(&self.path);
+        let (persons, unparsed, encoding) = get_persons(&self.path, quiet)?;
+        self.unparsed = unparsed;
+        self.encoding = encoding;
+        Ok(persons)
+    }
+
+    fn changed(&self) -> bool {
+        source::modified(&self.path) != self.modified
+    }
+
+    fn skipped(&self) -> usize {
+        self.unparsed.skipped()
+    }
+
+    fn save(&mut self, persons: &[Person]) -> Result<(), BirthdayError> {
+        let content = serialize_persons(persons, &self.unparsed)
+            .map_err(|error| BirthdayError::csv(&self.path, error))?;
+        let content = self.encoding.encode(content).map_err(|character| {
+            BirthdayError::Data(format!(
+                "{} is saved in Windows-1252, which has no \"{character}\"",
+                self.name()
+            ))
+        })?;
+        source::replace_file(&self.path, &content)?;
+        self.modified = source::modified(&self.path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::date;
+    use crate::index::{BirthdayIndex, MilestoneIndex};
+
+    use std::time::{Duration, Instant};
+
+    /// Generates a file with `count` persons whose birthdays are spread over the year.
+    fn rows(count: usize) -> Vec<u8> {
+        let mut content = Vec::new();
+        for index in 0..count {
+            let line = format!(
+                "Last{index},First{index},{:02}.{:02}.{},{},https://example.com/{index}.jpg\n",
+                index % 28 + 1,
+                index % 12 + 1,
+                1940 + index % 80,
+                if index % 2 == 0 { 'f' } else { 'm' },
+            );
+            content.extend_from_slice(line.as_bytes());
+        }
+        content
+    }
+
+    #[test]
+    fn indexes_every_loaded_person() {
+        let content = rows(1_000);
+
+        let (persons, unparsed) = parse_persons(&content, true).unwrap();
+        let index = BirthdayIndex::new(&persons);
+        let milestones = MilestoneIndex::new(&persons, Some(100));
+
+        assert_eq!(persons.len(), 1_000);
+        assert_eq!(unparsed.skipped(), 0);
+        assert_eq!(
+            index.between(date(2023, 1, 1), date(2024, 1, 1)).len(),
+            1_000
+        );
+        assert!(milestones.contains(persons[0].birthday + chrono::Days::new(100)));
+    }
+
+    /// Run with `cargo test --release -- --ignored`, debug builds are much slower.
+    #[test]
+    #[ignore]
+    fn loads_100k_persons_quickly() {
+        let content = rows(100_000);
+
+        let start = Instant::now();
+        let (persons, _) = parse_persons(&content, true).unwrap();
+        BirthdayIndex::new(&persons);
+        MilestoneIndex::new(&persons, Some(100));
+        let elapsed = start.elapsed();
+
+        assert_eq!(persons.len(), 100_000);
+        assert!(elapsed < Duration::from_secs(2), "loading took {elapsed:?}");
+    }
+
+    #[test]
+    fn keeps_every_byte_of_the_file() {
+        let content =
+            b"\r\nDoe,Jane,01.02.1990,f\r\ninvalid\r\n\r\nDoe,John,03.04.1985,m,,,,,,,extra\n";
+        let (persons, unparsed) = parse_persons(content, true).unwrap();
+
+        assert_eq!(persons.len(), 2);
+        assert_eq!(unparsed.skipped(), 1);
+        assert_eq!(persons[0].source.terminator, b"\r\n");
+        assert_eq!(persons[1].source.extra_fields, vec![b"extra".to_vec()]);
+        assert_eq!(serialize_persons(&persons, &unparsed).unwrap(), content);
+    }
+
+    #[test]
+    fn writes_an_unchanged_file_back_byte_for_byte() {
+        let content = b"\"Doe\",Jane,01.02.1990,f\r\n\
+                        \"Roe, Jr.\",\"Rick\",03.04.1985,m,,,\"orange\",\"says \"\"hi\"\"\"\r\n\
+                        # a comment\r\n\
+                        Poe,Edgar,19.01.1809,m,,,,,,,\"extra, column\"";
+        let (persons, unparsed) = parse_persons(content, true).unwrap();
+
+        assert_eq!(persons.len(), 3);
+        assert_eq!(serialize_persons(&persons, &unparsed).unwrap(), content);
+    }
+
+    #[test]
+    fn a_changed_row_keeps_its_extra_columns_and_quoting() {
+        let content = b"Doe,\"Smith, Jane\",01.02.1990,f,,,,,,,\"extra, column\",more\r\n\
+                        \"Roe\",Rick,03.04.1985,m\r\n";
+        let (mut persons, unparsed) = parse_persons(content, true).unwrap();
+        persons[0].first_name = String::from("Smith, Janet");
+        persons[0].source.text = None;
+
+        assert_eq!(
+            serialize_persons(&persons, &unparsed).unwrap(),
+            b"Doe,\"Smith, Janet\",01.02.1990,f,,,,,,,\"extra, column\",more\r\n\
+              \"Roe\",Rick,03.04.1985,m\r\n"
+        );
+    }
+
+    /// Writes `content` to a csv file of the test and returns it.
+    fn csv_file(name: &str, content: &[u8]) -> (PathBuf, CsvFile) {
+        let path = std::env::temp_dir().join(format!(
+            "birthday_display-test-{name}-{}.csv",
+            std::process::id()
+        ));
+        fs::write(&path, content).unwrap();
+        let file = CsvFile::new(&path);
+        (path, file)
+    }
+
+    #[test]
+    fn saves_in_the_encoding_of_the_file() {
+        for (name, content, expected) in [
+            (
+                "bom",
+                "\u{feff}Müller,Jörg,01.02.1990,m,\n".as_bytes(),
+                "\u{feff}Müller,Jörge,01.02.1990,m,\n".as_bytes(),
+            ),
+            (
+                "windows-1252",
+                b"M\xfcller,J\x80rg,01.02.1990,m,\n",
+                b"M\xfcller,J\x80rge,01.02.1990,m,\n",
+            ),
+        ] {
+            let (path, mut file) = csv_file(name, content);
+            let mut persons = file.load(true).unwrap();
+            persons[0].first_name.push('e');
+            persons[0].source.text = None;
+            file.save(&persons).unwrap();
+            let saved = fs::read(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(saved, expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn refuses_characters_windows_1252_does_not_have() {
+        let content = b"M\xfcller,J\xf6rg,01.02.1990,m\n";
+        let (path, mut file) = csv_file("no-windows-1252", content);
+        let mut persons = file.load(true).unwrap();
+        persons[0].first_name = String::from("Jörg 🎉");
+        persons[0].source.text = None;
+
+        let result = file.save(&persons);
+        let saved = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(BirthdayError::Data(_))));
+        assert_eq!(saved, content);
+    }
 }
@@ -0,0 +1,347 @@
+use crate::csv::custom_date_format;
+use crate::person::{Gender, InvalidPerson, Person};
+use crate::Message;
+
+use chrono::NaiveDate;
+
+use iced::widget::text_input::{self, Id};
+use iced::widget::{button, checkbox, column, row, scrollable, text, Column};
+use iced::{Alignment, Color, Element, Length};
+
+/// Updates of the admin editor.
+#[derive(Debug, Clone)]
+pub enum EditorMessage {
+    /// The content of the PIN input changed.
+    PinChanged(String),
+    /// The PIN input was submitted.
+    SubmitPin,
+    /// Start editing the person with the given index.
+    Edit(usize),
+    /// Start adding a new person.
+    New,
+    /// The content of a form input changed.
+    Changed(Field, String),
+    /// The opt-out of the person was toggled.
+    OptOut(bool),
+    /// Apply the form.
+    Save,
+    /// Drop the form without applying it.
+    Discard,
+}
+
+/// The inputs of the person form.
+#[derive(Debug, Clone, Copy)]
+pub enum Field {
+    LastName,
+    FirstName,
+    Birthday,
+    Gender,
+    ImageUrl,
+    ContactUrl,
+    Color,
+    Notes,
+    Department,
+}
+
+/// The unvalidated content of the person form.
+#[derive(Debug, Default)]
+struct Form {
+    /// Index of the edited person or `None` for a new person.
+    index: Option<usize>,
+    last_name: String,
+    first_name: String,
+    birthday: String,
+    gender: String,
+    image_url: String,
+    contact_url: String,
+    color: String,
+    notes: String,
+    department: String,
+    opt_out: bool,
+}
+
+impl Form {
+    fn from_person(index: usize, person: &Person) -> Self {
+        Self {
+            index: Some(index),
+            last_name: person.last_name.clone(),
+            first_name: person.first_name.clone(),
+            birthday: person
+                .birthday
+                .format(custom_date_format::FORMAT)
+                .to_string(),
+            gender: person.gender.to_string(),
+            image_url: person.image_url.clone().unwrap_or_default(),
+            contact_url: person.contact_url.clone().unwrap_or_default(),
+            color: person.color.clone().unwrap_or_default(),
+            notes: person.notes.clone().unwrap_or_default(),
+            department: person.department.clone().unwrap_or_default(),
+            opt_out: person.opt_out,
+        }
+    }
+
+    fn field_mut(&mut self, field: Field) -> &mut String {
+        match field {
+            Field::LastName => &mut self.last_name,
+            Field::FirstName => &mut self.first_name,
+            Field::Birthday => &mut self.birthday,
+            Field::Gender => &mut self.gender,
+            Field::ImageUrl => &mut self.image_url,
+            Field::ContactUrl => &mut self.contact_url,
+            Field::Color => &mut self.color,
+            Field::Notes => &mut self.notes,
+            Field::Department => &mut self.department,
+        }
+    }
+
+    fn to_person(&self) -> Result<Person, String> {
+        let last_name = self.last_name.trim();
+        let first_name = self.first_name.trim();
+        if last_name.is_empty() || first_name.is_empty() {
+            return Err(String::from("Vor- und Nachname dürfen nicht leer sein."));
+        }
+
+        let birthday = NaiveDate::parse_from_str(self.birthday.trim(), custom_date_format::FORMAT)
+            .map_err(|_| String::from("Das Geburtsdatum muss im Format TT.MM.JJJJ sein."))?;
+
+        let mut gender_chars = self.gender.trim().chars();
+        let gender = match (gender_chars.next(), gender_chars.next()) {
+            (Some(gender), None) => Gender::from(gender),
+            _ => {
+                return Err(String::from(
+                    "Das Geschlecht muss ein einzelnes Zeichen sein.",
+                ))
+            }
+        };
+
+        let optional = |value: &str| match value.trim() {
+            "" => None,
+            value => Some(value.to_string()),
+        };
+
+        Person::builder(last_name, first_name, birthday, gender)
+            .image_url(optional(&self.image_url))
+            .contact_url(optional(&self.contact_url))
+            .color(optional(&self.color))
+            .notes(optional(&self.notes))
+            .department(optional(&self.department))
+            .opt_out(self.opt_out)
+            .build()
+            .map_err(|error| match error {
+                InvalidPerson::EmptyName => {
+                    String::from("Vor- und Nachname dürfen nicht leer sein.")
+                }
+                InvalidPerson::Color(_) => {
+                    String::from("Die Farbe muss z.B. #ff8800 oder orange sein.")
+                }
+                InvalidPerson::ImageUrl(url) => {
+                    format!("Das Foto {url} muss z.B. mit https:// oder file:// beginnen.")
+                }
+                InvalidPerson::Url(url) => {
+                    format!("Der Link {url} muss z.B. mit https:// beginnen.")
+                }
+            })
+    }
+}
+
+/// State of the admin mode used to add or correct persons.
+#[derive(Debug)]
+pub struct Editor {
+    unlocked: bool,
+    pin_input: String,
+    form: Option<Form>,
+    status: Option<Result<String, String>>,
+}
+
+impl Editor {
+    /// Creates a new editor which is locked if a PIN is required.
+    pub fn new(pin: Option<&str>) -> Self {
+        Self {
+            unlocked: pin.is_none(),
+            pin_input: String::new(),
+            form: None,
+            status: None,
+        }
+    }
+
+    /// Returns the id of the input that should be focused when opening the editor.
+    pub fn input_id() -> Id {
+        Id::new("editor")
+    }
+
+    /// Shows the result of persisting the last change.
+    pub fn set_status(&mut self, status: Result<String, String>) {
+        self.status = Some(status);
+    }
+
+    /// Applies `message` to the editor state.
+    ///
+    /// Returns the index of the changed or added person after a successful save.
+    pub fn update(
+        &mut self,
+        message: EditorMessage,
+        persons: &mut Vec<Person>,
+        pin: Option<&str>,
+    ) -> Option<usize> {
+        match message {
+            EditorMessage::PinChanged(input) => self.pin_input = input,
+            EditorMessage::SubmitPin => {
+                if Some(self.pin_input.as_str()) == pin {
+                    self.unlocked = true;
+                    self.status = None;
+                } else {
+                    self.status = Some(Err(String::from("Falsche PIN.")));
+                }
+                self.pin_input.clear();
+            }
+            EditorMessage::Edit(index) if self.unlocked => {
+                if let Some(person) = persons.get(index) {
+                    self.form = Some(Form::from_person(index, person));
+                    self.status = None;
+                }
+            }
+            EditorMessage::New if self.unlocked => {
+                self.form = Some(Form::default());
+                self.status = None;
+            }
+            EditorMessage::Changed(field, value) => {
+                if let Some(form) = &mut self.form {
+                    *form.field_mut(field) = value;
+                }
+            }
+            EditorMessage::OptOut(opt_out) => {
+                if let Some(form) = &mut self.form {
+                    form.opt_out = opt_out;
+                }
+            }
+            EditorMessage::Save if self.unlocked => {
+                let form = self.form.as_ref()?;
+                let person = match form.to_person() {
+                    Ok(person) => person,
+                    Err(error) => {
+                        self.status = Some(Err(error));
+                        return None;
+                    }
+                };
+
+                let index = match form.index {
+                    Some(index) if index < persons.len() => {
+                        let old = std::mem::replace(&mut persons[index], person);
+                        if old.image_url == persons[index].image_url {
+                            persons[index].image_data = old.image_data;
+                        }
+                        // keep the unknown columns but serialize the changed fields
+                        persons[index].source = old.source;
+                        persons[index].source.text = None;
+                        index
+                    }
+                    _ => {
+                        persons.push(person);
+                        persons.len() - 1
+                    }
+                };
+
+                self.form = None;
+                return Some(index);
+            }
+            EditorMessage::Discard => {
+                self.form = None;
+                self.status = None;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Generates the admin screen for iced.
+    pub fn view<'a>(&'a self, persons: &'a [Person], error_color: Color) -> Element<'a, Message> {
+        let status: Element<Message> = match &self.status {
+            Some(Ok(info)) => text(info).size(20).into(),
+            Some(Err(error)) => text(error).size(20).style(error_color).into(),
+            None => text("").size(20).into(),
+        };
+
+        let content: Element<Message> = if !self.unlocked {
+            text_input::TextInput::new("PIN", &self.pin_input, |input| {
+                Message::Editor(EditorMessage::PinChanged(input))
+            })
+            .id(Self::input_id())
+            .password()
+            .on_submit(Message::Editor(EditorMessage::SubmitPin))
+            .size(30)
+            .padding(10)
+            .width(Length::Fixed(300.0))
+            .into()
+        } else if let Some(form) = &self.form {
+            let input = |placeholder: &str, value: &str, field: Field| {
+                text_input::TextInput::new(placeholder, value, move |input| {
+                    Message::Editor(EditorMessage::Changed(field, input))
+                })
+                .on_submit(Message::Editor(EditorMessage::Save))
+                .size(20)
+                .padding(10)
+                .width(Length::Fixed(600.0))
+            };
+
+            column![
+                input("Nachname", &form.last_name, Field::LastName).id(Self::input_id()),
+                input("Vorname", &form.first_name, Field::FirstName),
+                input("Geburtsdatum (TT.MM.JJJJ)", &form.birthday, Field::Birthday),
+                input("Geschlecht (m/w)", &form.gender, Field::Gender),
+                input("Bild-URL (optional)", &form.image_url, Field::ImageUrl),
+                input(
+                    "Kontakt-Link (optional)",
+                    &form.contact_url,
+                    Field::ContactUrl
+                ),
+                input("Farbe (optional, z.B. #ff8800)", &form.color, Field::Color),
+                input(
+                    "Notiz (optional, z.B. mag Schokokuchen)",
+                    &form.notes,
+                    Field::Notes
+                ),
+                input("Abteilung (optional)", &form.department, Field::Department),
+                checkbox("Geburtstag nicht anzeigen", form.opt_out, |opt_out| {
+                    Message::Editor(EditorMessage::OptOut(opt_out))
+                })
+                .width(Length::Fixed(600.0)),
+                row![
+                    button(text("Speichern")).on_press(Message::Editor(EditorMessage::Save)),
+                    button(text("Verwerfen")).on_press(Message::Editor(EditorMessage::Discard)),
+                ]
+                .spacing(15),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .into()
+        } else {
+            let entries: Vec<Element<Message>> = persons
+                .iter()
+                .enumerate()
+                .map(|(index, person)| {
+                    button(text(format!(
+                        "{}, {}",
+                        person.name(),
+                        person.birthday.format(custom_date_format::FORMAT)
+                    )))
+                    .on_press(Message::Editor(EditorMessage::Edit(index)))
+                    .width(Length::Fixed(600.0))
+                    .into()
+                })
+                .collect();
+
+            column![
+                button(text("Neue Person")).on_press(Message::Editor(EditorMessage::New)),
+                scrollable(Column::with_children(entries).spacing(5)).height(Length::Fill),
+            ]
+            .spacing(20)
+            .align_items(Alignment::Center)
+            .into()
+        };
+
+        column![text("Personen bearbeiten").size(30), content, status]
+            .spacing(20)
+            .align_items(Alignment::Center)
+            .into()
+    }
+}
@@ -0,0 +1,255 @@
+use crate::csv::{self, UnparsedRecords};
+use crate::error::BirthdayError;
+use crate::json;
+use crate::person::Person;
+use crate::source::{self, PersonSource};
+
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::SystemTime;
+
+/// The program a file is encrypted with.
+#[derive(Debug, Clone, Copy)]
+pub enum Tool {
+    Age,
+    Gpg {
+        /// Whether the file is ascii armored.
+        armor: bool,
+    },
+}
+
+impl Tool {
+    /// Returns the tool for the extension of `path`, e.g. "birthdays.csv.age".
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.extension().and_then(OsStr::to_str)? {
+            "age" => Some(Self::Age),
+            "gpg" => Some(Self::Gpg { armor: false }),
+            "asc" => Some(Self::Gpg { armor: true }),
+            _ => None,
+        }
+    }
+
+    fn program(self) -> &'static str {
+        match self {
+            Self::Age => "age",
+            Self::Gpg { .. } => "gpg",
+        }
+    }
+
+    /// Returns the arguments decrypting the file at `path` to the standard output.
+    fn decrypt_args<'a>(self, path: &'a Path, key: Option<&'a Path>) -> Vec<&'a OsStr> {
+        let mut args: Vec<&OsStr> = match self {
+            Self::Age => vec!["--decrypt".as_ref()],
+            Self::Gpg { .. } => vec!["--quiet".as_ref(), "--decrypt".as_ref()],
+        };
+        if let Some(key) = key {
+            args.extend(self.key_args(key));
+        }
+        args.push(path.as_os_str());
+        args
+    }
+
+    /// Returns the arguments encrypting the standard input with `key` to the standard output.
+    fn encrypt_args(self, key: &Path) -> Vec<&OsStr> {
+        let mut args: Vec<&OsStr> = match self {
+            // the file is encrypted to the recipient of the identity
+            Self::Age => vec!["--encrypt".as_ref()],
+            Self::Gpg { armor } => {
+                let mut args = vec!["--symmetric".as_ref(), "--output".as_ref(), "-".as_ref()];
+                if armor {
+                    args.push("--armor".as_ref());
+                }
+                args
+            }
+        };
+        args.extend(self.key_args(key));
+        args
+    }
+
+    fn key_args(self, key: &Path) -> Vec<&OsStr> {
+        match self {
+            Self::Age => vec!["--identity".as_ref(), key.as_os_str()],
+            Self::Gpg { .. } => vec![
+                "--batch".as_ref(),
+                "--yes".as_ref(),
+                "--pinentry-mode".as_ref(),
+                "loopback".as_ref(),
+                "--passphrase-file".as_ref(),
+                key.as_os_str(),
+            ],
+        }
+    }
+
+    /// Runs the tool with `args`, passing `input` to it, and returns its output.
+    fn run(self, args: &[&OsStr], input: Option<Vec<u8>>) -> Result<Vec<u8>, BirthdayError> {
+        // the passphrase is asked for on the terminal if no key is given
+        let mut child = Command::new(self.program())
+            .args(args)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::inherit()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|error| BirthdayError::io(Path::new(self.program()), error))?;
+
+        // writing in another thread keeps a full output pipe from blocking the tool
+        let writer = child
+            .stdin
+            .take()
+            .zip(input)
+            .map(|(mut stdin, input)| thread::spawn(move || stdin.write_all(&input)));
+        let output = child
+            .wait_with_output()
+            .map_err(|error| BirthdayError::io(Path::new(self.program()), error))?;
+        if let Some(writer) = writer {
+            writer
+                .join()
+                .expect("writing to a pipe does not panic")
+                .map_err(|error| BirthdayError::io(Path::new(self.program()), error))?;
+        }
+
+        if output.status.success() {
+            Ok(output.stdout)
+        } else {
+            Err(BirthdayError::Data(format!(
+                "{} exited with {}",
+                self.program(),
+                output.status
+            )))
+        }
+    }
+}
+
+/// A csv or json file encrypted with age or gpg that is only decrypted in memory.
+///
+/// Changes are only written back if the file can be encrypted again with `key`, an age
+/// identity or a file with the gpg passphrase.
+pub struct EncryptedFile {
+    path: PathBuf,
+    tool: Tool,
+    key: Option<PathBuf>,
+    /// Whether the decrypted content is a json array instead of csv.
+    json: bool,
+    unparsed: UnparsedRecords,
+    modified: Option<SystemTime>,
+}
+
+impl EncryptedFile {
+    pub fn new(path: &Path, tool: Tool, key: Option<&Path>) -> Self {
+        // the format is given by the extension before the one of the encryption
+        let json = path
+            .file_stem()
+            .map(Path::new)
+            .and_then(Path::extension)
+            .is_some_and(|extension| extension == "json");
+
+        Self {
+            path: path.to_path_buf(),
+            tool,
+            key: key.map(Path::to_path_buf),
+            json,
+            unparsed: UnparsedRecords::default(),
+            modified: None,
+        }
+    }
+}
+
+impl PersonSource for EncryptedFile {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&mut self, quiet: bool) -> Result<Vec<Person>, BirthdayError> {
+        self.modified = source::modified(&self.path);
+        let content = self
+            .tool
+            .run(
+                &self.tool.decrypt_args(&self.path, self.key.as_deref()),
+                None,
+            )
+            .map_err(|error| {
+                BirthdayError::Data(format!("error decrypting {}: {error}", self.path.display()))
+            })?;
+
+        if self.json {
+            json::parse_persons(&content).map_err(|error| {
+                BirthdayError::Data(format!("error in {}: {error}", self.path.display()))
+            })
+        } else {
+            let (persons, unparsed) = csv::parse_persons(&content, quiet)
+                .map_err(|error| BirthdayError::csv(&self.path, error))?;
+            self.unparsed = unparsed;
+            Ok(persons)
+        }
+    }
+
+    fn changed(&self) -> bool {
+        source::modified(&self.path) != self.modified
+    }
+
+    fn skipped(&self) -> usize {
+        self.unparsed.skipped()
+    }
+
+    fn save(&mut self, persons: &[Person]) -> Result<(), BirthdayError> {
+        let key = self
+            .key
+            .as_deref()
+            .ok_or_else(|| format!("{} can only be changed with --key-file", self.name()))?;
+        let content = if self.json {
+            json::serialize_persons(persons).map_err(|error| {
+                BirthdayError::Data(format!(
+                    "error serializing {}: {error}",
+                    self.path.display()
+                ))
+            })?
+        } else {
+            csv::serialize_persons(persons, &self.unparsed)
+                .map_err(|error| BirthdayError::csv(&self.path, error))?
+        };
+
+        let encrypted = self
+            .tool
+            .run(&self.tool.encrypt_args(key), Some(content))
+            .map_err(|error| {
+                BirthdayError::Data(format!("error encrypting {}: {error}", self.path.display()))
+            })?;
+        source::replace_file(&self.path, &encrypted)?;
+        self.modified = source::modified(&self.path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decrypts_with_the_tool_of_the_extension() {
+        let path = Path::new("birthdays.csv.asc");
+        let tool = Tool::detect(path).unwrap();
+
+        assert!(matches!(tool, Tool::Gpg { armor: true }));
+        assert_eq!(
+            tool.decrypt_args(path, Some(Path::new("passphrase"))),
+            [
+                "--quiet",
+                "--decrypt",
+                "--batch",
+                "--yes",
+                "--pinentry-mode",
+                "loopback",
+                "--passphrase-file",
+                "passphrase",
+                "birthdays.csv.asc",
+            ]
+        );
+        assert!(Tool::detect(Path::new("birthdays.csv")).is_none());
+    }
+}
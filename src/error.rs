@@ -0,0 +1,226 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use thiserror::Error;
+
+/// The exit code of errors without a more specific one, 2 is used by clap for invalid
+/// arguments.
+pub const EXIT_OTHER: u8 = 1;
+/// The exit code of unreadable or invalid files, rows, dates and config files.
+pub const EXIT_DATA: u8 = 3;
+/// The exit code of a window or graphics device that could not be opened.
+pub const EXIT_GUI: u8 = 4;
+/// The exit code of servers that could not be reached or refused a request, which may
+/// work when it is tried again.
+pub const EXIT_NETWORK: u8 = 5;
+
+/// An error of a command or the window, with the file, line or url it happened at.
+// the browser reads no files
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+#[derive(Debug, Error)]
+pub enum BirthdayError {
+    #[error("error accessing {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+
+    /// An invalid row or an unreadable csv file, `line` is `None` if the csv crate
+    /// does not know it.
+    #[error("error reading {}: {source}", path.display())]
+    Csv {
+        path: PathBuf,
+        line: Option<u64>,
+        source: csv::Error,
+    },
+
+    #[error("invalid date \"{value}\", expected {expected}")]
+    Date {
+        value: String,
+        expected: &'static str,
+    },
+
+    /// Invalid content of a file that could be read, e.g. a config file or the problems
+    /// found by `check`.
+    #[error("{0}")]
+    Data(String),
+
+    /// The url is in the message of reqwest already.
+    #[cfg(feature = "network")]
+    #[error("{source}")]
+    Http {
+        url: Option<String>,
+        source: reqwest::Error,
+    },
+
+    /// A server without http like a mail server.
+    #[error("{0}")]
+    Network(String),
+
+    #[cfg(feature = "gui")]
+    #[error("error showing the window: {0}")]
+    Gui(String),
+
+    #[error("{0}")]
+    Other(String),
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+impl BirthdayError {
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        Self::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn csv(path: &Path, source: csv::Error) -> Self {
+        Self::Csv {
+            path: path.to_path_buf(),
+            line: source.position().map(csv::Position::line),
+            source,
+        }
+    }
+
+    pub fn date(value: &str, expected: &'static str) -> Self {
+        Self::Date {
+            value: value.to_string(),
+            expected,
+        }
+    }
+
+    /// Returns the exit code of the process failing with this error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Io { .. } | Self::Csv { .. } | Self::Date { .. } | Self::Data(_) => EXIT_DATA,
+            #[cfg(feature = "network")]
+            Self::Http { .. } => EXIT_NETWORK,
+            Self::Network(_) => EXIT_NETWORK,
+            #[cfg(feature = "gui")]
+            Self::Gui(_) => EXIT_GUI,
+            Self::Other(_) => EXIT_OTHER,
+        }
+    }
+
+    /// Returns the error as json object for `--error-json`, e.g.
+    /// `{"exit_code":3,"kind":"csv","line":4,"message":"…","path":"persons.csv"}`.
+    pub fn to_json(&self) -> Value {
+        let (kind, mut context) = match self {
+            Self::Io { path, .. } => ("io", json!({ "path": path })),
+            Self::Csv { path, line, .. } => ("csv", json!({ "path": path, "line": line })),
+            Self::Date { value, .. } => ("date", json!({ "value": value })),
+            Self::Data(_) => ("data", json!({})),
+            #[cfg(feature = "network")]
+            Self::Http { url, source } => (
+                "http",
+                json!({ "url": url, "status": source.status().map(|status| status.as_u16()) }),
+            ),
+            Self::Network(_) => ("network", json!({})),
+            #[cfg(feature = "gui")]
+            Self::Gui(_) => ("gui", json!({})),
+            Self::Other(_) => ("other", json!({})),
+        };
+        context["kind"] = kind.into();
+        context["message"] = self.to_string().into();
+        context["exit_code"] = self.exit_code().into();
+        context
+    }
+}
+
+#[cfg(feature = "network")]
+impl From<reqwest::Error> for BirthdayError {
+    fn from(source: reqwest::Error) -> Self {
+        Self::Http {
+            url: source.url().map(ToString::to_string),
+            source,
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl From<iced::Error> for BirthdayError {
+    fn from(error: iced::Error) -> Self {
+        Self::Gui(error.to_string())
+    }
+}
+
+impl From<String> for BirthdayError {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+impl From<&str> for BirthdayError {
+    fn from(message: &str) -> Self {
+        Self::Other(message.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_errors_are_data_errors_with_their_line() {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(&b"Muster,Anna\nMuster\n"[..]);
+        let source = reader
+            .records()
+            .find_map(Result::err)
+            .expect("the second row is too short");
+        let error = BirthdayError::csv(Path::new("persons.csv"), source);
+
+        assert_eq!(error.exit_code(), EXIT_DATA);
+        let json = error.to_json();
+        assert_eq!(json["kind"], "csv");
+        assert_eq!(json["path"], "persons.csv");
+        assert_eq!(json["line"], 2);
+        assert_eq!(json["exit_code"], 3);
+        assert!(json["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("error reading persons.csv"));
+    }
+
+    #[test]
+    fn every_variant_has_the_exit_code_of_its_kind() {
+        let missing = io::Error::from(io::ErrorKind::NotFound);
+        // the errors of the network and the window are only added with their feature
+        #[cfg_attr(not(any(feature = "network", feature = "gui")), allow(unused_mut))]
+        let mut errors = vec![
+            (
+                BirthdayError::io(Path::new("persons.csv"), missing),
+                "io",
+                EXIT_DATA,
+            ),
+            (BirthdayError::date("31.02.", "DD.MM."), "date", EXIT_DATA),
+            (
+                BirthdayError::Data(String::from("broken")),
+                "data",
+                EXIT_DATA,
+            ),
+            (
+                BirthdayError::Network(String::from("refused")),
+                "network",
+                EXIT_NETWORK,
+            ),
+            (BirthdayError::from("unknown"), "other", EXIT_OTHER),
+        ];
+        #[cfg(feature = "network")]
+        errors.push((
+            BirthdayError::from(reqwest::Client::new().get("no url").build().unwrap_err()),
+            "http",
+            EXIT_NETWORK,
+        ));
+        #[cfg(feature = "gui")]
+        errors.push((
+            BirthdayError::Gui(String::from("no adapter")),
+            "gui",
+            EXIT_GUI,
+        ));
+
+        for (error, kind, exit_code) in errors {
+            assert_eq!(error.exit_code(), exit_code, "{error}");
+            assert_eq!(error.to_json()["kind"], kind);
+        }
+    }
+}
@@ -0,0 +1,422 @@
+use crate::clock::Clock;
+use crate::csv::{self, UnparsedRecords};
+use crate::error::BirthdayError;
+use crate::feed;
+use crate::index::BirthdayIndex;
+use crate::pdf::{self, Font, Page};
+use crate::person::{Gender, ImageScheme, Person};
+use crate::source;
+use crate::style;
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Months, NaiveDate};
+use clap::Subcommand;
+
+use iced_native::{Color, Theme};
+
+const MONTH_NAMES: [&str; 12] = [
+    "Januar",
+    "Februar",
+    "März",
+    "April",
+    "Mai",
+    "Juni",
+    "Juli",
+    "August",
+    "September",
+    "Oktober",
+    "November",
+    "Dezember",
+];
+
+const WEEKDAY_NAMES: [&str; 7] = [
+    "Montag",
+    "Dienstag",
+    "Mittwoch",
+    "Donnerstag",
+    "Freitag",
+    "Samstag",
+    "Sonntag",
+];
+
+const MALE_NAMES: [&str; 12] = [
+    "Lukas", "Jonas", "Felix", "Paul", "Leon", "Tim", "Jan", "Niklas", "David", "Moritz", "Simon",
+    "Tobias",
+];
+
+const FEMALE_NAMES: [&str; 12] = [
+    "Anna", "Lena", "Laura", "Julia", "Sarah", "Lea", "Hannah", "Sophie", "Marie", "Emma", "Clara",
+    "Nina",
+];
+
+const OTHER_NAMES: [&str; 6] = ["Alex", "Kim", "Robin", "Charlie", "Sascha", "Luca"];
+
+const LAST_NAMES: [&str; 16] = [
+    "Müller",
+    "Schmidt",
+    "Schneider",
+    "Fischer",
+    "Weber",
+    "Meyer",
+    "Wagner",
+    "Becker",
+    "Schulz",
+    "Hoffmann",
+    "Koch",
+    "Richter",
+    "Klein",
+    "Wolf",
+    "Neumann",
+    "Braun",
+];
+
+/// A transparent png of one pixel standing in for photos embedded in the file.
+const PLACEHOLDER_PNG: &str = "data:image/png;base64,\
+    iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=";
+
+/// Size of a landscape A4 page in points.
+const PAGE_SIZE: (f32, f32) = (842.0, 595.0);
+
+const MARGIN: f32 = 36.0;
+
+/// The formats the birthdays can be exported to.
+#[derive(Debug, Clone, Subcommand)]
+pub enum Format {
+    /// printable calendar poster with the birthdays of one month
+    Pdf {
+        /// the month of the poster, defaults to the current one
+        #[arg(long, value_name = "YYYY-MM", value_parser = parse_month)]
+        month: Option<NaiveDate>,
+
+        /// defaults to "geburtstage-YYYY-MM.pdf"
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// atom feed with an entry for every upcoming birthday
+    Feed {
+        /// include the birthdays of this many days starting today
+        #[arg(long, default_value_t = 30)]
+        days: u64,
+
+        /// defaults to the standard output
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Replaces the names, links, notes and departments of `persons` with made up ones.
+///
+/// Birthdays, genders, colors, opt-outs and the order are kept so a file with the same
+/// behaviour can be shared, e.g. in a bug report. The persons of one department stay in
+/// one made up department.
+pub fn anonymize(persons: &[Person]) -> Result<Vec<Person>, BirthdayError> {
+    let mut departments: Vec<&str> = Vec::new();
+    persons
+        .iter()
+        .enumerate()
+        .map(|(index, person)| {
+            let department = person.department.as_deref().map(|department| {
+                let position = departments
+                    .iter()
+                    .position(|known| *known == department)
+                    .unwrap_or_else(|| {
+                        departments.push(department);
+                        departments.len() - 1
+                    });
+                format!("Abteilung {}", position + 1)
+            });
+            let first_names: &[&str] = match person.gender() {
+                Gender::Male => &MALE_NAMES,
+                Gender::Female => &FEMALE_NAMES,
+                Gender::Other => &OTHER_NAMES,
+            };
+            // the combinations repeat only after every first name met every last name
+            let first_name = first_names[index % first_names.len()];
+            let last_name = LAST_NAMES[(index + index / LAST_NAMES.len()) % LAST_NAMES.len()];
+            let number = index + 1;
+
+            Person::builder(last_name, first_name, person.birthday, person.gender())
+                .image_url(
+                    person
+                        .image_url
+                        .as_deref()
+                        .map(|url| placeholder_image(url, number)),
+                )
+                .contact_url(
+                    person
+                        .contact_url
+                        .as_ref()
+                        .map(|_| format!("https://example.com/contacts/{number}")),
+                )
+                .color(person.color.clone())
+                .notes(person.notes.as_ref().map(|_| format!("Notiz {number}")))
+                .department(department)
+                .opt_out(person.opt_out)
+                .build()
+                .map_err(|error| {
+                    BirthdayError::Data(format!("error anonymizing a person: {error}"))
+                })
+        })
+        .collect()
+}
+
+/// Returns a made up image url of the scheme of `url`, which is one of the accepted ones.
+fn placeholder_image(url: &str, number: usize) -> String {
+    match ImageScheme::of(url) {
+        Some(ImageScheme::Http) => format!("http://example.com/images/{number}.png"),
+        Some(ImageScheme::File) => format!("file:///images/{number}.png"),
+        Some(ImageScheme::Data) => PLACEHOLDER_PNG.to_string(),
+        Some(ImageScheme::Https) | None => format!("https://example.com/images/{number}.png"),
+    }
+}
+
+/// Parses a month like "2025-03" to its first day.
+fn parse_month(value: &str) -> Result<NaiveDate, BirthdayError> {
+    NaiveDate::parse_from_str(&format!("{value}-01"), "%Y-%m-%d")
+        .map_err(|_| BirthdayError::date(value, "YYYY-MM"))
+}
+
+/// Writes the persons to `output`, encrypted with `key` if needed, or as csv to the standard output.
+pub fn write_persons(
+    persons: &[Person],
+    output: Option<&Path>,
+    key: Option<&Path>,
+) -> Result<(), BirthdayError> {
+    match output {
+        Some(path) => source::open(path, key).save(persons),
+        None => {
+            let stdout = Path::new("the standard output");
+            let content = csv::serialize_persons(persons, &UnparsedRecords::default())
+                .map_err(|error| BirthdayError::csv(stdout, error))?;
+            io::stdout()
+                .write_all(&content)
+                .map_err(|error| BirthdayError::io(stdout, error))
+        }
+    }
+}
+
+/// Writes the birthdays of `persons` from the csv file at `source` in the given `format`,
+/// starting on the day of `clock`.
+pub fn run(
+    format: &Format,
+    persons: &[Person],
+    source: &Path,
+    eink: bool,
+    clock: &dyn Clock,
+) -> Result<(), BirthdayError> {
+    match format {
+        Format::Pdf { month, output } => {
+            let today = clock.local().date_naive();
+            let month = month.unwrap_or_else(|| today.with_day(1).unwrap_or(today));
+            let output = output.clone().unwrap_or_else(|| {
+                PathBuf::from(month.format("geburtstage-%Y-%m.pdf").to_string())
+            });
+            let theme = if eink { style::eink() } else { Theme::Light };
+
+            fs::write(&output, poster(persons, month, &theme).to_bytes())
+                .map_err(|error| BirthdayError::io(&output, error))?;
+        }
+        Format::Feed { days, output } => {
+            let today = clock.today();
+            let source = source.file_name().unwrap_or(source.as_os_str());
+            let feed = feed::atom(persons, today, *days, &source.to_string_lossy());
+
+            match output {
+                Some(path) => {
+                    fs::write(path, feed).map_err(|error| BirthdayError::io(path, error))?
+                }
+                None => print!("{feed}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lays out a calendar of the month starting at `first` with the birthdays of `persons`.
+fn poster(persons: &[Person], first: NaiveDate, theme: &Theme) -> Page {
+    let palette = theme.palette();
+    let index = BirthdayIndex::new(persons);
+    let grid_color = Color::from_rgb(0.6, 0.6, 0.6);
+    let outside_color = Color::from_rgb(0.93, 0.93, 0.93);
+
+    let mut page = Page::new(PAGE_SIZE.0, PAGE_SIZE.1);
+    let title = format!(
+        "Geburtstage im {} {}",
+        MONTH_NAMES[first.month0() as usize],
+        first.year()
+    );
+    let mut top = page.height() - MARGIN - 28.0;
+    page.centered_text(
+        page.width() / 2.0,
+        top,
+        28.0,
+        Font::Bold,
+        palette.primary,
+        &title,
+    );
+
+    let cell_width = (page.width() - 2.0 * MARGIN) / 7.0;
+    top -= 30.0;
+    for (column, name) in WEEKDAY_NAMES.iter().enumerate() {
+        let center = MARGIN + (column as f32 + 0.5) * cell_width;
+        page.centered_text(center, top, 11.0, Font::Bold, palette.text, name);
+    }
+    top -= 8.0;
+
+    let last = first + Months::new(1);
+    let offset = first.weekday().num_days_from_monday();
+    let days = (last - first).num_days() as u32;
+    let rows = (offset + days).div_ceil(7);
+    let cell_height = (top - MARGIN) / rows as f32;
+
+    for cell in 0..rows * 7 {
+        let x = MARGIN + (cell % 7) as f32 * cell_width;
+        let y = top - (cell / 7 + 1) as f32 * cell_height;
+        let bounds = (x, y, cell_width, cell_height);
+
+        let Some(date) = cell
+            .checked_sub(offset)
+            .and_then(|day| first.with_day(day + 1))
+            .filter(|date| date.month() == first.month())
+        else {
+            page.rectangle(bounds, Some(outside_color), Some(grid_color));
+            continue;
+        };
+        page.rectangle(bounds, None, Some(grid_color));
+
+        let font_size = 9.0;
+        let line_height = font_size * 1.3;
+        let mut baseline = y + cell_height - 14.0;
+        page.text(
+            x + 4.0,
+            baseline,
+            12.0,
+            Font::Bold,
+            palette.text,
+            &date.day().to_string(),
+        );
+
+        let birthdays: Vec<&Person> = index
+            .on(date)
+            .iter()
+            .map(|&position| &persons[position])
+            .collect();
+        let fitting = ((baseline - y - 4.0) / line_height) as usize;
+        for (index, person) in birthdays.iter().enumerate() {
+            baseline -= line_height;
+            // the last line tells how many do not fit anymore
+            if index + 1 == fitting && birthdays.len() > fitting {
+                let more = format!("+{} weitere", birthdays.len() - index);
+                page.text(
+                    x + 4.0,
+                    baseline,
+                    font_size,
+                    Font::Regular,
+                    palette.text,
+                    &more,
+                );
+                break;
+            }
+
+            let age = date.years_since(person.birthday).unwrap_or_default();
+            let line = pdf::fit_text(
+                &format!("{} ({age})", person.name()),
+                font_size,
+                Font::Regular,
+                cell_width - 8.0,
+            );
+            page.text(
+                x + 4.0,
+                baseline,
+                font_size,
+                Font::Regular,
+                palette.text,
+                &line,
+            );
+        }
+    }
+
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{builder, date, person};
+
+    #[test]
+    fn anonymizes_the_text_but_keeps_the_behaviour() {
+        let person = |first_name: &str, department: &str| {
+            builder(first_name, date(1990, 3, 10))
+                .color(Some(String::from("#ff0000")))
+                .notes(Some(String::from("mag Tulpen")))
+                .department(Some(department.to_string()))
+                .opt_out(true)
+                .build()
+                .unwrap()
+        };
+        let persons = [
+            person("Anna", "Vertrieb"),
+            person("Berta", "Einkauf"),
+            person("Clara", "Vertrieb"),
+        ];
+
+        let anonymized = anonymize(&persons).unwrap();
+
+        let departments: Vec<_> = anonymized
+            .iter()
+            .map(|person| person.department.as_deref().unwrap())
+            .collect();
+        assert_eq!(departments, ["Abteilung 1", "Abteilung 2", "Abteilung 1"]);
+        assert_eq!(anonymized[0].notes.as_deref(), Some("Notiz 1"));
+        assert_eq!(anonymized[0].color.as_deref(), Some("#ff0000"));
+        assert!(anonymized.iter().all(|person| person.opt_out));
+    }
+
+    #[test]
+    fn replaces_images_with_ones_of_the_same_scheme() {
+        let persons: Vec<_> = ["data:image/png;base64,AAAA", "file:///fotos/anna.jpg"]
+            .into_iter()
+            .map(|url| {
+                builder("Anna", date(1990, 3, 10))
+                    .image_url(Some(url.to_string()))
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+
+        let anonymized = anonymize(&persons).unwrap();
+
+        assert_eq!(anonymized[0].image_url.as_deref(), Some(PLACEHOLDER_PNG));
+        assert_eq!(
+            anonymized[1].image_url.as_deref(),
+            Some("file:///images/2.png")
+        );
+    }
+
+    #[test]
+    fn the_feed_starts_on_the_day_of_the_clock() {
+        let path = std::env::temp_dir().join(format!(
+            "birthday_display-test-feed-{}.xml",
+            std::process::id()
+        ));
+        let format = Format::Feed {
+            days: 7,
+            output: Some(path.clone()),
+        };
+        let persons = [
+            person("Anna", date(1990, 3, 12)),
+            person("Ben", date(1991, 3, 20)),
+        ];
+        let clock = crate::clock::ShiftedClock::starting_on(date(2024, 3, 10));
+
+        run(&format, &persons, Path::new("persons.csv"), false, &clock).unwrap();
+        let feed = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(feed.contains("Anna"));
+        assert!(!feed.contains("Ben"));
+    }
+}
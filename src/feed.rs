@@ -0,0 +1,74 @@
+use crate::collation::SortKey;
+use crate::index::BirthdayIndex;
+use crate::percent;
+use crate::person::Person;
+
+use std::fmt::Write;
+
+use chrono::{Days, NaiveDate, Utc};
+
+/// Escapes the characters with a special meaning in xml.
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Formats the start of `date` as timestamp required by atom.
+fn timestamp(date: NaiveDate) -> String {
+    format!("{}T00:00:00Z", date.format("%Y-%m-%d"))
+}
+
+/// Generates an atom feed with one entry for every birthday in the next `days`
+/// days starting at `today`.
+///
+/// `source` identifies the feed so readers can tell feeds of different files apart.
+pub fn atom(persons: &[Person], today: NaiveDate, days: u64, source: &str) -> String {
+    let end = today + Days::new(days);
+    let mut birthdays: Vec<(NaiveDate, &Person)> = BirthdayIndex::new(persons)
+        .between(today, end)
+        .into_iter()
+        .map(|(date, index)| (date, &persons[index]))
+        .collect();
+    birthdays.sort_by_cached_key(|(date, person)| (*date, SortKey::new(&person.name())));
+
+    let mut feed = String::new();
+    let _ = write!(
+        feed,
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\">\n\
+         \x20 <title>Geburtstage</title>\n\
+         \x20 <id>tag:birthday_display,2023:{}</id>\n\
+         \x20 <updated>{}</updated>\n\
+         \x20 <author><name>birthday_display</name></author>\n",
+        percent::encode(source),
+        Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+    );
+
+    for (date, person) in birthdays {
+        // entries appear when the birthday enters the time span and do not change afterwards
+        let published = date.checked_sub_days(Days::new(days)).unwrap_or(date);
+        let _ = write!(
+            feed,
+            "  <entry>\n\
+             \x20   <title>{}</title>\n\
+             \x20   <id>tag:birthday_display,2023:{}/{}</id>\n\
+             \x20   <updated>{}</updated>\n\
+             \x20   <summary>{}</summary>\n",
+            escape(&person.name()),
+            percent::encode(&person.name()),
+            date.format("%Y-%m-%d"),
+            timestamp(published),
+            escape(&person.banner(date, None)),
+        );
+        if let Some(url) = &person.contact_url {
+            let _ = writeln!(feed, "    <link href=\"{}\"/>", escape(url));
+        }
+        feed.push_str("  </entry>\n");
+    }
+    feed.push_str("</feed>\n");
+
+    feed
+}
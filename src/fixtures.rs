@@ -0,0 +1,56 @@
+use crate::person::{Gender, Person, PersonBuilder};
+
+use chrono::{Datelike, NaiveDate};
+use proptest::prelude::*;
+use proptest::test_runner::Config;
+
+/// Returns the date, panicking for days that do not exist.
+pub fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+/// Returns the builder of a person named `first_name` Muster of the diverse gender, for
+/// the tests that need further fields.
+pub fn builder(first_name: &str, birthday: NaiveDate) -> PersonBuilder {
+    Person::builder("Muster", first_name, birthday, Gender::Other)
+}
+
+/// Returns a person named `first_name` Muster of the diverse gender.
+pub fn person(first_name: &str, birthday: NaiveDate) -> Person {
+    builder(first_name, birthday).build().unwrap()
+}
+
+/// Returns the config of the property tests, checking every property with 2000 cases.
+pub fn config() -> Config {
+    Config::with_cases(2000)
+}
+
+/// Returns dates from 1800 until 2200 favouring the edge cases: the turn of the year, the
+/// end of February and years before 1900 whose century is no leap year.
+pub fn dates() -> impl Strategy<Value = NaiveDate> {
+    let years = prop_oneof![
+        1 => prop::sample::select(vec![1800, 1899, 1900, 1999, 2000, 2100]),
+        3 => 1800..=2200,
+    ];
+    let days = prop_oneof![
+        1 => prop::sample::select(vec![(1, 1), (2, 28), (2, 29), (3, 1), (12, 31)]),
+        3 => (1..=12_u32, 1..=31_u32),
+    ];
+    (years, days).prop_map(|(year, (month, day))| {
+        // days after the end of the month fall on its last one
+        (1..=day)
+            .rev()
+            .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+            .expect("every month has a first day")
+    })
+}
+
+/// Returns a date of [`dates`] and one from it until `days` later.
+pub fn date_ranges(days: i64) -> impl Strategy<Value = (NaiveDate, NaiveDate)> {
+    (dates(), 0..=days).prop_map(|(from, days)| (from, from + chrono::Duration::days(days)))
+}
+
+/// Returns whether `date` is on the same day and month as `birthday`.
+pub fn same_day(date: NaiveDate, birthday: NaiveDate) -> bool {
+    (date.month(), date.day()) == (birthday.month(), birthday.day())
+}
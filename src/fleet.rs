@@ -0,0 +1,102 @@
+use crate::error::BirthdayError;
+
+use std::env;
+use std::fs;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use reqwest::Client;
+use serde::Serialize;
+
+/// How often the status is reported without `--report-every`.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// The state of a display posted to the management server as json.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub hostname: String,
+    pub version: &'static str,
+    pub uptime_seconds: u64,
+    /// The file or url the persons are read from.
+    pub source: String,
+    /// When the persons were last read successfully.
+    pub loaded_at: DateTime<Local>,
+    /// The reason the persons can not be read at the moment.
+    pub load_error: Option<String>,
+    pub persons: usize,
+    pub skipped_rows: usize,
+    pub images_loaded: usize,
+    pub images_failed: usize,
+    /// The number of errors shown or hidden by `--silent` since the start.
+    pub errors: usize,
+}
+
+/// Returns the name of this computer or "unknown".
+pub fn hostname() -> String {
+    env::var("COMPUTERNAME")
+        .or_else(|_| env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+/// Posts `report` to `url`, failing if the server does not answer with success.
+pub async fn send(client: Client, url: String, report: Report) -> Result<(), BirthdayError> {
+    client
+        .post(&url)
+        .timeout(Duration::from_secs(30))
+        .json(&report)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|error| {
+            BirthdayError::Network(format!("error reporting status to {url}: {error}"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use httptest::matchers::json_decoded;
+    use httptest::matchers::request::{body, method_path};
+    use httptest::responders::status_code;
+    use httptest::{all_of, Expectation, Server};
+
+    #[test]
+    fn posts_the_report_as_json() {
+        let server = Server::run();
+        server.expect(
+            Expectation::matching(all_of![
+                method_path("POST", "/status"),
+                body(json_decoded(|report: &serde_json::Value| {
+                    report["source"] == "persons.csv"
+                        && report["persons"] == 12
+                        && report["load_error"].is_null()
+                })),
+            ])
+            .respond_with(status_code(200)),
+        );
+        let report = Report {
+            hostname: hostname(),
+            version: env!("CARGO_PKG_VERSION"),
+            uptime_seconds: 0,
+            source: String::from("persons.csv"),
+            loaded_at: Local::now(),
+            load_error: None,
+            persons: 12,
+            skipped_rows: 0,
+            images_loaded: 0,
+            images_failed: 0,
+            errors: 0,
+        };
+
+        let runtime = crate::runtime::current_thread().unwrap();
+        runtime
+            .block_on(send(Client::new(), server.url_str("/status"), report))
+            .unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+use crate::error::BirthdayError;
+use crate::percent;
+use crate::sync::{BirthdayEvent, Calendar, Change, RemoteEvent};
+
+use chrono::{Days, NaiveDate};
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Value};
+
+const API: &str = "https://www.googleapis.com/calendar/v3";
+
+/// Marks the events created by this program.
+const MARKER: &str = "birthday_display";
+
+/// Stores the key of the person an event belongs to.
+const KEY_PROPERTY: &str = "birthday_display_key";
+
+/// A Google Calendar accessed with an OAuth access token.
+pub struct GoogleCalendar {
+    client: Client,
+    events_url: String,
+    access_token: String,
+}
+
+impl GoogleCalendar {
+    pub fn new(calendar: &str, access_token: &str) -> Result<Self, reqwest::Error> {
+        Ok(Self {
+            client: Client::builder().build()?,
+            events_url: format!("{API}/calendars/{}/events", percent::encode(calendar)),
+            access_token: access_token.to_string(),
+        })
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Value, BirthdayError> {
+        let response = request.bearer_auth(&self.access_token).send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(BirthdayError::Network(format!(
+                "google calendar returned {status}: {text}"
+            )));
+        }
+
+        // deletions have an empty body
+        if text.is_empty() {
+            Ok(Value::Null)
+        } else {
+            serde_json::from_str(&text).map_err(|error| {
+                BirthdayError::Network(format!("google calendar returned invalid json: {error}"))
+            })
+        }
+    }
+}
+
+impl Calendar for GoogleCalendar {
+    async fn events(&self) -> Result<Vec<RemoteEvent>, BirthdayError> {
+        let mut events = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut request = self.client.get(&self.events_url).query(&[
+                ("privateExtendedProperty", format!("{MARKER}=1")),
+                ("maxResults", String::from("2500")),
+            ]);
+            if let Some(token) = &page_token {
+                request = request.query(&[("pageToken", token)]);
+            }
+            let page = self.send(request).await?;
+
+            let items = page["items"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+            events.extend(items.iter().filter_map(parse_event));
+
+            match page["nextPageToken"].as_str() {
+                Some(token) => page_token = Some(token.to_string()),
+                None => return Ok(events),
+            }
+        }
+    }
+
+    async fn apply(&self, change: &Change) -> Result<(), BirthdayError> {
+        let request = match change {
+            Change::Create(event) => self.client.post(&self.events_url).json(&body(event)),
+            Change::Update(id, event) => self
+                .client
+                .put(format!("{}/{}", self.events_url, percent::encode(id)))
+                .json(&body(event)),
+            Change::Delete(remote) => self.client.delete(format!(
+                "{}/{}",
+                self.events_url,
+                percent::encode(&remote.id)
+            )),
+        };
+        self.send(request).await.map(drop)
+    }
+}
+
+/// Generates the json representation of a yearly all-day event.
+fn body(event: &BirthdayEvent) -> Value {
+    let end = event.date + Days::new(1);
+    json!({
+        "summary": event.summary,
+        "start": { "date": event.date.format("%Y-%m-%d").to_string() },
+        "end": { "date": end.format("%Y-%m-%d").to_string() },
+        "recurrence": ["RRULE:FREQ=YEARLY"],
+        "transparency": "transparent",
+        "extendedProperties": {
+            "private": { MARKER: "1", KEY_PROPERTY: event.key },
+        },
+    })
+}
+
+/// Reads an event created by [`body`].
+fn parse_event(item: &Value) -> Option<RemoteEvent> {
+    let date = NaiveDate::parse_from_str(item["start"]["date"].as_str()?, "%Y-%m-%d").ok()?;
+
+    Some(RemoteEvent {
+        id: item["id"].as_str()?.to_string(),
+        event: BirthdayEvent {
+            key: item["extendedProperties"]["private"][KEY_PROPERTY]
+                .as_str()?
+                .to_string(),
+            summary: item["summary"].as_str().unwrap_or_default().to_string(),
+            date,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::date;
+
+    #[test]
+    fn reads_the_events_it_creates() {
+        let event = BirthdayEvent {
+            key: String::from("Muster,Anna,10.03.1990"),
+            summary: String::from("Geburtstag Anna Muster"),
+            date: date(2024, 3, 10),
+        };
+        let mut item = body(&event);
+        item["id"] = "event-1".into();
+
+        let remote = parse_event(&item).unwrap();
+        assert_eq!(remote.id, "event-1");
+        assert_eq!(remote.event, event);
+    }
+}
@@ -0,0 +1,25 @@
+use rppal::gpio::{Gpio, OutputPin};
+
+/// An output pin that is raised while there are birthdays, e.g. driving a relay.
+pub struct Flag {
+    pin: OutputPin,
+    raised: Option<bool>,
+}
+
+impl Flag {
+    /// Opens the pin with the BCM number `pin`.
+    pub fn new(pin: u8) -> rppal::gpio::Result<Self> {
+        Ok(Self {
+            pin: Gpio::new()?.get(pin)?.into_output_low(),
+            raised: None,
+        })
+    }
+
+    /// Raises or lowers the pin if it is not already in that state.
+    pub fn set(&mut self, raised: bool) {
+        if self.raised != Some(raised) {
+            self.pin.write(raised.into());
+            self.raised = Some(raised);
+        }
+    }
+}
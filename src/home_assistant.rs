@@ -0,0 +1,118 @@
+use crate::config::{HomeAssistantConfig, WorkdaysConfig};
+use crate::error::BirthdayError;
+use crate::index::BirthdayIndex;
+use crate::mqtt::Connection;
+use crate::notify::{Notification, Notifier};
+
+use chrono::NaiveDate;
+use serde_json::json;
+
+const STATE_TOPIC: &str = "birthday_display/birthdays_today/state";
+const ATTRIBUTES_TOPIC: &str = "birthday_display/birthdays_today/attributes";
+
+/// Publishes the `sensor.birthdays_today` entity through MQTT discovery.
+pub struct HomeAssistant {
+    config: HomeAssistantConfig,
+}
+
+impl HomeAssistant {
+    pub fn new(config: &HomeAssistantConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Returns the topics and payloads describing the birthdays of `notification`.
+    fn messages(&self, notification: &Notification<'_>) -> [(String, String); 3] {
+        let prefix = self
+            .config
+            .discovery_prefix
+            .as_deref()
+            .unwrap_or("homeassistant");
+        let discovery = json!({
+            "name": "Birthdays today",
+            "unique_id": "birthday_display_birthdays_today",
+            "object_id": "birthdays_today",
+            "icon": "mdi:cake-variant",
+            "state_topic": STATE_TOPIC,
+            "json_attributes_topic": ATTRIBUTES_TOPIC,
+        });
+
+        let today = notification.today;
+        let tomorrow = today
+            .succ_opt()
+            .expect("the date is not the last one representable");
+        let days_to_next = BirthdayIndex::new(notification.persons)
+            .upcoming(tomorrow, 1)
+            .first()
+            .map(|(date, _)| (*date - today).num_days());
+        let birthdays = &notification.birthdays;
+        let attributes = json!({
+            "names": birthdays.iter().map(|(_, person)| person.name()).collect::<Vec<_>>(),
+            "ages": birthdays.iter().map(|(_, person)| today.years_since(person.birthday)).collect::<Vec<_>>(),
+            "departments": birthdays.iter().map(|(_, person)| person.department.as_deref()).collect::<Vec<_>>(),
+            "days_to_next": days_to_next,
+        });
+
+        [
+            (
+                format!("{prefix}/sensor/birthday_display/birthdays_today/config"),
+                discovery.to_string(),
+            ),
+            (String::from(STATE_TOPIC), birthdays.len().to_string()),
+            (String::from(ATTRIBUTES_TOPIC), attributes.to_string()),
+        ]
+    }
+}
+
+impl Notifier for HomeAssistant {
+    fn name(&self) -> String {
+        format!("home assistant at {}", self.config.host)
+    }
+
+    // the sensor is also updated on days without birthdays
+    fn on_empty_days(&self) -> bool {
+        true
+    }
+
+    // the sensor tells about the birthdays of the real day
+    fn dates(&self, today: NaiveDate, _workdays: Option<&WorkdaysConfig>) -> Vec<NaiveDate> {
+        vec![today]
+    }
+
+    fn preview(&self, notification: &Notification<'_>) -> String {
+        self.messages(notification)
+            .map(|(topic, payload)| format!("{topic} {payload}"))
+            .join("\n")
+    }
+
+    async fn send(&self, notification: &Notification<'_>) -> Result<(), BirthdayError> {
+        let config = &self.config;
+        let credentials = config
+            .username
+            .as_deref()
+            .map(|username| (username, config.password.as_deref().unwrap_or_default()));
+        let mut connection = Connection::connect(
+            &config.host,
+            config.port.unwrap_or(1883),
+            "birthday_display",
+            credentials,
+        )
+        .map_err(|error| {
+            BirthdayError::Network(format!("error connecting to {}: {error}", config.host))
+        })?;
+
+        // everything is retained so the entity survives restarts of home assistant
+        for (topic, payload) in self.messages(notification) {
+            connection
+                .publish(&topic, payload.as_bytes(), true)
+                .map_err(|error| {
+                    BirthdayError::Network(format!("error publishing to {}: {error}", config.host))
+                })?;
+        }
+        connection.disconnect().map_err(|error| {
+            BirthdayError::Network(format!("error publishing to {}: {error}", config.host))
+        })?;
+        Ok(())
+    }
+}
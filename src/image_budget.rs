@@ -0,0 +1,218 @@
+#[cfg(feature = "network")]
+use crate::config;
+use crate::person::Person;
+#[cfg(feature = "network")]
+use crate::{decode_image, decode_local_image, download_image};
+
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "network")]
+use std::fs;
+#[cfg(feature = "network")]
+use std::path::{Path, PathBuf};
+
+use iced_native::image::Data;
+use iced_native::image::Handle;
+#[cfg(feature = "network")]
+use reqwest::RequestBuilder;
+
+/// Limits the memory used by decoded photos to the ones of the persons that are shown
+/// or celebrate soon.
+///
+/// Downloaded photos are kept in a directory so evicted ones are decoded from disk
+/// again instead of being downloaded.
+#[derive(Debug)]
+pub struct ImageBudget {
+    /// The maximal number of bytes of decoded photos.
+    limit: usize,
+    #[cfg(feature = "network")]
+    dir: Option<PathBuf>,
+    /// The decoded sizes of the photos that were loaded before by their url.
+    sizes: HashMap<String, usize>,
+}
+
+impl ImageBudget {
+    /// Creates a budget of `megabytes`.
+    pub fn new(megabytes: usize) -> Self {
+        Self {
+            limit: megabytes * 1024 * 1024,
+            #[cfg(feature = "network")]
+            dir: None,
+            sizes: HashMap::new(),
+        }
+    }
+
+    /// Keeps the downloaded photos in `dir`.
+    #[cfg(feature = "network")]
+    pub fn cache_in(mut self, dir: PathBuf, verbosity: u8) -> Self {
+        match fs::create_dir_all(&dir) {
+            Ok(()) => self.dir = Some(dir),
+            Err(error) => {
+                if verbosity > 0 {
+                    println!("error creating image cache {}: {error}", dir.display());
+                }
+            }
+        }
+        self
+    }
+
+    /// Returns the directory the downloaded photos are kept in.
+    #[cfg(feature = "network")]
+    pub fn dir(&self) -> Option<PathBuf> {
+        self.dir.clone()
+    }
+
+    /// Remembers the size of the photo at `url` once it was loaded.
+    pub fn received(&mut self, url: &str, image_data: &Result<Handle, String>) {
+        if let Ok(handle) = image_data {
+            self.sizes.insert(url.to_string(), size(handle));
+        }
+    }
+
+    /// Drops the decoded photos of the persons not in `ranked` and of the last ones in
+    /// `ranked` that do not fit into the budget and returns the urls of the kept persons
+    /// that are not loaded yet.
+    ///
+    /// `ranked` contains the indices of the persons that should be kept ordered by how
+    /// soon they are shown, the first one is always kept.
+    pub fn plan(&mut self, persons: &mut [Person], ranked: &[usize]) -> Vec<String> {
+        let mut kept = HashSet::new();
+        let mut used = 0;
+        for &index in ranked {
+            let person = &persons[index];
+            let needed: usize = person
+                .image_urls()
+                .iter()
+                .map(|url| self.sizes.get(*url).copied().unwrap_or_default())
+                .sum();
+            if !kept.is_empty() && used + needed > self.limit {
+                break;
+            }
+            used += needed;
+            kept.insert(index);
+        }
+
+        let mut missing = Vec::new();
+        for (index, person) in persons.iter_mut().enumerate() {
+            if kept.contains(&index) {
+                missing.extend(
+                    person
+                        .image_urls()
+                        .into_iter()
+                        .filter(|url| !person.image_data.contains_key(*url))
+                        .map(str::to_string),
+                );
+            } else {
+                // failed photos are kept so they are not requested over and over
+                person
+                    .image_data
+                    .retain(|_, image_data| image_data.is_err());
+            }
+        }
+        missing
+    }
+}
+
+/// Returns the number of bytes the decoded photo of `handle` takes.
+fn size(handle: &Handle) -> usize {
+    match handle.data() {
+        Data::Path(_) => 0,
+        Data::Bytes(bytes) => bytes.len(),
+        Data::Rgba { pixels, .. } => pixels.len(),
+    }
+}
+
+/// Returns the directory the downloaded photos are kept in without `--image-cache`, in
+/// the cache directory of the user so other users can not swap the photos.
+#[cfg(feature = "network")]
+pub fn default_dir() -> Option<PathBuf> {
+    let dir = config::cache_dir()?.join("images");
+    config::create_private_dir(&dir).ok()?;
+    Some(dir)
+}
+
+/// Returns the file the photo at `url` is kept in.
+#[cfg(feature = "network")]
+pub fn cache_file(dir: &Path, url: &str) -> PathBuf {
+    dir.join(sha1_smol::Sha1::from(url).digest().to_string())
+}
+
+/// Loads the photo at `url` from the cache in `dir` or downloads it with `request`
+/// and stores it in the cache.
+#[cfg(feature = "network")]
+pub async fn request_cached_image(
+    request: RequestBuilder,
+    url: String,
+    verbosity: u8,
+    dir: Option<PathBuf>,
+) -> (Result<Handle, String>, String) {
+    if let Some(image_data) = decode_local_image(&url, verbosity).await {
+        return (image_data, url);
+    }
+
+    let file = dir.map(|dir| cache_file(&dir, &url));
+    if let Some(bytes) = file.as_ref().and_then(|file| fs::read(file).ok()) {
+        if let Ok(handle) = decode_image(bytes, &url, verbosity).await {
+            return (Ok(handle), url);
+        }
+    }
+
+    let image_data = match download_image(request, &url, verbosity).await {
+        Ok(bytes) => {
+            if let Some(file) = &file {
+                if let Err(error) = fs::write(file, &bytes) {
+                    if verbosity > 0 {
+                        println!("error caching image in {}: {error}", file.display());
+                    }
+                }
+            }
+            decode_image(bytes, &url, verbosity).await
+        }
+        Err(error) => Err(error),
+    };
+    (image_data, url)
+}
+
+#[cfg(all(test, feature = "network"))]
+mod tests {
+    use super::*;
+    use httptest::matchers::request::method_path;
+    use httptest::responders::status_code;
+    use httptest::{Expectation, Server};
+    use reqwest::Client;
+
+    #[test]
+    fn reads_cached_photos_instead_of_downloading_them() {
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(1, 1)
+            .write_to(&mut png, image::ImageOutputFormat::Png)
+            .unwrap();
+        let server = Server::run();
+        // the second request reads the cache, a second download fails the test
+        server.expect(
+            Expectation::matching(method_path("GET", "/photo.png")).respond_with(
+                status_code(200)
+                    .insert_header("Content-Type", "image/png")
+                    .body(png.into_inner()),
+            ),
+        );
+        let url = server.url_str("/photo.png");
+        let dir =
+            std::env::temp_dir().join(format!("birthday_display-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let runtime = crate::runtime::current_thread().unwrap();
+        for _ in 0..2 {
+            let request = Client::new().get(&url);
+            let (image_data, _) = runtime.block_on(request_cached_image(
+                request,
+                url.clone(),
+                0,
+                Some(dir.clone()),
+            ));
+            assert!(image_data.is_ok());
+        }
+        assert!(cache_file(&dir, &url).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use crate::image_budget::cache_file;
+use crate::photo::{self, BodyError};
+use crate::{decode_image, redact};
+
+use std::fs;
+use std::path::PathBuf;
+
+use iced_native::image::Handle;
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{RequestBuilder, StatusCode};
+
+/// What the server told about the version of an image to ask whether it changed.
+#[derive(Debug, Clone, Default)]
+pub struct Validator {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validator {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    /// Adds the conditional headers to `request`.
+    fn apply(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+/// A changed image with the validator of its new version.
+pub type Refreshed = (Result<Handle, String>, Validator);
+
+/// Asks whether the image at `url` changed since the version of `validator` and
+/// returns the new version if it did.
+///
+/// Without validator the image is always downloaded again. The new version replaces
+/// the one in the image cache `dir`.
+pub async fn revalidate(
+    request: RequestBuilder,
+    url: String,
+    validator: Option<Validator>,
+    verbosity: u8,
+    dir: Option<PathBuf>,
+) -> (String, Option<Refreshed>) {
+    let request = match &validator {
+        Some(validator) => validator.apply(request),
+        None => request,
+    };
+
+    crate::politeness::wait(&url).await;
+    let response = match request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(response) if response.status() == StatusCode::NOT_MODIFIED => return (url, None),
+        Ok(response) => response,
+        Err(error) => {
+            if verbosity > 0 {
+                println!(
+                    "error refreshing image {}: {}",
+                    redact::RedactedUrl(&url),
+                    error.without_url()
+                );
+            }
+            return (url, None);
+        }
+    };
+    let validator = Validator::from_headers(response.headers());
+    let bytes = match photo::read_body(response).await {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            if verbosity > 0 {
+                let error = match error {
+                    BodyError::Request(error) => error.without_url().to_string(),
+                    BodyError::TooLarge(megabytes) => {
+                        format!("the photo is larger than {megabytes} MB")
+                    }
+                };
+                println!(
+                    "error refreshing image {}: {error}",
+                    redact::RedactedUrl(&url),
+                );
+            }
+            return (url, None);
+        }
+    };
+
+    if let Some(dir) = dir {
+        let _ = fs::write(cache_file(&dir, &url), &bytes);
+    }
+    let image_data = decode_image(bytes, &url, verbosity).await;
+    (url, Some((image_data, validator)))
+}
@@ -0,0 +1,274 @@
+use crate::person::Person;
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, NaiveDate};
+
+/// Milestones are computed for the first 120 years of a person.
+const MILESTONES_UNTIL: u64 = 120 * 366;
+
+/// How many years are searched for the next birthday, a leap year follows within eight.
+#[cfg(not(target_arch = "wasm32"))]
+const YEARS: i32 = 8;
+
+/// Indices into the persons ordered by month and day of their birthday, leaving out the
+/// ones that are not [`Person::announced`].
+///
+/// Birthdays on the 29th of February are only found in leap years, like
+/// [`Person::next_birthday`] does.
+#[derive(Debug, Default, Clone)]
+pub struct BirthdayIndex(BTreeMap<(u32, u32), Vec<usize>>);
+
+impl BirthdayIndex {
+    /// Indexes `persons` by their birthday.
+    pub fn new(persons: &[Person]) -> Self {
+        Self::by(persons, |person| {
+            (person.birthday.day(), person.birthday.month())
+        })
+    }
+
+    /// Indexes `persons` by their half birthday.
+    pub fn half_birthdays(persons: &[Person]) -> Self {
+        Self::by(persons, Person::half_birthday)
+    }
+
+    /// Indexes `persons` by the day and month returned by `key`.
+    fn by(persons: &[Person], key: impl Fn(&Person) -> (u32, u32)) -> Self {
+        let mut index: BTreeMap<(u32, u32), Vec<usize>> = BTreeMap::new();
+        for (position, person) in persons.iter().enumerate() {
+            if !person.announced() {
+                continue;
+            }
+            let (day, month) = key(person);
+            index.entry((month, day)).or_default().push(position);
+        }
+        Self(index)
+    }
+
+    /// Returns the persons celebrating on `date`.
+    pub fn on(&self, date: NaiveDate) -> &[usize] {
+        self.0
+            .get(&(date.month(), date.day()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns whether somebody celebrates on `date`.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        !self.on(date).is_empty()
+    }
+
+    /// Returns the dates and persons of all birthdays from `from` until before `to`
+    /// ordered by date.
+    pub fn between(&self, from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDate, usize)> {
+        self.occurrences(from, to.year())
+            .take_while(|(date, _)| *date < to)
+            .collect()
+    }
+
+    /// Returns the next `count` birthdays on or after `from` ordered by date.
+    ///
+    /// Persons appear again with the birthday of the following year if there are less
+    /// than `count`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn upcoming(&self, from: NaiveDate, count: usize) -> Vec<(NaiveDate, usize)> {
+        self.occurrences(from, from.year() + YEARS)
+            .take(count)
+            .collect()
+    }
+
+    /// Iterates over the birthdays on or after `from` until the end of `last_year`.
+    fn occurrences(
+        &self,
+        from: NaiveDate,
+        last_year: i32,
+    ) -> impl Iterator<Item = (NaiveDate, usize)> + '_ {
+        (from.year()..=last_year).flat_map(move |year| {
+            let start = if year == from.year() {
+                (from.month(), from.day())
+            } else {
+                (1, 1)
+            };
+            self.0
+                .range(start..)
+                .filter_map(move |(&(month, day), indices)| {
+                    let date = NaiveDate::from_ymd_opt(year, month, day)?;
+                    Some(indices.iter().map(move |&index| (date, index)))
+                })
+                .flatten()
+        })
+    }
+}
+
+/// Indices into the persons with their birthday grouped by the remainder of the day
+/// number of the birthday divided by the distance of the milestones.
+///
+/// A person is alive a multiple of `every` days on exactly the dates with the same
+/// remainder, so the index only needs one entry per person instead of one per milestone.
+#[derive(Debug, Default, Clone)]
+pub struct MilestoneIndex {
+    every: u64,
+    persons: HashMap<u64, Vec<(usize, NaiveDate)>>,
+}
+
+impl MilestoneIndex {
+    /// Indexes the milestones every `every` days of `persons`, none if `every` is missing.
+    pub fn new(persons: &[Person], every: Option<u64>) -> Self {
+        let Some(every) = every else {
+            return Self::default();
+        };
+        let mut index: HashMap<u64, Vec<(usize, NaiveDate)>> = HashMap::new();
+        for (position, person) in persons
+            .iter()
+            .enumerate()
+            .filter(|(_, person)| person.announced())
+        {
+            index
+                .entry(remainder(person.birthday, every))
+                .or_default()
+                .push((position, person.birthday));
+        }
+        Self {
+            every,
+            persons: index,
+        }
+    }
+
+    /// Returns the persons with the days they are alive that have a milestone on `date`.
+    pub fn on(&self, date: NaiveDate) -> Vec<(usize, u64)> {
+        self.milestones(date).collect()
+    }
+
+    /// Returns whether somebody has a milestone on `date`.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.milestones(date).next().is_some()
+    }
+
+    fn milestones(&self, date: NaiveDate) -> impl Iterator<Item = (usize, u64)> + '_ {
+        let bucket = (self.every > 0)
+            .then(|| self.persons.get(&remainder(date, self.every)))
+            .flatten();
+        bucket
+            .into_iter()
+            .flatten()
+            .filter_map(move |&(position, birthday)| {
+                let days = u64::try_from((date - birthday).num_days()).ok()?;
+                (days > 0 && days <= MILESTONES_UNTIL).then_some((position, days))
+            })
+    }
+}
+
+/// Returns the remainder of the day number of `date` divided by `every`.
+fn remainder(date: NaiveDate, every: u64) -> u64 {
+    i64::from(date.num_days_from_ce()).rem_euclid(every as i64) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, date_ranges, dates, same_day};
+    use crate::person::Gender;
+
+    use proptest::prelude::*;
+
+    /// Returns up to 20 persons with birthdays of [`dates`].
+    fn persons() -> impl Strategy<Value = Vec<Person>> {
+        prop::collection::vec(dates(), 1..=20).prop_map(|birthdays| {
+            birthdays
+                .into_iter()
+                .enumerate()
+                .map(|(index, birthday)| {
+                    Person::builder(
+                        format!("Last{index}"),
+                        format!("First{index}"),
+                        birthday,
+                        Gender::Female,
+                    )
+                    .build()
+                    .unwrap()
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the birthdays from `from` until before `to` by looking at every day.
+    fn every_day(
+        persons: &[Person],
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> impl Iterator<Item = (NaiveDate, usize)> + '_ {
+        from.iter_days()
+            .take_while(move |date| *date < to)
+            .flat_map(|date| {
+                persons
+                    .iter()
+                    .enumerate()
+                    .filter(move |(_, person)| same_day(date, person.birthday))
+                    .map(move |(index, _)| (date, index))
+            })
+    }
+
+    proptest! {
+        #![proptest_config(fixtures::config())]
+
+        #[test]
+        fn finds_every_birthday_between_two_dates(
+            persons in persons(),
+            (from, to) in date_ranges(800),
+        ) {
+            prop_assert_eq!(
+                BirthdayIndex::new(&persons).between(from, to),
+                every_day(&persons, from, to).collect::<Vec<_>>()
+            );
+        }
+
+        #[test]
+        fn upcoming_birthdays_are_the_next_ones(
+            persons in persons(),
+            from in dates(),
+            count in 1..=30_usize,
+        ) {
+            let search_end = NaiveDate::from_ymd_opt(from.year() + YEARS + 1, 1, 1).unwrap();
+            let expected: Vec<_> = every_day(&persons, from, search_end).take(count).collect();
+
+            // the years after the last searched one are left out even with less than `count`
+            prop_assert_eq!(BirthdayIndex::new(&persons).upcoming(from, count), expected);
+        }
+
+        #[test]
+        fn agrees_with_the_next_birthday_of_the_person(persons in persons(), today in dates()) {
+            let index = BirthdayIndex::new(&persons);
+
+            for (position, person) in persons.iter().enumerate() {
+                let next = person.next_birthday(today);
+                prop_assert!(next >= today && same_day(next, person.birthday));
+                prop_assert!(index.on(next).contains(&position));
+                // no earlier year has the birthday on or after today
+                prop_assert!((today.year()..next.year()).all(|year| person
+                    .birthday
+                    .with_year(year)
+                    .is_none_or(|date| date < today)));
+            }
+        }
+
+        #[test]
+        fn finds_every_milestone(
+            persons in persons(),
+            every in 100..=20_000_u64,
+            date in dates(),
+        ) {
+            let expected: Vec<(usize, u64)> = persons
+                .iter()
+                .enumerate()
+                .filter_map(|(index, person)| {
+                    let days = u64::try_from((date - person.birthday).num_days()).ok()?;
+                    (days > 0 && days <= MILESTONES_UNTIL && days % every == 0)
+                        .then_some((index, days))
+                })
+                .collect();
+
+            let mut found = MilestoneIndex::new(&persons, Some(every)).on(date);
+            found.sort_unstable();
+            prop_assert_eq!(found, expected);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+/// The result of trying to become the only running instance.
+#[cfg(not(target_arch = "wasm32"))]
+pub enum Instance {
+    /// No other instance is running and commands are received on the listener.
+    Primary(Listener),
+    /// Another instance is running and was asked to focus its window.
+    AlreadyRunning,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use protocol::{answer, COMMAND_TIMEOUT};
+
+#[cfg(unix)]
+pub use unix::*;
+
+/// The line based commands understood by the running instance.
+#[cfg(not(target_arch = "wasm32"))]
+mod protocol {
+    use crate::{parse_date, DataMessage, Message, UiMessage};
+
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::time::Duration;
+
+    /// How long a connection may take to send its command and read the reply, so one
+    /// that sends nothing does not hold up the commands of the others.
+    pub const COMMAND_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Translates a line of the protocol into a message.
+    fn parse(line: &str) -> Result<Message, String> {
+        let (command, argument) = match line.split_once(' ') {
+            Some((command, argument)) => (command, Some(argument.trim())),
+            None => (line, None),
+        };
+
+        match (command, argument) {
+            ("focus", None) => Ok(Message::Ui(UiMessage::Focus)),
+            ("reload", None) => Ok(Message::Data(DataMessage::Reload)),
+            ("next-page", None) => Ok(Message::Ui(UiMessage::NextPage)),
+            ("set-date", Some("today")) => Ok(Message::Ui(UiMessage::SetDate(None))),
+            ("set-date", Some(date)) => parse_date(date)
+                .map(|date| Message::Ui(UiMessage::SetDate(Some(date))))
+                .map_err(|error| error.to_string()),
+            ("blank", None) => Ok(Message::Ui(UiMessage::Blank(None))),
+            ("blank", Some("on")) => Ok(Message::Ui(UiMessage::Blank(Some(true)))),
+            ("blank", Some("off")) => Ok(Message::Ui(UiMessage::Blank(Some(false)))),
+            ("greeting-card", name) => Ok(Message::Ui(UiMessage::GreetingCards(
+                name.map(str::to_string),
+            ))),
+            _ => Err(format!("unknown command \"{line}\"")),
+        }
+    }
+
+    /// Reads a command from `stream` and answers whether it was understood.
+    pub fn answer(mut stream: impl Read + Write) -> Option<Message> {
+        let mut line = String::new();
+        BufReader::new(&mut stream).read_line(&mut line).ok()?;
+
+        let result = parse(line.trim());
+        let reply = match &result {
+            Ok(_) => String::from("ok\n"),
+            Err(error) => format!("error: {error}\n"),
+        };
+        // instances asking for focus do not wait for the reply
+        let _ = stream.write_all(reply.as_bytes());
+
+        result.ok()
+    }
+}
+
+#[cfg(all(not(unix), not(target_arch = "wasm32")))]
+pub use tcp::*;
+
+#[cfg(target_arch = "wasm32")]
+pub use fallback::*;
+
+#[cfg(unix)]
+mod unix {
+    use super::Instance;
+    use crate::Message;
+
+    use std::env;
+    use std::io::{self, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use iced::{subscription, Subscription};
+
+    /// Socket on which other instances send their commands.
+    #[derive(Clone)]
+    pub struct Listener(Arc<UnixListener>);
+
+    /// Returns the path of the socket the running instance listens on.
+    fn socket_path() -> PathBuf {
+        let directory = env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(env::temp_dir);
+        let user = env::var("USER").unwrap_or_default();
+
+        directory.join(format!("birthday_display-{user}.sock"))
+    }
+
+    /// Checks whether another instance is running by binding its socket.
+    ///
+    /// The socket of an instance that did not shut down cleanly is replaced.
+    pub fn acquire() -> io::Result<Instance> {
+        let path = socket_path();
+
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(error) if error.kind() == io::ErrorKind::AddrInUse => {
+                match UnixStream::connect(&path) {
+                    Ok(mut stream) => {
+                        stream.write_all(b"focus\n")?;
+                        return Ok(Instance::AlreadyRunning);
+                    }
+                    Err(_) => {
+                        // nobody is listening so the socket is stale
+                        std::fs::remove_file(&path)?;
+                        UnixListener::bind(&path)?
+                    }
+                }
+            }
+            Err(error) => return Err(error),
+        };
+
+        Ok(Instance::Primary(Listener(Arc::new(listener))))
+    }
+
+    /// Blocks until a command is received on `listener`.
+    fn receive_command(listener: &UnixListener) -> Option<Message> {
+        let (stream, _) = listener.accept().ok()?;
+        stream.set_read_timeout(Some(super::COMMAND_TIMEOUT)).ok()?;
+        stream
+            .set_write_timeout(Some(super::COMMAND_TIMEOUT))
+            .ok()?;
+        super::answer(stream)
+    }
+
+    /// Subscribes to the commands sent by other instances.
+    pub fn commands(listener: &Listener) -> Subscription<Message> {
+        struct Commands;
+
+        subscription::unfold(
+            std::any::TypeId::of::<Commands>(),
+            listener.0.clone(),
+            |listener| async move {
+                let blocking_listener = listener.clone();
+                let message =
+                    tokio::task::spawn_blocking(move || receive_command(&blocking_listener))
+                        .await
+                        .ok()
+                        .flatten();
+                (message, listener)
+            },
+        )
+    }
+}
+
+#[cfg(all(not(unix), not(target_arch = "wasm32")))]
+mod tcp {
+    use super::Instance;
+    use crate::Message;
+
+    use std::io::{self, Write};
+    use std::net::{Ipv4Addr, TcpListener, TcpStream};
+    use std::sync::Arc;
+
+    use iced::{subscription, Subscription};
+
+    /// The port of the loopback interface the running instance listens on without unix
+    /// sockets, one that is not assigned to another service.
+    const PORT: u16 = 47_815;
+
+    /// Port on which other instances send their commands.
+    #[derive(Clone)]
+    pub struct Listener(Arc<TcpListener>);
+
+    /// Checks whether another instance is running by binding its port.
+    pub fn acquire() -> io::Result<Instance> {
+        match TcpListener::bind((Ipv4Addr::LOCALHOST, PORT)) {
+            Ok(listener) => Ok(Instance::Primary(Listener(Arc::new(listener)))),
+            Err(error) if error.kind() == io::ErrorKind::AddrInUse => {
+                let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, PORT))?;
+                stream.write_all(b"focus\n")?;
+                Ok(Instance::AlreadyRunning)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Blocks until a command is received on `listener`.
+    fn receive_command(listener: &TcpListener) -> Option<Message> {
+        let (stream, _) = listener.accept().ok()?;
+        stream.set_read_timeout(Some(super::COMMAND_TIMEOUT)).ok()?;
+        stream
+            .set_write_timeout(Some(super::COMMAND_TIMEOUT))
+            .ok()?;
+        super::answer(stream)
+    }
+
+    /// Subscribes to the commands sent by other instances.
+    pub fn commands(listener: &Listener) -> Subscription<Message> {
+        struct Commands;
+
+        subscription::unfold(
+            std::any::TypeId::of::<Commands>(),
+            listener.0.clone(),
+            |listener| async move {
+                let blocking_listener = listener.clone();
+                let message =
+                    tokio::task::spawn_blocking(move || receive_command(&blocking_listener))
+                        .await
+                        .ok()
+                        .flatten();
+                (message, listener)
+            },
+        )
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod fallback {
+    use crate::Message;
+
+    use iced::Subscription;
+
+    /// Placeholder in the browser, which opens a new tab for every instance.
+    #[derive(Clone)]
+    pub struct Listener;
+
+    /// Never produces a message in the browser.
+    pub fn commands(_listener: &Listener) -> Subscription<Message> {
+        Subscription::none()
+    }
+}
@@ -0,0 +1,128 @@
+use crate::error::BirthdayError;
+use crate::person::{self, Person};
+use crate::source::{self, PersonSource};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// A person as stored in a json file, the birthday is written as "YYYY-MM-DD".
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Entry {
+    last_name: String,
+    first_name: String,
+    birthday: NaiveDate,
+    gender: char,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    contact_url: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "person::deserialize_color",
+        skip_serializing_if = "Option::is_none"
+    )]
+    color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    department: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    opt_out: bool,
+}
+
+/// Parses the json array `content` the same way as a [`JsonFile`] is read.
+pub fn parse_persons(content: &[u8]) -> serde_json::Result<Vec<Person>> {
+    let entries: Vec<Entry> = serde_json::from_slice(content)?;
+    entries
+        .into_iter()
+        .map(|entry| {
+            Person::builder(
+                entry.last_name,
+                entry.first_name,
+                entry.birthday,
+                entry.gender.into(),
+            )
+            .image_url(entry.image_url)
+            .contact_url(entry.contact_url)
+            .color(entry.color)
+            .notes(entry.notes)
+            .department(entry.department)
+            .opt_out(entry.opt_out)
+            .build()
+            .map_err(serde::de::Error::custom)
+        })
+        .collect()
+}
+
+/// Formats `persons` as a json array like it is written to a [`JsonFile`].
+pub fn serialize_persons(persons: &[Person]) -> serde_json::Result<Vec<u8>> {
+    let entries: Vec<Entry> = persons
+        .iter()
+        .map(|person| Entry {
+            last_name: person.last_name.clone(),
+            first_name: person.first_name.clone(),
+            birthday: person.birthday,
+            gender: person.gender,
+            image_url: person.image_url.clone(),
+            contact_url: person.contact_url.clone(),
+            color: person.color.clone(),
+            notes: person.notes.clone(),
+            department: person.department.clone(),
+            opt_out: person.opt_out,
+        })
+        .collect();
+
+    let mut content = serde_json::to_vec_pretty(&entries)?;
+    content.push(b'\n');
+    Ok(content)
+}
+
+/// A json file containing an array of persons.
+pub struct JsonFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl JsonFile {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            modified: None,
+        }
+    }
+}
+
+impl PersonSource for JsonFile {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&mut self, _quiet: bool) -> Result<Vec<Person>, BirthdayError> {
+        self.modified = source::modified(&self.path);
+        let content = fs::read(&self.path).map_err(|error| BirthdayError::io(&self.path, error))?;
+        parse_persons(&content).map_err(|error| {
+            BirthdayError::Data(format!("error in {}: {error}", self.path.display()))
+        })
+    }
+
+    fn changed(&self) -> bool {
+        source::modified(&self.path) != self.modified
+    }
+
+    fn save(&mut self, persons: &[Person]) -> Result<(), BirthdayError> {
+        let content = serialize_persons(persons).map_err(|error| {
+            BirthdayError::Data(format!(
+                "error serializing {}: {error}",
+                self.path.display()
+            ))
+        })?;
+        source::replace_file(&self.path, &content)?;
+        self.modified = source::modified(&self.path);
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+#[cfg(feature = "gui")]
+use crate::{DataMessage, Message, UiMessage};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use iced_native::keyboard::{KeyCode, Modifiers};
+use serde::{Deserialize, Deserializer};
+
+/// The days listed by the week and the month view.
+pub const WEEK_DAYS: u64 = 7;
+pub const MONTH_DAYS: u64 = 30;
+
+/// The names of the keys that can be bound.
+const KEY_NAMES: [(&str, KeyCode); 58] = [
+    ("A", KeyCode::A),
+    ("B", KeyCode::B),
+    ("C", KeyCode::C),
+    ("D", KeyCode::D),
+    ("E", KeyCode::E),
+    ("F", KeyCode::F),
+    ("G", KeyCode::G),
+    ("H", KeyCode::H),
+    ("I", KeyCode::I),
+    ("J", KeyCode::J),
+    ("K", KeyCode::K),
+    ("L", KeyCode::L),
+    ("M", KeyCode::M),
+    ("N", KeyCode::N),
+    ("O", KeyCode::O),
+    ("P", KeyCode::P),
+    ("Q", KeyCode::Q),
+    ("R", KeyCode::R),
+    ("S", KeyCode::S),
+    ("T", KeyCode::T),
+    ("U", KeyCode::U),
+    ("V", KeyCode::V),
+    ("W", KeyCode::W),
+    ("X", KeyCode::X),
+    ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z),
+    ("0", KeyCode::Key0),
+    ("1", KeyCode::Key1),
+    ("2", KeyCode::Key2),
+    ("3", KeyCode::Key3),
+    ("4", KeyCode::Key4),
+    ("5", KeyCode::Key5),
+    ("6", KeyCode::Key6),
+    ("7", KeyCode::Key7),
+    ("8", KeyCode::Key8),
+    ("9", KeyCode::Key9),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+];
+
+/// What a key does.
+// the keys of toml tables can only be deserialized from strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Action {
+    Quit,
+    Reload,
+    ToggleFullscreen,
+    Today,
+    Week,
+    Month,
+    Statistics,
+    Search,
+    Editor,
+    NextPage,
+    PreviousPage,
+    Blank,
+    GreetingCards,
+}
+
+impl TryFrom<String> for Action {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Ok(match name.as_str() {
+            "quit" => Self::Quit,
+            "reload" => Self::Reload,
+            "toggle-fullscreen" => Self::ToggleFullscreen,
+            "today" => Self::Today,
+            "week" => Self::Week,
+            "month" => Self::Month,
+            "statistics" => Self::Statistics,
+            "search" => Self::Search,
+            "editor" => Self::Editor,
+            "next-page" => Self::NextPage,
+            "previous-page" => Self::PreviousPage,
+            "blank" => Self::Blank,
+            "greeting-cards" => Self::GreetingCards,
+            _ => return Err(format!("unknown action \"{name}\"")),
+        })
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Action {
+    pub fn message(self) -> Message {
+        match self {
+            Self::Quit => Message::Ui(UiMessage::Quit),
+            Self::Reload => Message::Data(DataMessage::Reload),
+            Self::ToggleFullscreen => Message::Ui(UiMessage::ToggleFullscreen),
+            Self::Today => Message::Ui(UiMessage::ShowToday),
+            Self::Week => Message::Ui(UiMessage::ShowUpcoming(WEEK_DAYS)),
+            Self::Month => Message::Ui(UiMessage::ShowUpcoming(MONTH_DAYS)),
+            Self::Statistics => Message::Ui(UiMessage::ToggleStatistics),
+            Self::Search => Message::Ui(UiMessage::OpenSearch),
+            Self::Editor => Message::Ui(UiMessage::OpenEditor),
+            Self::NextPage => Message::Ui(UiMessage::NextPage),
+            Self::PreviousPage => Message::Ui(UiMessage::PreviousPage),
+            Self::Blank => Message::Ui(UiMessage::Blank(None)),
+            Self::GreetingCards => Message::Ui(UiMessage::GreetingCards(None)),
+        }
+    }
+}
+
+/// A key with the modifiers that have to be held, parsed from e.g. `Ctrl+Shift+R`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    key: KeyCode,
+    modifiers: Modifiers,
+}
+
+impl FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+
+        let mut modifiers = Modifiers::empty();
+        for part in parts {
+            modifiers |= match part.to_lowercase().as_str() {
+                "ctrl" | "control" => Modifiers::CTRL,
+                "alt" => Modifiers::ALT,
+                "shift" => Modifiers::SHIFT,
+                "super" | "logo" => Modifiers::LOGO,
+                _ => return Err(format!("unknown modifier \"{part}\" in \"{s}\"")),
+            };
+        }
+
+        let key = KEY_NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, code)| *code)
+            .ok_or_else(|| format!("unknown key \"{key}\" in \"{s}\""))?;
+        Ok(Self { key, modifiers })
+    }
+}
+
+impl<'de> Deserialize<'de> for Binding {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// The bindings of all shortcuts, `Esc` always closes the search and the admin mode.
+#[derive(Debug, Clone)]
+pub struct Keymap(Vec<(Binding, Action)>);
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = [
+            ("Ctrl+Q", Action::Quit),
+            ("F5", Action::Reload),
+            ("F11", Action::ToggleFullscreen),
+            ("T", Action::Today),
+            ("W", Action::Week),
+            ("M", Action::Month),
+            ("S", Action::Statistics),
+            ("Ctrl+F", Action::Search),
+            ("Ctrl+E", Action::Editor),
+            ("Right", Action::NextPage),
+            ("PageDown", Action::NextPage),
+            ("Left", Action::PreviousPage),
+            ("PageUp", Action::PreviousPage),
+            ("B", Action::Blank),
+            ("G", Action::GreetingCards),
+        ];
+        Self(
+            bindings
+                .into_iter()
+                .map(|(binding, action)| {
+                    (binding.parse().expect("default bindings are valid"), action)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Keymap {
+    /// Returns the default keymap with the bindings of the actions in `keys` replaced.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_overrides(keys: &HashMap<Action, Binding>) -> Self {
+        let mut keymap = Self::default();
+        keymap.0.retain(|(_, action)| !keys.contains_key(action));
+        keymap
+            .0
+            .extend(keys.iter().map(|(action, binding)| (*binding, *action)));
+        keymap
+    }
+
+    /// Returns the action bound to `key` pressed with exactly `modifiers`.
+    pub fn action(&self, key: KeyCode, modifiers: Modifiers) -> Option<Action> {
+        self.0
+            .iter()
+            .find(|(binding, _)| binding.key == key && binding.modifiers == modifiers)
+            .map(|(_, action)| *action)
+    }
+}
@@ -0,0 +1,346 @@
+use crate::error::BirthdayError;
+use crate::{BirthdayDisplay, Message};
+
+use std::cell::Cell;
+use std::ffi::c_void;
+use std::io;
+use std::mem;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use iced::application::StyleSheet;
+use iced::futures::channel::mpsc;
+use iced::{Application, Command, Executor, Point, Size, Theme};
+use iced_graphics::window::{compositor::SurfaceError, Compositor as _};
+use iced_native::command::Action;
+use iced_native::{clipboard, renderer, user_interface, window, Event, UserInterface};
+use iced_wgpu::window::Compositor;
+use iced_wgpu::{Settings, Viewport};
+use raw_window_handle::{
+    HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle,
+    WaylandDisplayHandle, WaylandWindowHandle,
+};
+use wayland_client::protocol::wl_compositor::WlCompositor;
+use wayland_client::{Display, EventQueue, GlobalManager};
+use wayland_protocols::wlr::unstable::layer_shell::v1::client::zwlr_layer_shell_v1::{
+    self, ZwlrLayerShellV1,
+};
+use wayland_protocols::wlr::unstable::layer_shell::v1::client::zwlr_layer_surface_v1::{
+    self, Anchor, KeyboardInteractivity,
+};
+
+/// How long to wait for events of the compositor before the messages of the
+/// subscriptions are handled.
+const POLL_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// The layer of the compositor the display is shown on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Layer {
+    /// below the windows, on the wallpaper of the desktop
+    Background,
+    /// above the windows
+    Overlay,
+}
+
+impl From<Layer> for zwlr_layer_shell_v1::Layer {
+    fn from(layer: Layer) -> Self {
+        match layer {
+            Layer::Background => Self::Background,
+            Layer::Overlay => Self::Overlay,
+        }
+    }
+}
+
+type Runtime = iced_native::Runtime<
+    <BirthdayDisplay as Application>::Executor,
+    mpsc::UnboundedSender<Message>,
+    Message,
+>;
+
+/// The pointers to the connection and the surface that wgpu draws to.
+struct Handles {
+    display: *mut c_void,
+    surface: *mut c_void,
+}
+
+unsafe impl HasRawWindowHandle for Handles {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        let mut handle = WaylandWindowHandle::empty();
+        handle.surface = self.surface;
+        RawWindowHandle::Wayland(handle)
+    }
+}
+
+unsafe impl HasRawDisplayHandle for Handles {
+    fn raw_display_handle(&self) -> RawDisplayHandle {
+        let mut handle = WaylandDisplayHandle::empty();
+        handle.display = self.display;
+        RawDisplayHandle::Wayland(handle)
+    }
+}
+
+/// The size given by the compositor and whether it removed the surface.
+#[derive(Default)]
+struct Configuration {
+    size: Cell<Option<(u32, u32)>>,
+    closed: Cell<bool>,
+}
+
+/// Shows the display of `flags` as wlr-layer-shell surface on `layer` until the
+/// compositor closes it or the display quits.
+///
+/// The surface covers the whole output, or is `size` big in its top right corner. It
+/// takes no input so the clicks go to the desktop or the windows below it, the display
+/// is controlled with the instance socket or the control port like a window.
+pub fn run(
+    flags: <BirthdayDisplay as Application>::Flags,
+    layer: Layer,
+    size: Option<(u32, u32)>,
+    default_font: Option<&'static [u8]>,
+) -> Result<(), BirthdayError> {
+    let connection = Display::connect_to_env().map_err(|error| {
+        BirthdayError::Gui(format!(
+            "error connecting to the wayland compositor: {error}"
+        ))
+    })?;
+    let mut queue = connection.create_event_queue();
+    let attached = connection.attach(queue.token());
+    let globals = GlobalManager::new(&attached);
+    queue
+        .sync_roundtrip(&mut (), |_, _, _| {})
+        .map_err(wayland_error)?;
+
+    let compositor = globals
+        .instantiate_exact::<WlCompositor>(4)
+        .map_err(|error| BirthdayError::Gui(format!("no wayland compositor: {error}")))?;
+    let shell = globals
+        .instantiate_exact::<ZwlrLayerShellV1>(1)
+        .map_err(|error| {
+            BirthdayError::Gui(format!(
+                "the compositor does not support the wlr-layer-shell protocol: {error}"
+            ))
+        })?;
+
+    let surface = compositor.create_surface();
+    let layer_surface = shell.get_layer_surface(
+        &surface,
+        None,
+        layer.into(),
+        String::from("birthday_display"),
+    );
+    match size {
+        Some((width, height)) => {
+            layer_surface.set_anchor(Anchor::Top | Anchor::Right);
+            layer_surface.set_size(width, height);
+        }
+        None => {
+            layer_surface.set_anchor(Anchor::all());
+            layer_surface.set_size(0, 0);
+        }
+    }
+    // the other windows are neither moved aside nor lose the keyboard or the clicks
+    layer_surface.set_exclusive_zone(-1);
+    layer_surface.set_keyboard_interactivity(KeyboardInteractivity::None);
+    let region = compositor.create_region();
+    surface.set_input_region(Some(&region));
+    region.destroy();
+
+    let configuration = Rc::new(Configuration::default());
+    {
+        let configuration = configuration.clone();
+        layer_surface.quick_assign(move |layer_surface, event, _| match event {
+            zwlr_layer_surface_v1::Event::Configure {
+                serial,
+                width,
+                height,
+            } => {
+                layer_surface.ack_configure(serial);
+                configuration.size.set(Some((width, height)));
+            }
+            zwlr_layer_surface_v1::Event::Closed => configuration.closed.set(true),
+            _ => {}
+        });
+    }
+    // the compositor answers the first commit without a buffer with the size
+    surface.commit();
+    while configuration.size.get().is_none() && !configuration.closed.get() {
+        queue
+            .dispatch(&mut (), |_, _, _| {})
+            .map_err(wayland_error)?;
+    }
+
+    let handles = Handles {
+        display: connection.get_display_ptr().cast(),
+        surface: surface.as_ref().c_ptr().cast(),
+    };
+    let settings = Settings {
+        default_font,
+        ..Settings::from_env()
+    };
+    let (mut gpu, mut renderer) = Compositor::<Theme>::new(settings, Some(&handles))
+        .map_err(|error| BirthdayError::Gui(error.to_string()))?;
+    let mut target = gpu.create_surface(&handles);
+
+    let (sender, mut receiver) = mpsc::unbounded();
+    let executor = <BirthdayDisplay as Application>::Executor::new()
+        .map_err(|error| format!("error starting the runtime: {error}"))?;
+    let mut runtime = Runtime::new(executor, sender);
+    let (mut display, command) = runtime.enter(|| BirthdayDisplay::new(flags));
+    if run_command(&mut runtime, command) {
+        return Ok(());
+    }
+    runtime.track(display.subscription());
+
+    let mut cache = user_interface::Cache::default();
+    let mut messages = Vec::new();
+    let mut physical_size = (0, 0);
+    let mut redraw_at = Some(Instant::now());
+    while !configuration.closed.get() {
+        if let Some((width, height)) = configuration.size.take() {
+            // 0 leaves the size to the client, which then takes the requested one
+            let (default_width, default_height) = size.unwrap_or((800, 600));
+            let width = if width == 0 { default_width } else { width };
+            let height = if height == 0 { default_height } else { height };
+            physical_size = (width, height);
+            gpu.configure_surface(&mut target, width, height);
+            redraw_at = Some(Instant::now());
+        }
+
+        while let Ok(Some(message)) = receiver.try_next() {
+            messages.push(message);
+        }
+        if !messages.is_empty() {
+            for message in messages.drain(..) {
+                let command = runtime.enter(|| display.update(message));
+                if run_command(&mut runtime, command) {
+                    return Ok(());
+                }
+            }
+            runtime.track(display.subscription());
+            redraw_at = Some(Instant::now());
+        }
+
+        if redraw_at.is_some_and(|at| at <= Instant::now()) {
+            let viewport = Viewport::with_physical_size(
+                Size::new(physical_size.0, physical_size.1),
+                display.scale_factor(),
+            );
+            let mut interface = UserInterface::build(
+                display.view(),
+                viewport.logical_size(),
+                mem::take(&mut cache),
+                &mut renderer,
+            );
+            // the cursor is never on the surface so nothing is highlighted
+            let cursor = Point::new(-1.0, -1.0);
+            let (state, _) = interface.update(
+                &[Event::Window(
+                    window::Event::RedrawRequested(Instant::now()),
+                )],
+                cursor,
+                &mut renderer,
+                &mut clipboard::Null,
+                &mut messages,
+            );
+            let theme = display.theme();
+            let appearance = theme.appearance(&display.style());
+            interface.draw(
+                &mut renderer,
+                &theme,
+                &renderer::Style {
+                    text_color: appearance.text_color,
+                },
+                cursor,
+            );
+            cache = interface.into_cache();
+
+            redraw_at = match state {
+                user_interface::State::Updated {
+                    redraw_request: Some(window::RedrawRequest::At(at)),
+                } => Some(at),
+                user_interface::State::Updated {
+                    redraw_request: Some(window::RedrawRequest::NextFrame),
+                } => Some(Instant::now()),
+                _ => None,
+            };
+            match gpu.present::<&str>(
+                &mut renderer,
+                &mut target,
+                &viewport,
+                appearance.background_color,
+                &[],
+            ) {
+                Ok(()) => {}
+                Err(SurfaceError::Outdated | SurfaceError::Lost) => {
+                    gpu.configure_surface(&mut target, physical_size.0, physical_size.1);
+                    redraw_at = Some(Instant::now());
+                }
+                Err(SurfaceError::Timeout) => redraw_at = Some(Instant::now()),
+                Err(error) => return Err(BirthdayError::Gui(error.to_string())),
+            }
+        }
+
+        let timeout = match redraw_at {
+            Some(at) => at
+                .saturating_duration_since(Instant::now())
+                .min(POLL_TIMEOUT),
+            None => POLL_TIMEOUT,
+        };
+        wait(&mut queue, timeout)?;
+    }
+
+    Ok(())
+}
+
+/// Spawns the futures of `command` and returns whether it closes the display.
+///
+/// The other actions move, resize or focus windows, which a layer surface can't do.
+fn run_command(runtime: &mut Runtime, command: Command<Message>) -> bool {
+    let mut close = false;
+    for action in command.actions() {
+        match action {
+            Action::Future(future) => runtime.spawn(future),
+            Action::Window(window::Action::Close) => close = true,
+            _ => {}
+        }
+    }
+    close
+}
+
+/// Sends the requests to the compositor and handles its events, waits up to `timeout`
+/// for them.
+fn wait(queue: &mut EventQueue, timeout: Duration) -> Result<(), BirthdayError> {
+    queue
+        .dispatch_pending(&mut (), |_, _, _| {})
+        .map_err(wayland_error)?;
+    match queue.display().flush() {
+        Err(error) if error.kind() != io::ErrorKind::WouldBlock => {
+            return Err(wayland_error(error))
+        }
+        _ => {}
+    }
+
+    if let Some(guard) = queue.prepare_read() {
+        let mut descriptor = libc::pollfd {
+            fd: queue.display().get_connection_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let milliseconds = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+        // SAFETY: the descriptor is valid for the call and poll only writes to revents
+        if unsafe { libc::poll(&mut descriptor, 1, milliseconds) } > 0 {
+            guard.read_events().map_err(wayland_error)?;
+        } else {
+            guard.cancel();
+        }
+    }
+    queue
+        .dispatch_pending(&mut (), |_, _, _| {})
+        .map_err(wayland_error)?;
+    Ok(())
+}
+
+fn wayland_error(error: io::Error) -> BirthdayError {
+    BirthdayError::Gui(format!("error talking to the wayland compositor: {error}"))
+}
@@ -0,0 +1,174 @@
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use unicode_bidi::BidiInfo;
+
+use crate::person::Gender;
+
+/// The age a person turns, young children are counted in months.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Age {
+    Years(u32),
+    /// The years of a half birthday, half a year is added.
+    HalfYears(u32),
+    Months(u32),
+}
+
+/// The languages the cards can be shown in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    German,
+    English,
+    Hebrew,
+    Arabic,
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "de" => Ok(Self::German),
+            "en" => Ok(Self::English),
+            "he" => Ok(Self::Hebrew),
+            "ar" => Ok(Self::Arabic),
+            _ => Err(format!(
+                "unknown locale \"{s}\", expected one of de, en, he, ar"
+            )),
+        }
+    }
+}
+
+impl Locale {
+    /// Returns whether the language is written from right to left.
+    pub fn rtl(self) -> bool {
+        matches!(self, Self::Hebrew | Self::Arabic)
+    }
+
+    /// Formats day and month of `date` like it is usual for the language.
+    pub fn day_and_month(self, date: NaiveDate) -> String {
+        let format = match self {
+            Self::German => "%d.%m.",
+            Self::English => "%B %-d",
+            Self::Hebrew => "%d.%m",
+            Self::Arabic => "%d/%m",
+        };
+        date.format(format).to_string()
+    }
+
+    /// Returns the title of the person for `gender` including a trailing space.
+    fn title(self, gender: Gender) -> &'static str {
+        match (self, gender) {
+            (Self::German, Gender::Male) => "Herr ",
+            (Self::German, Gender::Female) => "Frau ",
+            _ => "",
+        }
+    }
+
+    /// Returns the sentence announcing the birthday of `name` on `date`, which is
+    /// today if `today`, turning `age` if known.
+    pub fn banner(
+        self,
+        gender: Gender,
+        name: &str,
+        date: NaiveDate,
+        today: bool,
+        age: Option<Age>,
+    ) -> String {
+        let title = self.title(gender);
+        let date = self.day_and_month(date);
+        let age = age.map(|age| self.age(age));
+        match (self, today, age) {
+            (Self::German, true, Some(age)) => format!("{title}{name} wird heute {age} alt."),
+            (Self::German, false, Some(age)) => {
+                format!("{title}{name} wird am {date} {age} alt.")
+            }
+            (Self::German, true, None) => format!("{title}{name} hat heute Geburtstag."),
+            (Self::German, false, None) => format!("{title}{name} hat am {date} Geburtstag."),
+            (Self::English, true, Some(age)) => format!("{name} turns {age} today."),
+            (Self::English, false, Some(age)) => format!("{name} turns {age} on {date}."),
+            (Self::English, true, None) => format!("{name} celebrates their birthday today."),
+            (Self::English, false, None) => format!("{name} celebrates their birthday on {date}."),
+            (Self::Hebrew, true, Some(age)) => format!("יום הולדת {age} היום ל{name}"),
+            (Self::Hebrew, false, Some(age)) => format!("יום הולדת {age} ל{name} ב-{date}"),
+            (Self::Hebrew, true, None) => format!("יום הולדת היום ל{name}"),
+            (Self::Hebrew, false, None) => format!("יום הולדת ל{name} ב-{date}"),
+            (Self::Arabic, true, Some(age)) => format!("عيد ميلاد {name} الـ{age} اليوم"),
+            (Self::Arabic, false, Some(age)) => format!("عيد ميلاد {name} الـ{age} في {date}"),
+            (Self::Arabic, true, None) => format!("عيد ميلاد {name} اليوم"),
+            (Self::Arabic, false, None) => format!("عيد ميلاد {name} في {date}"),
+        }
+    }
+
+    /// Returns the sentence announcing that `name` is alive `days` on `date`, which is
+    /// today if `today`.
+    pub fn milestone(
+        self,
+        gender: Gender,
+        name: &str,
+        date: NaiveDate,
+        today: bool,
+        days: u64,
+    ) -> String {
+        let title = self.title(gender);
+        let date = self.day_and_month(date);
+        match (self, today) {
+            (Self::German, true) => format!("{title}{name} ist heute {days} Tage alt."),
+            (Self::German, false) => format!("{title}{name} ist am {date} {days} Tage alt."),
+            (Self::English, true) => format!("{name} is {days} days old today."),
+            (Self::English, false) => format!("{name} is {days} days old on {date}."),
+            (Self::Hebrew, true) => format!("{days} ימים ל{name} היום"),
+            (Self::Hebrew, false) => format!("{days} ימים ל{name} ב-{date}"),
+            (Self::Arabic, true) => format!("{name} يبلغ {days} يومًا اليوم"),
+            (Self::Arabic, false) => format!("{name} يبلغ {days} يومًا في {date}"),
+        }
+    }
+
+    /// Returns how long it is until a birthday in `days`, which is at least one.
+    pub fn countdown(self, days: i64) -> String {
+        match (self, days) {
+            (Self::German, 1) => String::from("morgen"),
+            (Self::German, _) => format!("in {days} Tagen"),
+            (Self::English, 1) => String::from("tomorrow"),
+            (Self::English, _) => format!("in {days} days"),
+            (Self::Hebrew, 1) => String::from("מחר"),
+            (Self::Hebrew, _) => format!("בעוד {days} ימים"),
+            (Self::Arabic, 1) => String::from("غدًا"),
+            (Self::Arabic, _) => format!("بعد {days} أيام"),
+        }
+    }
+
+    /// Formats `age` with its unit unless the sentences of the language leave it out.
+    fn age(self, age: Age) -> String {
+        match (self, age) {
+            (Self::German, Age::Years(years)) => format!("{years} Jahre"),
+            (Self::German, Age::HalfYears(years)) => format!("{years}½ Jahre"),
+            (Self::German, Age::Months(months)) => format!("{months} Monate"),
+            (Self::English, Age::Years(years)) => years.to_string(),
+            (Self::English, Age::HalfYears(years)) => format!("{years}½"),
+            (Self::English, Age::Months(months)) => format!("{months} months old"),
+            (Self::Hebrew, Age::Years(years)) => years.to_string(),
+            (Self::Hebrew, Age::HalfYears(years)) => format!("{years}½"),
+            (Self::Hebrew, Age::Months(months)) => format!("{months} חודשים"),
+            (Self::Arabic, Age::Years(years)) => years.to_string(),
+            (Self::Arabic, Age::HalfYears(years)) => format!("{years}½"),
+            (Self::Arabic, Age::Months(months)) => format!("{months} شهرًا"),
+        }
+    }
+
+    /// Returns `text` in the order its characters are drawn in.
+    ///
+    /// The renderer draws every character from left to right, so right to left text
+    /// is reordered with the unicode bidirectional algorithm first.
+    pub fn visual(self, text: &str) -> String {
+        if !self.rtl() && text.is_ascii() {
+            return text.to_string();
+        }
+        let info = BidiInfo::new(text, None);
+        info.paragraphs
+            .iter()
+            .map(|paragraph| info.reorder_line(paragraph, paragraph.range.clone()))
+            .collect()
+    }
+}
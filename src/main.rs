@@ -1,204 +1,3251 @@
+// the commands only use a part of the persons, cards and settings shared with the gui
+#![cfg_attr(not(feature = "gui"), allow(dead_code))]
+
+#[cfg(not(target_arch = "wasm32"))]
+mod audit;
+#[cfg(feature = "gui")]
+mod browser;
+#[cfg(not(target_arch = "wasm32"))]
+mod bundle;
+#[cfg(feature = "gui")]
+mod calendar;
+#[cfg(feature = "gui")]
+mod changes;
+#[cfg(not(target_arch = "wasm32"))]
+mod check;
+mod clock;
+mod collation;
+#[cfg(not(target_arch = "wasm32"))]
+mod config;
+#[cfg(all(not(target_arch = "wasm32"), feature = "gui"))]
+mod control;
 mod csv;
-mod error_wrapper;
+#[cfg(feature = "gui")]
+mod dim;
+#[cfg(feature = "gui")]
+mod editor;
+#[cfg(not(target_arch = "wasm32"))]
+mod encrypted;
+mod error;
+#[cfg(not(target_arch = "wasm32"))]
+mod export;
+#[cfg(not(target_arch = "wasm32"))]
+mod feed;
+#[cfg(test)]
+mod fixtures;
+#[cfg(all(not(target_arch = "wasm32"), feature = "network", feature = "gui"))]
+mod fleet;
+#[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+mod google;
+#[cfg(feature = "gpio")]
+mod gpio;
+#[cfg(not(target_arch = "wasm32"))]
+mod home_assistant;
+#[cfg(all(not(target_arch = "wasm32"), feature = "gui"))]
+mod image_budget;
+#[cfg(all(not(target_arch = "wasm32"), feature = "network", feature = "gui"))]
+mod image_refresh;
+mod index;
+#[cfg(feature = "gui")]
+mod instance;
+#[cfg(not(target_arch = "wasm32"))]
+mod json;
+mod keymap;
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "layer-shell",
+    not(feature = "glow")
+))]
+mod layer;
+mod locale;
+#[cfg(not(target_arch = "wasm32"))]
+mod merge;
+#[cfg(all(not(target_arch = "wasm32"), feature = "gui"))]
+mod monitor;
+#[cfg(not(target_arch = "wasm32"))]
+mod mqtt;
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(not(target_arch = "wasm32"))]
+mod notify;
+#[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+mod outlook;
+#[cfg(all(not(target_arch = "wasm32"), feature = "gui"))]
+mod panic;
+#[cfg(not(target_arch = "wasm32"))]
+mod pdf;
+mod percent;
 mod person;
+mod photo;
+#[cfg(all(not(target_arch = "wasm32"), feature = "network", feature = "gui"))]
+mod politeness;
+mod qr;
+mod redact;
+#[cfg(not(target_arch = "wasm32"))]
+mod runtime;
+mod schedule;
+#[cfg(feature = "gui")]
+mod screensaver;
+#[cfg(feature = "scripting")]
+mod script;
+#[cfg(feature = "gui")]
+mod search;
+#[cfg(not(target_arch = "wasm32"))]
+mod server;
+#[cfg(not(target_arch = "wasm32"))]
+mod signal;
+#[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+mod sms;
+#[cfg(all(not(any(target_arch = "wasm32", feature = "glow")), feature = "gui"))]
+mod snapshot;
+mod source;
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite"))]
+mod sqlite;
+#[cfg(not(target_arch = "wasm32"))]
+mod state;
+#[cfg(feature = "gui")]
+mod stats;
+mod style;
+#[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+mod sync;
+#[cfg(feature = "gui")]
+mod ticker;
+#[cfg(feature = "gui")]
+mod upcoming;
+#[cfg(all(not(target_arch = "wasm32"), feature = "network", feature = "gui"))]
+mod update;
+#[cfg(all(not(target_arch = "wasm32"), feature = "gui"))]
+mod watchdog;
+#[cfg(target_arch = "wasm32")]
+mod web;
+#[cfg(not(target_arch = "wasm32"))]
+mod weekly;
+
+#[cfg(all(target_arch = "wasm32", not(feature = "network")))]
+compile_error!("the browser downloads the csv file, build it with the network feature");
+#[cfg(all(target_arch = "wasm32", not(feature = "gui")))]
+compile_error!("the browser only shows the gui, build it with the gui feature");
 
-use crate::csv::get_persons;
-use crate::error_wrapper::ErrorDisplayWrapper;
-use crate::person::Person;
+#[cfg(feature = "gui")]
+use crate::calendar::Calendar;
+#[cfg(feature = "gui")]
+use crate::changes::Changes;
+#[cfg(feature = "gui")]
+use crate::clock::Clock;
+#[cfg(all(not(target_arch = "wasm32"), feature = "gui"))]
+use crate::control::ControlPort;
+#[cfg(feature = "gui")]
+use crate::dim::Dimmed;
+#[cfg(feature = "gui")]
+use crate::editor::{Editor, EditorMessage};
+#[cfg(feature = "gui")]
+use crate::index::{BirthdayIndex, MilestoneIndex};
+#[cfg(feature = "gui")]
+use crate::instance::Listener;
+#[cfg(feature = "gui")]
+use crate::keymap::Keymap;
+#[cfg(feature = "gui")]
+use crate::locale::Locale;
+#[cfg(feature = "gui")]
+use crate::person::{CardSettings, Occasion, Person};
+use crate::schedule::{DimRule, Interval, TimeRange};
+#[cfg(feature = "gui")]
+use crate::screensaver::Screensaver;
+#[cfg(feature = "gui")]
+use crate::source::PersonSource;
+#[cfg(feature = "gui")]
+use crate::stats::Statistics;
+#[cfg(all(not(target_arch = "wasm32"), feature = "gui"))]
+use crate::watchdog::Watchdog;
 
-use std::borrow::Cow;
-use std::collections::HashMap;
-use std::error::Error;
+#[cfg(feature = "gui")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "gui")]
 use std::fmt::Debug;
 use std::path::PathBuf;
+#[cfg(feature = "gui")]
+use std::sync::{Arc, Mutex};
 
+#[cfg(all(feature = "network", feature = "gui"))]
 use bytes::Bytes;
-use chrono::{Datelike, Utc};
-use clap::{ArgGroup, Parser};
+use chrono::NaiveDate;
+#[cfg(feature = "gui")]
+use chrono::{DateTime, Days, Local, NaiveTime};
+use clap::{ArgGroup, Parser, Subcommand};
+#[cfg(all(feature = "network", feature = "gui"))]
 use reqwest::{Client, RequestBuilder};
 
+#[cfg(feature = "gui")]
+use iced::futures::future::{abortable, AbortHandle, Aborted};
+#[cfg(all(not(target_arch = "wasm32"), feature = "network", feature = "gui"))]
+use iced::futures::FutureExt;
+#[cfg(feature = "gui")]
+use iced::keyboard::{self, KeyCode};
+#[cfg(feature = "gui")]
 use iced::time::{every, Duration, Instant};
+#[cfg(feature = "gui")]
 use iced::widget::image::Handle;
-use iced::widget::{container, row};
-use iced::{Application, Command, Element, Length, Settings, Subscription};
+#[cfg(feature = "gui")]
+use iced::widget::{button, column, container, text, text_input};
+#[cfg(feature = "gui")]
+use iced::{event, subscription, theme, touch};
+#[cfg(feature = "gui")]
+use iced::{
+    Alignment, Application, Color, Command, Element, Event, Length, Point, Size, Subscription,
+};
 
 #[derive(Parser, Default)]
 #[command(author, version, about, long_about = None)]
 #[command(group(ArgGroup::new("verbosity").args(["quiet", "verbose"])))]
 struct Cli {
-    /// csv file in format "lastname,firstname,dd.mm.YYYY,gender,[image url]"
+    /// csv file in format "lastname,firstname,dd.mm.YYYY,gender,[image url],[contact url]"
+    /// or a ".json" file with an array of objects with these fields,
+    /// encrypted if it ends in ".age", ".gpg" or ".asc", or a ".bday" bundle
     file: PathBuf,
 
+    /// age identity or file with the gpg passphrase to decrypt FILE, asked for if missing
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "PATH")]
+    key_file: Option<PathBuf>,
+
+    /// read further settings from this toml file, downloaded at the start if it is an
+    /// http or https url
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// keep the config file downloaded from a url here to start with it while the server
+    /// can not be reached
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    #[arg(long, value_name = "PATH", requires = "config")]
+    config_cache: Option<PathBuf>,
+
+    /// apply the settings of the section [profile.<NAME>] of the config file
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "NAME", requires = "config")]
+    profile: Option<String>,
+
     #[arg(short, long)]
     quiet: bool,
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
 
+    /// show names, birthdays and links of persons in log messages instead of hashes
+    #[arg(long)]
+    log_pii: bool,
+
+    /// print the error the program exits with as json on the standard error, with its
+    /// kind, message, exit code and the file, line or url
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long)]
+    error_json: bool,
+
+    /// what to do with persons born in the future or more than 120 years ago
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_enum, default_value_t, value_name = "SEVERITY")]
+    implausible_birthdays: source::Implausible,
+
+    /// leave out the persons with an opt-out column, pass false to show them anyway
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set, value_name = "BOOL")]
+    respect_optout: bool,
+
+    /// the kinds of image urls that are accepted, persons with other ones are skipped
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "SCHEMES",
+        default_values_t = <person::ImageScheme as clap::ValueEnum>::value_variants().to_vec()
+    )]
+    image_schemes: Vec<person::ImageScheme>,
+
     /// hide errors in gui
     #[arg(short, long)]
     silent: bool,
+
+    /// require this PIN before persons can be edited in the admin mode
+    #[arg(long, value_name = "PIN")]
+    admin_pin: Option<String>,
+
+    /// start even if another instance is already running
+    #[arg(long)]
+    multiple_instances: bool,
+
+    /// blank the screen during this daily time range, e.g. "22:00-06:00"
+    #[arg(long, value_name = "HH:MM-HH:MM")]
+    blank_between: Option<TimeRange>,
+
+    /// show a clock while the screen is blank
+    #[arg(long, requires = "blank_between")]
+    blank_clock: bool,
+
+    /// dim the screen to PERCENT brightness, e.g. "Mon-Fri 18:00-22:00=40";
+    /// can be repeated and the first matching rule is used
+    #[arg(long, value_name = "[DAYS] HH:MM-HH:MM=PERCENT")]
+    dim: Vec<DimRule>,
+
+    /// show details when a card is tapped and browse upcoming birthdays by swiping
+    #[arg(long)]
+    touch: bool,
+
+    /// show a qr code with this link on every card, "{first_name}", "{last_name}" and
+    /// "{name}" are replaced, e.g. "mailto:team@example.com?subject=Alles%20Gute%20{first_name}"
+    #[arg(long, value_name = "TEMPLATE")]
+    greeting_url: Option<String>,
+
+    /// on days without birthdays show the next ones within this many days
+    #[arg(long, value_name = "DAYS", default_value_t = 0)]
+    look_ahead: u64,
+
+    /// on days without birthdays show the next one as a large card counting down to it
+    #[arg(long)]
+    hero: bool,
+
+    /// use a black and white theme and only refresh on changes for e-paper displays
+    #[arg(long)]
+    eink: bool,
+
+    /// also redraw the screen every this many seconds, otherwise it is only redrawn
+    /// when something changes and at midnight
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    refresh_interval: Option<u64>,
+
+    /// read the persons again every this long, e.g. 30m or 1h, even if the source can not
+    /// tell whether it changed
+    #[arg(long, value_name = "DURATION")]
+    reload_every: Option<Interval>,
+
+    /// show where the persons were loaded from and how many images could be loaded
+    /// at the bottom of the window
+    #[arg(long)]
+    status_bar: bool,
+
+    /// scroll the birthdays of the next 30 days through a line at the bottom of the window
+    #[arg(long)]
+    ticker: bool,
+
+    /// show the photos of all persons one after another on days without birthdays
+    #[arg(long)]
+    screensaver: bool,
+
+    /// show the photos in this folder instead of the ones of the persons with --screensaver
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "DIR", requires = "screensaver")]
+    screensaver_dir: Option<PathBuf>,
+
+    /// the language of the cards, one of de, en, he, ar
+    #[arg(long, value_name = "LOCALE", default_value = "de")]
+    locale: locale::Locale,
+
+    /// the layout of the cards, one of photo-top, photo-left, text-only, compact-list
+    #[arg(long, value_name = "LAYOUT", default_value = "photo-top")]
+    layout: person::Layout,
+
+    /// how photos are fitted into the cards, one of contain, cover, fixed-height
+    #[arg(long, value_name = "FIT", default_value = "contain")]
+    photo_fit: person::PhotoFit,
+
+    /// the largest part of the height of a card a photo may take up, e.g. 0.6
+    #[arg(long, value_name = "FRACTION", value_parser = parse_fraction)]
+    photo_height: Option<f32>,
+
+    /// count the age of children younger than this many months in months, 0 to disable
+    #[arg(long, value_name = "MONTHS", default_value_t = person::MONTHS_UNTIL)]
+    months_until: u32,
+
+    /// also celebrate the half birthday six months after the birthday
+    #[arg(long)]
+    half_birthdays: bool,
+
+    /// the banner of half birthdays, "{first_name}", "{last_name}", "{name}" and "{age}"
+    /// are replaced, e.g. "{first_name} ist heute {age} Jahre alt!"
+    #[arg(long, value_name = "TEMPLATE", requires = "half_birthdays")]
+    half_birthday_template: Option<String>,
+
+    /// the greeting above the card on greeting cards with the same placeholders as
+    /// --half-birthday-template
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        default_value = "Alles Gute zum Geburtstag, {first_name}!"
+    )]
+    greeting_template: String,
+
+    /// write the greeting cards to this directory instead of the temporary one
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "DIR")]
+    greeting_dir: Option<PathBuf>,
+
+    /// also announce every multiple of this many days a person is alive, e.g. 10000
+    #[arg(long, value_name = "DAYS", value_parser = clap::value_parser!(u64).range(100..))]
+    milestones: Option<u64>,
+
+    /// also show the banner of every card in this language below the first one
+    #[arg(long, value_name = "LOCALE")]
+    second_locale: Option<locale::Locale>,
+
+    /// show the two languages one after another for this many seconds instead of both
+    #[arg(long, value_name = "SECONDS", requires = "second_locale")]
+    alternate_every: Option<u64>,
+
+    /// keep at most this many megabytes of decoded photos in memory, only the ones of the
+    /// shown and upcoming birthdays, the others are read again from the image cache
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "MB")]
+    image_memory: Option<usize>,
+
+    /// the directory downloaded photos are kept in for --image-memory, defaults to one in
+    /// the cache directory of the user
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    #[arg(long, value_name = "DIR", requires = "image_memory")]
+    image_cache: Option<PathBuf>,
+
+    /// ask the image servers every this many minutes whether the photos changed and show
+    /// the new ones
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    #[arg(long, value_name = "MINUTES", value_parser = clap::value_parser!(u64).range(1..))]
+    image_refresh: Option<u64>,
+
+    /// start at most this many photo downloads per second from one server, with a random
+    /// delay added, 0 for no limit
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network", feature = "gui"))]
+    #[arg(long, value_name = "N", default_value_t = politeness::DEFAULT_RATE)]
+    host_rate: u32,
+
+    /// stop downloading photos larger than this many megabytes, e.g. a video linked by
+    /// mistake
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network", feature = "gui"))]
+    #[arg(long, value_name = "MB", default_value_t = photo::DEFAULT_MAX_DOWNLOAD)]
+    max_image_size: usize,
+
+    /// post the state of the display as json to this url, e.g. to see which of many
+    /// kiosks are healthy
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    #[arg(long, value_name = "URL")]
+    report_url: Option<String>,
+
+    /// post the state every this long, e.g. 1m or 1h, instead of every 5 minutes
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    #[arg(long, value_name = "DURATION", requires = "report_url")]
+    report_every: Option<Interval>,
+
+    /// ask this url once a day for the latest version, e.g. the latest release of the
+    /// GitHub api, and show a small hint if there is a newer one
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    #[arg(long, value_name = "URL")]
+    update_url: Option<String>,
+
+    /// draw text with this ttf or otf font, e.g. one containing hebrew or arabic letters
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "PATH")]
+    font: Option<PathBuf>,
+
+    /// show white and yellow text on black for viewers with low vision
+    #[arg(long)]
+    high_contrast: bool,
+
+    /// enlarge text and cards by this factor, e.g. 1.5 to read them from further away
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0, value_parser = parse_scale)]
+    scale: f64,
+
+    /// enlarge or shrink text and cards to fill the window with the cards of the day instead of --scale
+    #[arg(long, conflicts_with = "scale")]
+    auto_scale: bool,
+
+    /// the title of the window and its name in the taskbar, e.g. to tell several displays apart
+    #[arg(long, value_name = "TITLE", default_value = "Birthday Display")]
+    title: String,
+
+    /// show this png or jpeg file as the icon of the window
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "PATH")]
+    icon: Option<PathBuf>,
+
+    /// open the window on this monitor, counted from 0
+    #[arg(long, value_name = "INDEX")]
+    monitor: Option<usize>,
+
+    /// open a window on every monitor, the control port, the watchdog, the status file
+    /// and the gpio pin belong to the one on the first monitor
+    #[arg(long, conflicts_with = "monitor")]
+    all_monitors: bool,
+
+    /// show the birthdays on this layer of a wayland compositor like sway instead of in a
+    /// window, e.g. on the wallpaper of the desktop
+    #[cfg(all(
+        not(target_arch = "wasm32"),
+        feature = "layer-shell",
+        not(feature = "glow")
+    ))]
+    #[arg(long, value_enum, conflicts_with_all = ["monitor", "all_monitors"])]
+    layer: Option<layer::Layer>,
+
+    /// the size of the layer surface in its top right corner of the monitor, by default it
+    /// covers the whole monitor
+    #[cfg(all(
+        not(target_arch = "wasm32"),
+        feature = "layer-shell",
+        not(feature = "glow")
+    ))]
+    #[arg(long, value_name = "WIDTHxHEIGHT", requires = "layer", value_parser = parse_size)]
+    layer_size: Option<(u32, u32)>,
+
+    /// write an image of the birthdays to this png file instead of opening a window
+    #[arg(long, value_name = "PATH")]
+    snapshot: Option<PathBuf>,
+
+    /// run as if today was this date, e.g. for a snapshot, a demo or testing the notifiers
+    #[arg(long, value_name = "dd.mm.YYYY", value_parser = parse_date)]
+    date: Option<NaiveDate>,
+
+    /// the size of the snapshot
+    #[arg(long, value_name = "WIDTHxHEIGHT", requires = "snapshot", value_parser = parse_size, default_value = "1920x1080")]
+    snapshot_size: (u32, u32),
+
+    /// pack FILE, the config file, the font and the cached photos into this archive,
+    /// e.g. out.bday, which can be started instead of FILE on another display
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "PATH", conflicts_with = "snapshot")]
+    bundle: Option<PathBuf>,
+
+    /// raise this gpio pin (BCM numbering) while there are birthdays today, e.g. for a relay
+    #[cfg(feature = "gpio")]
+    #[arg(long, value_name = "PIN")]
+    gpio_pin: Option<u8>,
+
+    /// also accept the commands of the instance socket on this local tcp port
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "PORT")]
+    control_port: Option<u16>,
+
+    /// customize the persons and their cards with the hooks of this rhai script
+    #[cfg(feature = "scripting")]
+    #[arg(long, value_name = "PATH")]
+    script: Option<PathBuf>,
+
+    /// run the downloads and file reads of the window on this many threads instead of one
+    /// per cpu core, e.g. 1 on devices with little memory
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "COUNT", value_parser = clap::value_parser!(u16).range(1..))]
+    worker_threads: Option<u16>,
+
+    /// write whether the display is running or crashed to this file for a watchdog
+    #[arg(long, value_name = "PATH")]
+    status_file: Option<PathBuf>,
+
+    /// keep the pinned date and when the notifiers fired in this file so a restart
+    /// does not show another day or notify twice
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "PATH")]
+    state_file: Option<PathBuf>,
+
+    /// append which persons were shown or notified about and when to this file
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "PATH")]
+    audit_log: Option<PathBuf>,
+
+    /// notify the systemd watchdog while the gui responds or, with a PATH, write the
+    /// time of the last response to this file
+    #[cfg(not(target_arch = "wasm32"))]
+    #[arg(long, value_name = "PATH", num_args = 0..=1, require_equals = true)]
+    watchdog: Option<Option<PathBuf>>,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// only show the error message of a crashed instance
+    #[arg(long, value_name = "MESSAGE", hide = true)]
+    show_panic: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// write the birthdays to a file instead of showing them
+    #[cfg(not(target_arch = "wasm32"))]
+    Export {
+        /// replace names and links with made up ones, e.g. to share the file in a bug report
+        #[arg(long)]
+        anonymize: bool,
+
+        /// the file the persons are written to without a format, defaults to the standard output
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        #[command(subcommand)]
+        format: Option<export::Format>,
+    },
+    /// keep the birthdays in an online calendar up to date
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    Sync {
+        /// only print the changes without making them
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(subcommand)]
+        target: sync::Target,
+    },
+    /// send today's birthdays to the notifiers of the config file, e.g. daily from cron
+    #[cfg(not(target_arch = "wasm32"))]
+    Notify {
+        /// only print the messages without sending them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// keep running and fire every notifier daily at the time of the config file
+        #[arg(long)]
+        daemon: bool,
+    },
+    /// combine the persons of FILE with those of another file
+    #[cfg(not(target_arch = "wasm32"))]
+    Merge {
+        /// the file whose persons are added
+        other: PathBuf,
+
+        /// the file the merged persons are written to
+        #[arg(short, long, value_name = "PATH")]
+        output: PathBuf,
+
+        /// keep the entry of this file if a person differs instead of asking
+        #[arg(long, value_enum)]
+        prefer: Option<merge::Prefer>,
+    },
+    /// serve the csv file and the web build, telling the browsers about changes over a websocket
+    #[cfg(not(target_arch = "wasm32"))]
+    Serve {
+        /// the address to listen on
+        #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1:8080")]
+        listen: std::net::SocketAddr,
+
+        /// directory with the output of `trunk build`
+        #[arg(long, value_name = "PATH")]
+        web_root: Option<PathBuf>,
+    },
+    /// report the rows that were skipped and the photos on the disk or in the file that
+    /// can not be read, failing if there are any
+    #[cfg(not(target_arch = "wasm32"))]
+    Check,
 }
 
-/// Types of updates for the BirthdayDisplay application.
-#[derive(Debug)]
+/// Parses a window size like "400x600".
+fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| String::from("expected WIDTHxHEIGHT"))?;
+    let parse = |number: &str| {
+        number
+            .trim()
+            .parse::<u32>()
+            .map_err(|error| error.to_string())
+    };
+
+    Ok((parse(width)?, parse(height)?))
+}
+
+/// Parses a fraction above 0 and at most 1.
+fn parse_fraction(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(fraction) if fraction > 0.0 && fraction <= 1.0 => Ok(fraction),
+        _ => Err(format!(
+            "invalid fraction \"{value}\", expected a number above 0 and at most 1"
+        )),
+    }
+}
+
+/// Parses a scale factor between 0.5 and 4.
+fn parse_scale(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(scale) if (MIN_SCALE..=MAX_SCALE).contains(&scale) => Ok(scale),
+        _ => Err(format!(
+            "invalid scale \"{value}\", expected a number from 0.5 to 4"
+        )),
+    }
+}
+
+/// Parses a date in the format of the csv file.
+fn parse_date(value: &str) -> Result<NaiveDate, error::BirthdayError> {
+    NaiveDate::parse_from_str(value, csv::custom_date_format::FORMAT)
+        .map_err(|_| error::BirthdayError::date(value, "dd.mm.YYYY"))
+}
+
+/// Types of updates for the BirthdayDisplay application, grouped by where they come from.
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone)]
 #[non_exhaustive]
 pub enum Message {
+    /// Persons, photos or answers of servers arrived.
+    Data(DataMessage),
+    /// The user or another program interacted with the display.
+    Ui(UiMessage),
+    /// A periodic subscription fired.
+    Timer(TimerMessage),
+    /// Update of the admin mode.
+    Editor(EditorMessage),
+}
+
+/// Persons, photos or answers of servers arrived, or have to be read again.
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum DataMessage {
+    /// The photo downloaded from the url given as second String.
+    ImageReceived(Result<Handle, String>, String),
+    /// A download was cancelled because its image is not needed anymore.
+    DownloadCancelled,
+    /// The csv file was downloaded in the browser.
+    #[cfg(target_arch = "wasm32")]
+    PersonsReceived(Result<Vec<u8>, String>),
+    /// Read the persons again, e.g. after the csv file was changed.
+    Reload,
+    /// The persons were read from the source.
+    #[cfg(not(target_arch = "wasm32"))]
+    PersonsLoaded(Result<Vec<Person>, String>),
+    /// The photo at the url changed if there is a new version.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    ImageRefreshed(String, Option<image_refresh::Refreshed>),
+    /// The state was posted or the reason it could not be.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    StatusReported(Result<(), String>),
+    /// The newer version if there is one.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    UpdateChecked(Result<Option<String>, String>),
+}
+
+/// The user or another program through the socket interacted with the display.
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum UiMessage {
+    /// Switch between the birthday and the statistics screen.
+    ToggleStatistics,
+    /// Show the search overlay.
+    OpenSearch,
+    /// Hide the search overlay or the admin mode.
+    CloseOverlay,
+    /// The content of the search input changed.
+    SearchChanged(String),
+    /// Show the admin mode to add or correct persons.
+    OpenEditor,
+    /// Another instance was started and asks to focus this one instead.
+    Focus,
+    /// Switch between the card and the details of the person with the given index.
+    ToggleDetails(usize),
+    /// The window was resized to the logical width and height.
+    Resized(u32, u32),
+    /// A finger touched the screen at the position.
+    SwipeStarted(Point),
+    /// A finger was lifted from the screen at the position.
+    SwipeEnded(Point),
+    /// Open the url in the default browser.
+    OpenLink(String),
+    /// Show the birthdays of the given date until told otherwise, today if missing.
+    SetDate(Option<NaiveDate>),
+    /// Show the next birthdays after the shown ones.
+    NextPage,
+    /// Show the birthdays before the shown ones, but not before today.
+    PreviousPage,
+    /// Blank the screen or show it again, toggling if missing.
+    Blank(Option<bool>),
+    /// Write greeting cards of the persons with the name or of the shown cards if missing.
+    GreetingCards(Option<String>),
+    /// A key was pressed outside of a text input.
+    KeyPressed(KeyCode, keyboard::Modifiers),
+    /// Close the window.
+    Quit,
+    /// Switch between fullscreen and the normal window.
+    ToggleFullscreen,
+    /// Show the birthdays of today.
+    ShowToday,
+    /// List the birthdays of this many days starting today.
+    ShowUpcoming(u64),
+}
+
+/// A periodic subscription fired.
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum TimerMessage {
     /// Periodic update with the time that has passed.
     UpdateDay(Instant),
-    /// Data with the associated url as second String.
-    DataReceived(Result<Handle, String>, String),
+    /// Tell the watchdog that the gui still responds.
+    #[cfg(not(target_arch = "wasm32"))]
+    Heartbeat,
+    /// Apply the config file again if it was changed.
+    #[cfg(not(target_arch = "wasm32"))]
+    CheckConfig,
+    /// Ask whether the loaded photos changed.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    RefreshImages,
+    /// Post the state of the display to `--report-url`.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    ReportStatus,
+    /// Ask `--update-url` for a newer version.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    CheckUpdate,
+}
+
+/// The bounds of `--scale` and of the factor computed by `--auto-scale`.
+const MIN_SCALE: f64 = 0.5;
+const MAX_SCALE: f64 = 4.0;
+
+/// The minimal horizontal distance of a touch movement to count as swipe.
+#[cfg(feature = "gui")]
+const SWIPE_DISTANCE: f32 = 100.0;
+
+/// The time after which the touch mode returns to the cards of today.
+#[cfg(feature = "gui")]
+const TOUCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a toast is shown.
+#[cfg(feature = "gui")]
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// The size of the greeting cards in pixels.
+#[cfg(all(not(any(target_arch = "wasm32", feature = "glow")), feature = "gui"))]
+const GREETING_CARD_SIZE: (u32, u32) = (1200, 900);
+
+/// How many toasts are shown at once, older ones are dropped.
+#[cfg(feature = "gui")]
+const MAX_TOASTS: usize = 3;
+
+/// How long every photo of a person with several ones is shown.
+#[cfg(feature = "gui")]
+const SLIDE_DURATION: i64 = 5;
+
+/// The longest time the wait for the next day sleeps before it looks at the clock again,
+/// so a changed system clock or a suspended device does not delay the new day.
+#[cfg(feature = "gui")]
+const DAY_CHANGE_CHECK: Duration = Duration::from_secs(15 * 60);
+
+/// How long the window waits before it tries again to load the persons the first time.
+#[cfg(feature = "gui")]
+const LOAD_RETRY: Duration = Duration::from_secs(30);
+
+/// How often the number of read records is updated while the persons are loaded.
+#[cfg(feature = "gui")]
+const LOADING_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The photos and qr codes of persons celebrating within this many days are prepared.
+#[cfg(feature = "gui")]
+const DUE_SOON: u64 = 7;
+
+/// How often the config file is checked for changes.
+#[cfg(all(not(target_arch = "wasm32"), feature = "gui"))]
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The screens the BirthdayDisplay application can show.
+#[cfg(feature = "gui")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Screen {
+    #[default]
+    Birthdays,
+    /// The birthdays of this many days starting today.
+    Upcoming(u64),
+    Statistics,
+    Search,
+    Editor,
 }
 
+/// Loads the image at `orig_url` with `request` unless it is embedded or a local file.
+#[cfg(all(feature = "network", feature = "gui"))]
 async fn request_birthday_image(
     request: RequestBuilder,
     orig_url: String,
     verbosity: u8,
 ) -> (Result<Handle, String>, String) {
-    async fn try_get_data(request: RequestBuilder) -> Result<Bytes, reqwest::Error> {
-        request.send().await?.error_for_status()?.bytes().await
+    // images embedded in the file or on the disk are decoded without a request
+    if let Some(image_data) = decode_local_image(&orig_url, verbosity).await {
+        return (image_data, orig_url);
     }
 
-    let image_data = match try_get_data(request).await {
-        Ok(bytes) => {
-            let cow: Cow<'_, [u8]> = Cow::from(bytes.to_vec());
-            Ok(Handle::from_memory(cow))
-        }
-        Err(error) => {
+    let image_data = match download_image(request, &orig_url, verbosity).await {
+        Ok(bytes) => decode_image(bytes, &orig_url, verbosity).await,
+        Err(error) => Err(error),
+    };
+    (image_data, orig_url)
+}
+
+/// Loads the image at `orig_url` if it is embedded or a local file, the only ones that
+/// can be loaded without the network feature.
+#[cfg(all(not(feature = "network"), feature = "gui"))]
+async fn load_local_image(orig_url: String, verbosity: u8) -> (Result<Handle, String>, String) {
+    let image_data = match decode_local_image(&orig_url, verbosity).await {
+        Some(image_data) => image_data,
+        None => {
             if verbosity > 0 {
-                println!("error loading image: {error}");
+                println!(
+                    "error loading image {}: only files can be loaded without the network feature",
+                    redact::RedactedUrl(&orig_url)
+                );
             }
             Err(String::from("[failed to load image]"))
         }
     };
-
     (image_data, orig_url)
 }
 
+/// Decodes the image of a data url or a `file://` url or returns `None` if `url` is
+/// neither.
+#[cfg(feature = "gui")]
+async fn decode_local_image(url: &str, verbosity: u8) -> Option<Result<Handle, String>> {
+    let bytes = photo::decode_data_url(url);
+    #[cfg(not(target_arch = "wasm32"))]
+    let bytes = bytes.or_else(|| photo::read_file_url(url));
+    let image_data = match bytes? {
+        Ok(bytes) => photo::decode_in_background(bytes).await,
+        Err(error) => Err(error),
+    };
+    let image_data = image_data.map_err(|error| {
+        if verbosity > 0 {
+            println!("error decoding image {}: {error}", redact::RedactedUrl(url));
+        }
+        String::from("[failed to load image]")
+    });
+    Some(image_data)
+}
+
+/// Downloads the image at `url` with `request`.
+#[cfg(all(feature = "network", feature = "gui"))]
+async fn download_image(
+    request: RequestBuilder,
+    url: &str,
+    verbosity: u8,
+) -> Result<Bytes, String> {
+    async fn try_get_data(
+        request: RequestBuilder,
+    ) -> Result<(Option<String>, Bytes), photo::BodyError> {
+        let response = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(photo::BodyError::Request)?;
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        Ok((content_type, photo::read_body(response).await?))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    politeness::wait(url).await;
+    let (content_type, bytes) = try_get_data(request).await.map_err(|error| match error {
+        photo::BodyError::Request(error) => {
+            if verbosity > 0 {
+                let error = if redact::log_pii() {
+                    error
+                } else {
+                    error.without_url()
+                };
+                println!("error loading image {}: {error}", redact::RedactedUrl(url));
+            }
+            String::from("[failed to load image]")
+        }
+        photo::BodyError::TooLarge(megabytes) => {
+            let error = format!("the photo is larger than {megabytes} MB");
+            if verbosity > 0 {
+                println!("error loading image {}: {error}", redact::RedactedUrl(url));
+            }
+            format!("[{error}]")
+        }
+    })?;
+    // a login page would only fail to decode, and be cached, with a vague error
+    photo::sniff(&bytes, content_type.as_deref()).map_err(|error| {
+        if verbosity > 0 {
+            println!("error loading image {}: {error}", redact::RedactedUrl(url));
+        }
+        format!("[{error}]")
+    })?;
+    Ok(bytes)
+}
+
+/// Decodes the downloaded `bytes` of the image at `url`.
+#[cfg(all(feature = "network", feature = "gui"))]
+async fn decode_image(
+    bytes: impl AsRef<[u8]> + Send + 'static,
+    url: &str,
+    verbosity: u8,
+) -> Result<Handle, String> {
+    photo::decode_in_background(bytes).await.map_err(|error| {
+        if verbosity > 0 {
+            println!("error decoding image {}: {error}", redact::RedactedUrl(url));
+        }
+        String::from("[failed to load image]")
+    })
+}
+
+/// Produces a message when the next day starts.
+///
+/// The clock is looked at again after every sleep and only a changed date produces the
+/// message, so waking up too early does not redraw the screen.
+#[cfg(feature = "gui")]
+fn day_change(clock: Arc<dyn Clock>) -> Subscription<Message> {
+    struct DayChange;
+
+    subscription::unfold(std::any::TypeId::of::<DayChange>(), clock, |clock| async {
+        let dates = || (clock.today(), clock.local().date_naive());
+        let start = dates();
+        while dates() == start {
+            tokio::time::sleep(clock::until_midnight(clock.as_ref()).min(DAY_CHANGE_CHECK)).await;
+        }
+        (
+            Some(Message::Timer(TimerMessage::UpdateDay(Instant::now()))),
+            clock,
+        )
+    })
+}
+
+/// Generates the qr code with the greeting link of `person` if requested.
+#[cfg(feature = "gui")]
+fn generate_qr_code(person: &mut Person, template: Option<&str>, verbosity: u8) {
+    person.qr_code = template.map(|template| qr::generate(&qr::greeting_link(template, person)));
+    if let Some(Err(error)) = &person.qr_code {
+        if verbosity > 0 {
+            println!("error generating qr code: {error}");
+        }
+    }
+}
+
+/// A short message shown at the bottom of the window for [`TOAST_DURATION`].
+#[cfg(feature = "gui")]
+struct Toast {
+    text: String,
+    error: bool,
+    shown: Instant,
+}
+
+/// A source that is also read on a blocking thread while the window stays responsive.
+#[cfg(feature = "gui")]
+type SharedSource = Arc<Mutex<Box<dyn PersonSource>>>;
+
+/// The banners of a card with their language from top to bottom.
+#[cfg(feature = "gui")]
+type Banners = Vec<(Locale, String)>;
+
+/// The cards of a date with their banners, prepared once instead of for every frame.
+#[cfg(feature = "gui")]
+struct DayCards {
+    date: NaiveDate,
+    /// The day the banners were written on, they announce the birthdays of it as today.
+    written: NaiveDate,
+    /// The persons in the order of the cards with what they celebrate and the banners in
+    /// all languages.
+    cards: Vec<(usize, Occasion, Banners)>,
+}
+
+/// Why the persons could not be loaded the first time.
+#[cfg(feature = "gui")]
+struct LoadError {
+    reason: String,
+    /// When loading is tried again.
+    retry_at: Instant,
+}
+
+/// The settings of the gui that can be changed in the config file while it runs.
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, PartialEq)]
+struct Display {
+    dark: bool,
+    eink: bool,
+    high_contrast: bool,
+    /// The factor text and cards are enlarged by.
+    scale: f64,
+    /// The smallest and largest factor if it is computed from the size of the window.
+    auto_scale: Option<(f64, f64)>,
+    greeting_url: Option<String>,
+    look_ahead: u64,
+    hero: bool,
+    layout: person::Layout,
+    photo_fit: person::PhotoFit,
+    photo_height: Option<f32>,
+}
+
+#[cfg(feature = "gui")]
+impl Display {
+    fn new(cli: &Cli) -> Self {
+        Self {
+            dark: false,
+            eink: cli.eink,
+            high_contrast: cli.high_contrast,
+            scale: cli.scale,
+            auto_scale: cli.auto_scale.then_some((MIN_SCALE, MAX_SCALE)),
+            greeting_url: cli.greeting_url.clone(),
+            look_ahead: cli.look_ahead,
+            hero: cli.hero,
+            layout: cli.layout,
+            photo_fit: cli.photo_fit,
+            photo_height: cli.photo_height,
+        }
+    }
+
+    /// Returns the settings of `cli` with the ones of the config file replacing them.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn with_config(cli: &Cli, config: &config::DisplayConfig) -> Self {
+        let mut display = Self::new(cli);
+        if let Some(theme) = config.theme {
+            display.dark = theme == config::ThemeName::Dark;
+            display.eink = theme == config::ThemeName::Eink;
+            display.high_contrast = theme == config::ThemeName::HighContrast;
+        }
+        if let Some(scale) = config.scale {
+            display.scale = scale;
+        }
+        if config.auto_scale.unwrap_or(display.auto_scale.is_some()) {
+            display.auto_scale = Some((
+                config.min_scale.unwrap_or(MIN_SCALE),
+                config.max_scale.unwrap_or(MAX_SCALE),
+            ));
+        } else {
+            display.auto_scale = None;
+        }
+        if let Some(template) = &config.greeting_url {
+            display.greeting_url = Some(template.clone());
+        }
+        if let Some(days) = config.look_ahead {
+            display.look_ahead = days;
+        }
+        if let Some(hero) = config.hero {
+            display.hero = hero;
+        }
+        if let Some(layout) = config.layout {
+            display.layout = layout;
+        }
+        if let Some(fit) = config.photo_fit {
+            display.photo_fit = fit;
+        }
+        if let Some(fraction) = config.photo_height {
+            display.photo_height = Some(fraction);
+        }
+        display
+    }
+}
+
+#[cfg(feature = "gui")]
 struct BirthdayDisplay {
-    persons_by_birthday: HashMap<(u32, u32), Vec<Person>>,
+    /// All persons in the order of the csv file.
+    persons: Vec<Person>,
+    /// Indices into `persons` by day and month of the birthday.
+    persons_by_birthday: BirthdayIndex,
+    /// Indices into `persons` by day and month of the half birthday if they are celebrated.
+    half_birthdays: BirthdayIndex,
+    /// Indices into `persons` by the dates of their milestones if they are celebrated.
+    milestones_by_date: MilestoneIndex,
+    /// The cards of the shown date, prepared again when the date or the persons change.
+    cards: Option<DayCards>,
+    /// Where changes of the admin mode are saved, the page can not save in a browser.
+    source: Option<SharedSource>,
+    /// Whether the window waits for the persons to be loaded the first time.
+    loading: bool,
+    /// Shown instead of the persons until loading them succeeds.
+    load_error: Option<LoadError>,
     cli: Cli,
+    display: Display,
+    /// The config file that is applied again when it changes.
+    #[cfg(not(target_arch = "wasm32"))]
+    config: Option<config::Watch>,
+    /// The newest toast comes last.
+    toasts: Vec<Toast>,
+    keymap: Keymap,
+    #[cfg(not(target_arch = "wasm32"))]
+    fullscreen: bool,
+    /// The size of the window at a scale factor of 1 once it is known.
+    window_size: Option<Size>,
+    /// When the persons were loaded last.
+    loaded_at: DateTime<Local>,
+    /// The number of records of the source that could not be read.
+    skipped: usize,
+    #[cfg(feature = "network")]
+    client: Option<Client>,
+    /// The pending image downloads by their url.
+    downloads: HashMap<String, AbortHandle>,
+    /// The versions of the loaded photos by their url for `--image-refresh`.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    validators: HashMap<String, image_refresh::Validator>,
+    /// When the window was opened, for the uptime of `--report-url`.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    started: Instant,
+    /// The number of errors since the start, including the ones hidden by `--silent`.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    errors: usize,
+    /// A newer version found at `--update-url`.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    update: Option<String>,
+    /// Limits the memory of the decoded photos if requested.
+    #[cfg(not(target_arch = "wasm32"))]
+    images: Option<image_budget::ImageBudget>,
+    /// Records the shown cards for `--audit-log`.
+    #[cfg(not(target_arch = "wasm32"))]
+    audit: Option<audit::DisplayAudit>,
+    /// Shows photos on days without birthdays if requested.
+    screensaver: Option<Screensaver>,
+    screen: Screen,
+    search_query: String,
+    editor: Editor,
+    /// Receives commands from other instances if this is the only one running.
+    instance: Option<Listener>,
+    /// Tells the time, on another day with `--date`.
+    clock: Arc<dyn Clock>,
+    /// The day the cards, ages and photos were prepared for, see [`Self::start_day`].
+    day: NaiveDate,
+    /// The date whose birthdays are shown if it is not today.
+    shown_date: Option<NaiveDate>,
+    /// Whether `shown_date` was set remotely and is kept after the touch timeout.
+    date_pinned: bool,
+    /// Whether the screen was blanked remotely.
+    blanked: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    control: Option<ControlPort>,
+    #[cfg(not(target_arch = "wasm32"))]
+    watchdog: Option<Watchdog>,
+    #[cfg(feature = "scripting")]
+    script: Option<Arc<script::Script>>,
+    /// The day the script was last told about.
+    #[cfg(feature = "scripting")]
+    script_day: NaiveDate,
+    /// Indices of the persons whose details are shown instead of their card.
+    expanded: HashSet<usize>,
+    swipe_start: Option<Point>,
+    last_interaction: Instant,
+    #[cfg(feature = "gpio")]
+    flag: Option<gpio::Flag>,
+}
+
+/// Indexes `persons` by day and month of their half birthday if `enabled`.
+#[cfg(feature = "gui")]
+fn index_half_birthdays(persons: &[Person], enabled: bool) -> BirthdayIndex {
+    if enabled {
+        BirthdayIndex::half_birthdays(persons)
+    } else {
+        BirthdayIndex::default()
+    }
+}
+
+/// Reads the persons of `source` on a blocking thread so the window stays responsive.
+#[cfg(all(not(target_arch = "wasm32"), feature = "gui"))]
+fn load_persons(source: SharedSource, quiet: bool) -> Command<Message> {
+    Command::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let mut source = source
+                    .lock()
+                    .map_err(|_| String::from("the source is locked after an error"))?;
+                source.load(quiet).map_err(|error| error.to_string())
+            })
+            .await
+            .unwrap_or_else(|error| Err(error.to_string()))
+        },
+        |result| Message::Data(DataMessage::PersonsLoaded(result)),
+    )
+}
+
+/// Returns the screensaver of `--screensaver`, which shows the photos of the persons if
+/// the ones of `--screensaver-dir` can not be read.
+#[cfg(feature = "gui")]
+fn new_screensaver(cli: &Cli) -> Option<Screensaver> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(dir) = &cli.screensaver_dir {
+        return Some(Screensaver::with_folder(dir).unwrap_or_else(|error| {
+            println!("{error}, showing the photos of the persons instead");
+            Screensaver::default()
+        }));
+    }
+    cli.screensaver.then(Screensaver::default)
+}
+
+/// Returns the urls of the photos of the persons at `ranked` that are not loaded yet.
+#[cfg(feature = "gui")]
+fn missing_images(persons: &[Person], ranked: &[usize]) -> Vec<String> {
+    ranked
+        .iter()
+        .flat_map(|&index| {
+            let person = &persons[index];
+            person
+                .image_urls()
+                .into_iter()
+                .filter(|url| !person.image_data.contains_key(*url))
+        })
+        .map(str::to_string)
+        .collect()
 }
 
+/// The channels to other programs that are opened before the window, so the start fails
+/// if one of them can not be opened.
+#[cfg(feature = "gui")]
+#[derive(Default)]
+pub struct Services {
+    /// Receives commands from other instances if this is the only one running.
+    pub instance: Option<Listener>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub control: Option<ControlPort>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub watchdog: Option<Watchdog>,
+    #[cfg(feature = "gpio")]
+    pub flag: Option<gpio::Flag>,
+    /// The script that already transforms the persons of the source.
+    #[cfg(feature = "scripting")]
+    pub script: Option<Arc<script::Script>>,
+}
+
+#[cfg(feature = "gui")]
 impl Application for BirthdayDisplay {
+    #[cfg(not(target_arch = "wasm32"))]
+    type Executor = runtime::Executor;
+    #[cfg(target_arch = "wasm32")]
     type Executor = iced::executor::Default;
     type Message = Message;
     type Theme = iced::theme::Theme;
-    type Flags = (Cli, Vec<Person>);
-
-    fn new(flags: (Cli, Vec<Person>)) -> (Self, Command<Message>) {
-        let (cli, persons) = flags;
-
-        // prepare loading of images
-        let loadable_persons: Vec<&Person> = persons
-            .iter()
-            .filter(|person| person.image_url.is_some())
-            .collect();
+    type Flags = (Cli, Option<Box<dyn PersonSource>>, Services);
 
-        // try to generate reqwest client if needed
-        let reqwest_client = match loadable_persons.len() {
-            0 => None,
-            _ => match Client::builder().build() {
-                Ok(client) => Some(client),
-                Err(error) => {
-                    if cli.verbose > 0 {
-                        println!("error while initializing web client: {error}");
-                    }
-                    None
-                }
-            },
-        };
+    fn new(flags: Self::Flags) -> (Self, Command<Message>) {
+        let (cli, source, services) = flags;
+        let clock = clock::for_date(cli.date);
+        let day = clock.today();
+        #[cfg(feature = "scripting")]
+        let script_day = day;
+        #[cfg(not(target_arch = "wasm32"))]
+        let images = cli.image_memory.map(|megabytes| {
+            let images = image_budget::ImageBudget::new(megabytes);
+            #[cfg(feature = "network")]
+            let images = match cli.image_cache.clone().or_else(image_budget::default_dir) {
+                Some(dir) => images.cache_in(dir, cli.verbose),
+                None => images,
+            };
+            images
+        });
 
-        // generate Command to load images async
-        let command = if let Some(client) = reqwest_client {
-            Command::batch(
-                loadable_persons
-                    .iter()
-                    .map(|person| {
-                        Command::perform(
-                            request_birthday_image(
-                                client.get(person.image_url.as_ref().unwrap()),
-                                person.image_url.as_ref().unwrap().clone(),
-                                cli.verbose,
-                            ),
-                            |(data, url)| Message::DataReceived(data, url),
-                        )
-                    })
-                    .collect::<Vec<Command<Message>>>(),
-            )
-        } else {
-            Command::none()
+        // the browser sets nothing up that needs a mutable application
+        #[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
+        let mut application = Self {
+            persons: Vec::new(),
+            persons_by_birthday: BirthdayIndex::default(),
+            half_birthdays: BirthdayIndex::default(),
+            milestones_by_date: MilestoneIndex::default(),
+            cards: None,
+            source: source.map(|source| Arc::new(Mutex::new(source))),
+            loading: true,
+            load_error: None,
+            editor: Editor::new(cli.admin_pin.as_deref()),
+            display: Display::new(&cli),
+            #[cfg(not(target_arch = "wasm32"))]
+            audit: cli.audit_log.clone().map(audit::DisplayAudit::new),
+            screensaver: new_screensaver(&cli),
+            cli,
+            #[cfg(not(target_arch = "wasm32"))]
+            config: None,
+            toasts: Vec::new(),
+            keymap: Keymap::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            fullscreen: false,
+            window_size: None,
+            loaded_at: Local::now(),
+            skipped: 0,
+            #[cfg(feature = "network")]
+            client: None,
+            downloads: HashMap::new(),
+            #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+            validators: HashMap::new(),
+            #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+            started: Instant::now(),
+            #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+            errors: 0,
+            #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+            update: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            images,
+            screen: Screen::default(),
+            search_query: String::new(),
+            instance: services.instance,
+            clock,
+            day,
+            shown_date: None,
+            date_pinned: false,
+            blanked: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            control: services.control,
+            #[cfg(not(target_arch = "wasm32"))]
+            watchdog: services.watchdog,
+            #[cfg(feature = "scripting")]
+            script: services.script,
+            #[cfg(feature = "scripting")]
+            script_day,
+            expanded: HashSet::new(),
+            swipe_start: None,
+            last_interaction: Instant::now(),
+            #[cfg(feature = "gpio")]
+            flag: services.flag,
         };
+        // the config file was already checked before the window opened
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = &application.cli.config {
+            let mut watch = config::Watch::new(path, application.cli.profile.as_deref());
+            if let Ok(config) = watch.load() {
+                application.display = Display::with_config(&application.cli, &config.display);
+                application.keymap = Keymap::with_overrides(&config.keys);
+            }
+            application.config = Some(watch);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = &application.cli.state_file {
+            let state = state::State::load(path, application.cli.quiet);
+            let today = application.clock.today();
+            application.shown_date = state.pinned_date.filter(|date| *date != today);
+            application.date_pinned = application.shown_date.is_some();
+            #[cfg(feature = "scripting")]
+            if let Some(day) = state.script_day {
+                application.script_day = day;
+            }
+        }
+        let command = application.load();
+        #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+        let command = Command::batch([command, application.check_update()]);
 
-        let mut persons_by_birthday: HashMap<(u32, u32), Vec<Person>> = HashMap::new();
-        for person in persons {
-            let key = (person.birthday.day(), person.birthday.month());
+        (application, command)
+    }
 
-            persons_by_birthday.entry(key).or_default();
+    fn title(&self) -> String {
+        self.cli.title.clone()
+    }
 
-            persons_by_birthday.get_mut(&key).unwrap().push(person);
+    fn theme(&self) -> Self::Theme {
+        if self.display.high_contrast {
+            style::high_contrast()
+        } else if self.display.eink {
+            style::eink()
+        } else if self.display.dark {
+            Self::Theme::Dark
+        } else {
+            Self::Theme::default()
         }
-
-        (
-            Self {
-                persons_by_birthday,
-                cli,
-            },
-            command,
-        )
     }
 
-    fn title(&self) -> String {
-        String::from("Birthday Display")
+    fn scale_factor(&self) -> f64 {
+        match (self.display.auto_scale, self.window_size) {
+            (Some((min, max)), Some(window)) if self.screen == Screen::Birthdays => {
+                let count = self.cards.as_ref().map_or(0, |day| day.cards.len());
+                let cards = self.display.layout.size(count);
+                // a margin is left for the heading of other days and the toasts
+                let fit =
+                    0.9 * f64::from((window.width / cards.width).min(window.height / cards.height));
+                fit.min(max).max(min)
+            }
+            _ => self.display.scale,
+        }
     }
 
     fn update(&mut self, message: Self::Message) -> Command<Message> {
-        if let Message::DataReceived(image_data, orig_url) = message {
-            let url = Some(orig_url);
-            self.persons_by_birthday
-                .iter_mut()
-                .flat_map(|(_, persons)| persons.iter_mut())
-                .filter(|person| person.image_url == url)
-                .for_each(|person| {
-                    person.image_data.replace(image_data.clone());
-                });
+        let today = self.clock.today();
+        if today != self.day {
+            self.start_day(today);
         }
-        iced::window::maximize(true)
+        let command = match message {
+            Message::Data(message) => self.update_data(message),
+            Message::Ui(message) => self.update_ui(message),
+            Message::Timer(message) => self.update_timer(message),
+            Message::Editor(message) => self.update_editor(message),
+        };
+        // the cards are only prepared again if the message did not answer with a command
+        if let Some(command) = command {
+            return command;
+        }
+        self.prepare_cards();
+        Command::batch([self.maximize(), self.manage_images()])
     }
 
-    fn view(&self) -> Element<Self::Message> {
-        let today = Utc::now().date_naive();
-        let key = (today.day(), today.month());
+    fn view(&self) -> Element<'_, Self::Message> {
+        let now = self.clock.local().naive_local();
+        if self.blanked
+            || self
+                .cli
+                .blank_between
+                .is_some_and(|quiet_hours| quiet_hours.contains(now.time()))
+        {
+            return self.view_blank(now.time());
+        }
 
-        let maybe_persons_today = self.persons_by_birthday.get(&key);
+        let today = self.clock.today();
 
-        let elements: Vec<Element<Message>> = match maybe_persons_today {
-            Some(persons_today) => persons_today
-                .iter()
-                .map(|person| person.view(self.cli.silent))
-                .collect(),
-            None => Vec::new(),
+        let content: Element<Message> = match self.screen {
+            _ if self.load_error.is_some() && !self.cli.silent => self.view_load_error(),
+            _ if self.loading => self.view_loading(),
+            Screen::Birthdays => self.view_birthdays(),
+            Screen::Upcoming(days) => {
+                upcoming::view(&self.persons, &self.persons_by_birthday, today, days)
+            }
+            Screen::Statistics => Statistics::new(&self.persons, today).view(),
+            Screen::Search => search::view(&self.search_query, &self.persons, today),
+            Screen::Editor => self.editor.view(&self.persons, self.error_color()),
         };
 
-        container(row(elements).spacing(15))
+        let content = container(content)
             .padding(20)
             .width(Length::Fill)
             .height(Length::Fill)
             .center_x()
-            .center_y()
+            .center_y();
+
+        let mut footer: Vec<Element<Message>> = Vec::new();
+        for toast in &self.toasts {
+            let color = if toast.error {
+                self.error_color()
+            } else {
+                self.theme().palette().text
+            };
+            footer.push(text(&toast.text).size(20).style(color).into());
+        }
+        if self.cli.ticker && self.screen == Screen::Birthdays {
+            let line = ticker::line(&self.persons, &self.persons_by_birthday, today);
+            if !line.is_empty() {
+                let step = Local::now().timestamp_millis() / ticker::STEP.as_millis() as i64;
+                // the window is assumed to be as wide as a full hd screen until it is known
+                let width = self.window_size.map_or(1920.0, |size| size.width);
+                let width = width / self.scale_factor() as f32;
+                footer.push(ticker::view(&line, step as usize, width));
+            }
+        }
+        if self.cli.status_bar {
+            footer.push(self.view_status_bar());
+        }
+        #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+        if let Some(version) = &self.update {
+            footer.push(text(format!("Update verfügbar: {version}")).size(14).into());
+        }
+        let content: Element<Message> = if footer.is_empty() {
+            content.into()
+        } else {
+            column![
+                content,
+                column(footer).spacing(5).align_items(Alignment::Center)
+            ]
+            .align_items(Alignment::Center)
+            .padding(10)
             .into()
+        };
+
+        match schedule::brightness(&self.cli.dim, now) {
+            brightness if brightness < 1.0 => Dimmed::new(content, 1.0 - brightness).into(),
+            _ => content,
+        }
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        every(Duration::from_secs(5)).map(Message::UpdateDay)
-    }
-}
+        let instance_commands = match &self.instance {
+            Some(listener) => instance::commands(listener),
+            None => Subscription::none(),
+        };
 
-fn main() -> Result<(), ErrorDisplayWrapper> {
-    let cli: Cli = Cli::parse();
+        // e-paper displays and screens without anything changing by itself are only
+        // refreshed when the day changes or, if the screen is dimmed by time, once a minute
+        let tick = if !self.display.eink && self.animates() {
+            every(Duration::from_secs(SLIDE_DURATION as u64))
+                .map(|_| Message::Timer(TimerMessage::UpdateDay(Instant::now())))
+        } else if self.cli.blank_between.is_some() || !self.cli.dim.is_empty() {
+            every(Duration::from_secs(60))
+                .map(|_| Message::Timer(TimerMessage::UpdateDay(Instant::now())))
+        } else {
+            day_change(self.clock.clone())
+        };
+
+        let touch_events = if self.cli.touch {
+            subscription::events_with(|event, _| match event {
+                Event::Touch(touch::Event::FingerPressed { position, .. }) => {
+                    Some(Message::Ui(UiMessage::SwipeStarted(position)))
+                }
+                Event::Touch(touch::Event::FingerLifted { position, .. }) => {
+                    Some(Message::Ui(UiMessage::SwipeEnded(position)))
+                }
+                _ => None,
+            })
+        } else {
+            Subscription::none()
+        };
+
+        let resizes = if self.display.auto_scale.is_some() || self.cli.ticker {
+            subscription::events_with(|event, _| match event {
+                Event::Window(iced_native::window::Event::Resized { width, height }) => {
+                    Some(Message::Ui(UiMessage::Resized(width, height)))
+                }
+                _ => None,
+            })
+        } else {
+            Subscription::none()
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        let remote_commands = web::server_events();
+        #[cfg(not(target_arch = "wasm32"))]
+        let remote_commands = match &self.control {
+            Some(control) => control.commands(),
+            None => Subscription::none(),
+        };
 
-    let persons = get_persons(&cli.file, cli.quiet)?;
+        // the heartbeats are handled by the gui so a hang stops them
+        #[cfg(not(target_arch = "wasm32"))]
+        let heartbeat = match &self.watchdog {
+            Some(watchdog) => {
+                every(watchdog.interval()).map(|_| Message::Timer(TimerMessage::Heartbeat))
+            }
+            None => Subscription::none(),
+        };
+        #[cfg(target_arch = "wasm32")]
+        let heartbeat = Subscription::none();
 
-    BirthdayDisplay::run(Settings::with_flags((cli, persons)))
-        .map_err(|error| ErrorDisplayWrapper::from(Box::new(error) as Box<dyn Error>))?;
-    Ok(())
+        #[cfg(not(target_arch = "wasm32"))]
+        let config_changes = match &self.config {
+            Some(_) => {
+                every(CONFIG_POLL_INTERVAL).map(|_| Message::Timer(TimerMessage::CheckConfig))
+            }
+            None => Subscription::none(),
+        };
+        #[cfg(target_arch = "wasm32")]
+        let config_changes = Subscription::none();
+
+        #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+        let image_refresh = match self.cli.image_refresh {
+            Some(minutes) => every(Duration::from_secs(minutes * 60))
+                .map(|_| Message::Timer(TimerMessage::RefreshImages)),
+            None => Subscription::none(),
+        };
+        #[cfg(not(all(not(target_arch = "wasm32"), feature = "network")))]
+        let image_refresh = Subscription::none();
+
+        #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+        let status_reports = match self.cli.report_url {
+            Some(_) => {
+                let interval = self
+                    .cli
+                    .report_every
+                    .as_ref()
+                    .map_or(fleet::DEFAULT_INTERVAL, |interval| interval.0);
+                every(interval).map(|_| Message::Timer(TimerMessage::ReportStatus))
+            }
+            None => Subscription::none(),
+        };
+        #[cfg(not(all(not(target_arch = "wasm32"), feature = "network")))]
+        let status_reports = Subscription::none();
+
+        #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+        let update_checks = match self.cli.update_url {
+            Some(_) => every(update::INTERVAL).map(|_| Message::Timer(TimerMessage::CheckUpdate)),
+            None => Subscription::none(),
+        };
+        #[cfg(not(all(not(target_arch = "wasm32"), feature = "network")))]
+        let update_checks = Subscription::none();
+
+        let refresh = match self.cli.refresh_interval {
+            Some(seconds) => every(Duration::from_secs(seconds))
+                .map(|_| Message::Timer(TimerMessage::UpdateDay(Instant::now()))),
+            None => Subscription::none(),
+        };
+
+        let reload = match self.cli.reload_every {
+            Some(Interval(interval)) => every(interval).map(|_| Message::Data(DataMessage::Reload)),
+            None => Subscription::none(),
+        };
+
+        // the progress of loading a large file is shown while it is read and the
+        // countdown until the next attempt after it failed
+        let loading = match (self.loading, &self.load_error) {
+            (_, Some(_)) => every(Duration::from_secs(1))
+                .map(|_| Message::Timer(TimerMessage::UpdateDay(Instant::now()))),
+            (true, None) => every(LOADING_PROGRESS_INTERVAL)
+                .map(|_| Message::Timer(TimerMessage::UpdateDay(Instant::now()))),
+            (false, None) => Subscription::none(),
+        };
+
+        let ticker = if self.cli.ticker && !self.display.eink && self.screen == Screen::Birthdays {
+            every(ticker::STEP).map(|_| Message::Timer(TimerMessage::UpdateDay(Instant::now())))
+        } else {
+            Subscription::none()
+        };
+
+        // a toast has to disappear again even on e-paper displays
+        let toast_timeout = if self.toasts.is_empty() {
+            Subscription::none()
+        } else {
+            every(Duration::from_secs(1))
+                .map(|_| Message::Timer(TimerMessage::UpdateDay(Instant::now())))
+        };
+
+        Subscription::batch([
+            instance_commands,
+            remote_commands,
+            heartbeat,
+            config_changes,
+            image_refresh,
+            status_reports,
+            update_checks,
+            toast_timeout,
+            touch_events,
+            resizes,
+            ticker,
+            tick,
+            refresh,
+            reload,
+            loading,
+            subscription::events_with(|event, status| match (event, status) {
+                (
+                    Event::Keyboard(keyboard::Event::KeyPressed {
+                        key_code: KeyCode::Escape,
+                        ..
+                    }),
+                    _,
+                ) => Some(Message::Ui(UiMessage::CloseOverlay)),
+                (
+                    Event::Keyboard(keyboard::Event::KeyPressed {
+                        key_code,
+                        modifiers,
+                    }),
+                    event::Status::Ignored,
+                ) => Some(Message::Ui(UiMessage::KeyPressed(key_code, modifiers))),
+                _ => None,
+            }),
+        ])
+    }
+}
+
+#[cfg(feature = "gui")]
+impl BirthdayDisplay {
+    /// Handles arrived persons, photos and answers of servers, `None` if the cards have
+    /// to be prepared again.
+    fn update_data(&mut self, message: DataMessage) -> Option<Command<Message>> {
+        match message {
+            DataMessage::ImageReceived(image_data, orig_url) => {
+                // the download was already replaced by one for the reloaded persons
+                if self.downloads.remove(&orig_url).is_none() {
+                    return Some(Command::none());
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(images) = &mut self.images {
+                    images.received(&orig_url, &image_data);
+                }
+                if let Some(screensaver) = &mut self.screensaver {
+                    screensaver.received(&orig_url, &image_data);
+                }
+                let shows = |person: &Person| person.image_urls().contains(&orig_url.as_str());
+                if image_data.is_err() {
+                    if let Some(person) = self.persons.iter().find(|person| shows(person)) {
+                        let text = format!("Bild von {} nicht geladen", person.name());
+                        self.show_toast(text, true);
+                    }
+                }
+                self.persons
+                    .iter_mut()
+                    .filter(|person| shows(person))
+                    .for_each(|person| {
+                        person
+                            .image_data
+                            .insert(orig_url.clone(), image_data.clone());
+                    });
+            }
+            DataMessage::DownloadCancelled => {}
+            DataMessage::Reload => return Some(self.load()),
+            #[cfg(not(target_arch = "wasm32"))]
+            DataMessage::PersonsLoaded(result) => match result {
+                Ok(persons) => {
+                    self.announce_changes(&persons);
+                    self.loading = false;
+                    self.load_error = None;
+                    return Some(Command::batch([self.set_persons(persons), self.maximize()]));
+                }
+                Err(error) if self.loading => self.load_failed(error),
+                Err(error) => {
+                    println!("error reloading {}: {error}", self.cli.file.display());
+                    let text = format!("Fehler beim Neuladen: {error}");
+                    self.show_toast(text, true);
+                }
+            },
+            #[cfg(target_arch = "wasm32")]
+            DataMessage::PersonsReceived(result) => {
+                match result.and_then(|content| {
+                    csv::parse_persons(&content, self.cli.quiet).map_err(|error| error.to_string())
+                }) {
+                    Ok((persons, unparsed)) => {
+                        self.announce_changes(&persons);
+                        self.loading = false;
+                        self.load_error = None;
+                        let command = self.set_persons(persons);
+                        self.skipped = unparsed.skipped();
+                        return Some(command);
+                    }
+                    Err(error) if self.loading => self.load_failed(error),
+                    Err(error) => {
+                        web_sys::console::error_1(
+                            &format!("error loading {}: {error}", self.cli.file.display()).into(),
+                        );
+                        self.show_toast(format!("Fehler beim Laden: {error}"), true);
+                    }
+                }
+            }
+            #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+            DataMessage::UpdateChecked(result) => match result {
+                Ok(Some(version)) => {
+                    if !self.cli.quiet && self.update.as_ref() != Some(&version) {
+                        println!("version {version} is available");
+                    }
+                    self.update = Some(version);
+                }
+                Ok(None) => self.update = None,
+                Err(error) => {
+                    if !self.cli.quiet {
+                        println!("{error}");
+                    }
+                }
+            },
+            #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+            DataMessage::StatusReported(result) => {
+                if let Err(error) = result {
+                    if !self.cli.quiet {
+                        println!("{error}");
+                    }
+                }
+            }
+            #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+            DataMessage::ImageRefreshed(url, refreshed) => {
+                if let Some((image_data, validator)) = refreshed {
+                    self.validators.insert(url.clone(), validator);
+                    // a photo that became unreadable does not replace the old one
+                    if image_data.is_ok() {
+                        if let Some(images) = &mut self.images {
+                            images.received(&url, &image_data);
+                        }
+                        for person in &mut self.persons {
+                            if let Some(old) = person.image_data.get_mut(&url) {
+                                *old = image_data.clone();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Handles the interactions of the user and of other programs, `None` if the cards
+    /// have to be prepared again.
+    fn update_ui(&mut self, message: UiMessage) -> Option<Command<Message>> {
+        match message {
+            UiMessage::ToggleStatistics => {
+                self.screen = match self.screen {
+                    Screen::Statistics => Screen::Birthdays,
+                    _ => Screen::Statistics,
+                };
+            }
+            UiMessage::OpenSearch => {
+                self.screen = Screen::Search;
+                return Some(Command::batch([
+                    self.maximize(),
+                    text_input::focus(search::input_id()),
+                ]));
+            }
+            UiMessage::CloseOverlay => match self.screen {
+                Screen::Search => {
+                    self.screen = Screen::Birthdays;
+                    self.search_query.clear();
+                }
+                Screen::Editor => {
+                    self.screen = Screen::Birthdays;
+                    self.editor = Editor::new(self.cli.admin_pin.as_deref());
+                }
+                Screen::Upcoming(_) => self.screen = Screen::Birthdays,
+                _ => {}
+            },
+            UiMessage::SearchChanged(query) => self.search_query = query,
+            UiMessage::OpenEditor => {
+                self.screen = Screen::Editor;
+                return Some(Command::batch([
+                    self.maximize(),
+                    text_input::focus(Editor::input_id()),
+                ]));
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            UiMessage::Focus => {
+                return Some(Command::batch([
+                    self.maximize(),
+                    iced::window::gain_focus(),
+                ]))
+            }
+            #[cfg(target_arch = "wasm32")]
+            UiMessage::Focus => {}
+            UiMessage::SetDate(date) => {
+                let today = self.clock.today();
+                self.shown_date = date.filter(|date| *date != today);
+                self.date_pinned = self.shown_date.is_some();
+                self.expanded.clear();
+                #[cfg(not(target_arch = "wasm32"))]
+                self.save_state();
+            }
+            UiMessage::NextPage => {
+                self.last_interaction = Instant::now();
+                self.browse(true);
+            }
+            UiMessage::Blank(blanked) => self.blanked = blanked.unwrap_or(!self.blanked),
+            #[cfg(not(any(target_arch = "wasm32", feature = "glow")))]
+            UiMessage::GreetingCards(name) => match self.write_greeting_cards(name.as_deref()) {
+                Ok(paths) => {
+                    let paths: Vec<String> = paths
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect();
+                    let text = format!("Glückwunschkarte gespeichert: {}", paths.join(", "));
+                    self.show_toast(text, false);
+                }
+                Err(error) => self.show_toast(error, true),
+            },
+            #[cfg(any(target_arch = "wasm32", feature = "glow"))]
+            UiMessage::GreetingCards(_) => {
+                let text = String::from("Glückwunschkarten gibt es hier nicht.");
+                self.show_toast(text, true);
+            }
+            UiMessage::ToggleDetails(index) => {
+                self.last_interaction = Instant::now();
+                if !self.expanded.remove(&index) {
+                    self.expanded.insert(index);
+                }
+            }
+            UiMessage::OpenLink(url) => {
+                if let Err(error) = browser::open(&url) {
+                    if self.cli.verbose > 0 {
+                        println!("error opening link: {error}");
+                    }
+                }
+            }
+            UiMessage::Resized(width, height) => {
+                // the size is given in the scale factor it was measured with
+                let scale = self.scale_factor() as f32;
+                self.window_size = Some(Size::new(width as f32 * scale, height as f32 * scale));
+            }
+            UiMessage::SwipeStarted(position) => self.swipe_start = Some(position),
+            UiMessage::SwipeEnded(position) => {
+                self.last_interaction = Instant::now();
+                if let Some(start) = self.swipe_start.take() {
+                    let distance = position.x - start.x;
+                    if distance.abs() >= SWIPE_DISTANCE {
+                        // swiping to the left shows the following birthdays
+                        self.browse(distance < 0.0);
+                    }
+                }
+            }
+            UiMessage::KeyPressed(key_code, modifiers) => {
+                if let Some(action) = self.keymap.action(key_code, modifiers) {
+                    return Some(self.update(action.message()));
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            UiMessage::Quit => return Some(iced::window::close()),
+            #[cfg(not(target_arch = "wasm32"))]
+            UiMessage::ToggleFullscreen => {
+                self.fullscreen = !self.fullscreen;
+                let mode = if self.fullscreen {
+                    iced::window::Mode::Fullscreen
+                } else {
+                    iced::window::Mode::Windowed
+                };
+                return Some(Command::batch([
+                    iced::window::change_mode(mode),
+                    self.maximize(),
+                ]));
+            }
+            // the browser closes the tab and switches to fullscreen itself
+            #[cfg(target_arch = "wasm32")]
+            UiMessage::Quit | UiMessage::ToggleFullscreen => {}
+            UiMessage::ShowToday => {
+                self.screen = Screen::Birthdays;
+                self.shown_date = None;
+                self.date_pinned = false;
+                self.expanded.clear();
+                #[cfg(not(target_arch = "wasm32"))]
+                self.save_state();
+            }
+            UiMessage::ShowUpcoming(days) => self.screen = Screen::Upcoming(days),
+            UiMessage::PreviousPage => {
+                self.last_interaction = Instant::now();
+                self.browse(false);
+            }
+        }
+        None
+    }
+
+    /// Handles the periodic subscriptions, `None` if the cards have to be prepared again.
+    fn update_timer(&mut self, message: TimerMessage) -> Option<Command<Message>> {
+        match message {
+            #[cfg(not(target_arch = "wasm32"))]
+            TimerMessage::Heartbeat => {
+                if let Some(watchdog) = &mut self.watchdog {
+                    if let Err(error) = watchdog.heartbeat() {
+                        if self.cli.verbose > 0 {
+                            println!("error notifying watchdog: {error}");
+                        }
+                    }
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            TimerMessage::CheckConfig => self.reload_config(),
+            #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+            TimerMessage::RefreshImages => return Some(self.refresh_images()),
+            #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+            TimerMessage::ReportStatus => return Some(self.report_status()),
+            #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+            TimerMessage::CheckUpdate => return Some(self.check_update()),
+            TimerMessage::UpdateDay(now) => {
+                if self
+                    .load_error
+                    .as_ref()
+                    .is_some_and(|error| now >= error.retry_at)
+                {
+                    self.load_error = None;
+                    return Some(self.load());
+                }
+                self.toasts
+                    .retain(|toast| now.duration_since(toast.shown) <= TOAST_DURATION);
+                if !self.date_pinned && now.duration_since(self.last_interaction) > TOUCH_TIMEOUT {
+                    self.shown_date = None;
+                    self.expanded.clear();
+                }
+                #[cfg(feature = "gpio")]
+                self.update_flag();
+                #[cfg(feature = "scripting")]
+                self.tell_script();
+            }
+        }
+        None
+    }
+
+    /// Handles the updates of the admin mode, `None` if the cards have to be prepared again.
+    fn update_editor(&mut self, message: EditorMessage) -> Option<Command<Message>> {
+        let focus = matches!(message, EditorMessage::Edit(_) | EditorMessage::New);
+        let changed = self
+            .editor
+            .update(message, &mut self.persons, self.cli.admin_pin.as_deref());
+
+        if let Some(index) = changed {
+            return Some(self.persons_changed(index));
+        }
+        if focus {
+            return Some(Command::batch([
+                self.maximize(),
+                text_input::focus(Editor::input_id()),
+            ]));
+        }
+        None
+    }
+
+    /// Returns the text color of errors matching the theme.
+    fn error_color(&self) -> Color {
+        if self.display.eink {
+            Color::BLACK
+        } else {
+            style::error_color(&self.theme())
+        }
+    }
+
+    /// Keeps the window maximized unless it is shown in fullscreen.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn maximize(&self) -> Command<Message> {
+        if self.fullscreen {
+            Command::none()
+        } else {
+            iced::window::maximize(true)
+        }
+    }
+
+    /// The size of the canvas is decided by the page in a browser.
+    #[cfg(target_arch = "wasm32")]
+    fn maximize(&self) -> Command<Message> {
+        Command::none()
+    }
+
+    /// Generates a Command that loads the image at `url` async unless it is already loading.
+    fn load_image(&mut self, url: &str) -> Command<Message> {
+        if self.downloads.contains_key(url) {
+            return Command::none();
+        }
+        let verbosity = self.cli.verbose;
+        #[cfg(feature = "network")]
+        let Some(client) = &self.client
+        else {
+            return Command::none();
+        };
+        #[cfg(feature = "network")]
+        let request = client.get(url);
+        #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+        let future = match &self.images {
+            Some(images) => image_budget::request_cached_image(
+                request,
+                url.to_string(),
+                verbosity,
+                images.dir(),
+            )
+            .boxed(),
+            None => request_birthday_image(request, url.to_string(), verbosity).boxed(),
+        };
+        #[cfg(target_arch = "wasm32")]
+        let future = request_birthday_image(request, url.to_string(), verbosity);
+        #[cfg(not(feature = "network"))]
+        let future = load_local_image(url.to_string(), verbosity);
+
+        let (future, handle) = abortable(future);
+        self.downloads.insert(url.to_string(), handle);
+        Command::perform(future, |result| match result {
+            Ok((data, url)) => Message::Data(DataMessage::ImageReceived(data, url)),
+            Err(Aborted) => Message::Data(DataMessage::DownloadCancelled),
+        })
+    }
+
+    /// Asks the servers of the loaded photos whether they changed.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    fn refresh_images(&self) -> Command<Message> {
+        let Some(client) = &self.client else {
+            return Command::none();
+        };
+        let mut urls: Vec<&String> = self
+            .persons
+            .iter()
+            .flat_map(|person| person.image_data.iter())
+            .filter(|(url, image_data)| {
+                image_data.is_ok()
+                    && person::ImageScheme::of(url).is_some_and(person::ImageScheme::is_remote)
+            })
+            .map(|(url, _)| url)
+            .collect();
+        urls.sort_unstable();
+        urls.dedup();
+
+        let dir = self.images.as_ref().and_then(|images| images.dir());
+        Command::batch(urls.into_iter().map(|url| {
+            Command::perform(
+                image_refresh::revalidate(
+                    client.get(url),
+                    url.clone(),
+                    self.validators.get(url).cloned(),
+                    self.cli.verbose,
+                    dir.clone(),
+                ),
+                |(url, refreshed)| Message::Data(DataMessage::ImageRefreshed(url, refreshed)),
+            )
+        }))
+    }
+
+    /// Asks `--update-url` whether there is a newer version.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    fn check_update(&mut self) -> Command<Message> {
+        let Some(url) = self.cli.update_url.clone() else {
+            return Command::none();
+        };
+        if self.client.is_none() {
+            self.client = Client::builder().build().ok();
+        }
+        match self.client.clone() {
+            Some(client) => Command::perform(update::check(client, url), |result| {
+                Message::Data(DataMessage::UpdateChecked(result))
+            }),
+            None => Command::none(),
+        }
+    }
+
+    /// Posts the state of the display to `--report-url`.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+    fn report_status(&mut self) -> Command<Message> {
+        let Some(url) = self.cli.report_url.clone() else {
+            return Command::none();
+        };
+        if self.client.is_none() {
+            self.client = Client::builder().build().ok();
+        }
+        let Some(client) = self.client.clone() else {
+            return Command::none();
+        };
+        let images = || {
+            self.persons
+                .iter()
+                .flat_map(|person| person.image_data.values())
+        };
+        let source = match self
+            .source
+            .as_ref()
+            .and_then(|source| source.try_lock().ok())
+        {
+            Some(source) => source.name(),
+            None => self.cli.file.display().to_string(),
+        };
+        let report = fleet::Report {
+            hostname: fleet::hostname(),
+            version: env!("CARGO_PKG_VERSION"),
+            uptime_seconds: self.started.elapsed().as_secs(),
+            source,
+            loaded_at: self.loaded_at,
+            load_error: self.load_error.as_ref().map(|error| error.reason.clone()),
+            persons: self.persons.len(),
+            skipped_rows: self.skipped,
+            images_loaded: images().filter(|image| image.is_ok()).count(),
+            images_failed: images().filter(|image| image.is_err()).count(),
+            errors: self.errors,
+        };
+        Command::perform(fleet::send(client, url, report), |result| {
+            Message::Data(DataMessage::StatusReported(
+                result.map_err(|error| error.to_string()),
+            ))
+        })
+    }
+
+    /// Aborts the pending image downloads except the ones of `wanted` urls.
+    fn cancel_downloads(&mut self, wanted: &[String]) {
+        self.downloads.retain(|url, handle| {
+            let keep = wanted.contains(url);
+            if !keep {
+                handle.abort();
+            }
+            keep
+        });
+    }
+
+    /// Moves the display to the new day `today` before the first message of it is handled.
+    ///
+    /// The cards are written again with the new ages, a date looked at on the touch
+    /// screen is left and the photos of the persons that are not shown soon anymore are
+    /// dropped, including failed ones so they are tried again next year. The photos of
+    /// the new birthdays are requested once the cards are prepared after the message.
+    fn start_day(&mut self, today: NaiveDate) {
+        self.day = today;
+        self.cards = None;
+        self.expanded.clear();
+        if !self.date_pinned || self.shown_date == Some(today) {
+            self.shown_date = None;
+            self.date_pinned = false;
+        }
+
+        let (ranked, _) = self.ranked_persons();
+        let kept: HashSet<usize> = ranked.into_iter().collect();
+        for (index, person) in self.persons.iter_mut().enumerate() {
+            if !kept.contains(&index) {
+                person.image_data.clear();
+            }
+        }
+        #[cfg(feature = "gpio")]
+        self.update_flag();
+    }
+
+    /// Returns the persons whose photos and qr codes are needed, the shown cards first,
+    /// followed by the birthdays ordered by their date, and the missing photos of the
+    /// screensaver.
+    fn ranked_persons(&mut self) -> (Vec<usize>, Vec<String>) {
+        let today = self.clock.today();
+        let date = self.displayed_date();
+        let mut ranked: Vec<usize> = self
+            .persons_by_birthday
+            .on(date)
+            .iter()
+            .chain(self.half_birthdays.on(date))
+            .copied()
+            .chain(
+                self.milestones_by_date
+                    .on(date)
+                    .into_iter()
+                    .map(|(index, _)| index),
+            )
+            .collect();
+        let horizon = DUE_SOON.max(self.display.look_ahead) + 1;
+        let end = today.checked_add_days(Days::new(horizon)).unwrap_or(today);
+        for (_, index) in self.persons_by_birthday.between(today, end) {
+            if !ranked.contains(&index) {
+                ranked.push(index);
+            }
+        }
+        if let Some((_, index)) = self.hero() {
+            if !ranked.contains(&index) {
+                ranked.insert(0, index);
+            }
+        }
+        // the screensaver shows the photos of the persons on days without cards
+        let mut screensaver = Vec::new();
+        if self.screensaver_shown() {
+            if let Some(slides) = &mut self.screensaver {
+                for index in slides.persons(&self.persons).into_iter().rev() {
+                    ranked.retain(|ranked| *ranked != index);
+                    ranked.insert(0, index);
+                }
+                screensaver = slides.plan(&self.persons);
+            }
+        }
+
+        (ranked, screensaver)
+    }
+
+    /// Prepares the photos and qr codes of the shown and upcoming birthdays and loads the
+    /// missing photos, the ones of everybody else are only prepared once they are needed.
+    ///
+    /// With an image budget only the photos fitting into it are kept.
+    fn manage_images(&mut self) -> Command<Message> {
+        let (ranked, screensaver) = self.ranked_persons();
+
+        // every qr code takes a quarter of a megabyte
+        let kept: HashSet<usize> = ranked.iter().copied().collect();
+        for (index, person) in self.persons.iter_mut().enumerate() {
+            if !kept.contains(&index) {
+                person.qr_code = None;
+            } else if person.qr_code.is_none() {
+                generate_qr_code(
+                    person,
+                    self.display.greeting_url.as_deref(),
+                    self.cli.verbose,
+                );
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut missing = match &mut self.images {
+            Some(images) => images.plan(&mut self.persons, &ranked),
+            None => missing_images(&self.persons, &ranked),
+        };
+        #[cfg(target_arch = "wasm32")]
+        let mut missing = missing_images(&self.persons, &ranked);
+        missing.extend(screensaver);
+        // downloads of persons that are not shown soon anymore are not needed
+        self.cancel_downloads(&missing);
+        Command::batch(
+            missing
+                .iter()
+                .map(|url| self.load_image(url))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Raises the gpio flag if somebody has birthday today.
+    #[cfg(feature = "gpio")]
+    fn update_flag(&mut self) {
+        let today = self.clock.today();
+        let birthdays = self.persons_by_birthday.contains(today);
+        if let Some(flag) = &mut self.flag {
+            flag.set(birthdays);
+        }
+    }
+
+    /// Writes the pinned date and the day the script was told about to the state file.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_state(&self) {
+        let Some(path) = &self.cli.state_file else {
+            return;
+        };
+        let pinned_date = self.shown_date.filter(|_| self.date_pinned);
+        #[cfg(feature = "scripting")]
+        let script_day = self.script_day;
+        state::State::update(path, self.cli.quiet, |state| {
+            state.pinned_date = pinned_date;
+            #[cfg(feature = "scripting")]
+            {
+                state.script_day = Some(script_day);
+            }
+        });
+    }
+
+    /// Calls the day change hook of the script once a new day started.
+    #[cfg(feature = "scripting")]
+    fn tell_script(&mut self) {
+        let today = self.clock.today();
+        if today == self.script_day {
+            return;
+        }
+        self.script_day = today;
+        self.save_state();
+
+        if let Some(script) = &self.script {
+            let birthdays: Vec<&Person> = self
+                .persons_by_birthday
+                .on(today)
+                .iter()
+                .map(|&index| &self.persons[index])
+                .collect();
+            script.day_change(today, &birthdays, self.cli.quiet);
+        }
+    }
+
+    /// Reads the persons of the source again in the background.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load(&self) -> Command<Message> {
+        match &self.source {
+            Some(source) => load_persons(source.clone(), self.cli.quiet),
+            None => Command::none(),
+        }
+    }
+
+    /// Downloads the csv file again.
+    #[cfg(target_arch = "wasm32")]
+    fn load(&self) -> Command<Message> {
+        web::fetch_persons(self.cli.file.display().to_string())
+    }
+
+    /// Shows why the persons could not be loaded the first time until the next attempt.
+    fn load_failed(&mut self, reason: String) {
+        let message = format!(
+            "error loading {}, retrying in {} seconds: {reason}",
+            self.cli.file.display(),
+            LOAD_RETRY.as_secs()
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        println!("{message}");
+        #[cfg(target_arch = "wasm32")]
+        web_sys::console::error_1(&message.into());
+        self.load_error = Some(LoadError {
+            reason,
+            retry_at: Instant::now() + LOAD_RETRY,
+        });
+    }
+
+    /// Tells who was added, removed or changed when the persons were read again in a
+    /// toast and the log, the persons themselves are only logged with `-v`.
+    fn announce_changes(&mut self, persons: &[Person]) {
+        if self.loading {
+            return;
+        }
+        let changes = Changes::between(&self.persons, persons);
+        if changes.is_empty() {
+            return;
+        }
+
+        if !self.cli.quiet {
+            let mut lines = vec![format!(
+                "reloaded {}: {} added, {} removed, {} changed",
+                self.cli.file.display(),
+                changes.added.len(),
+                changes.removed.len(),
+                changes.changed.len()
+            )];
+            if self.cli.verbose > 0 {
+                for (what, persons) in [
+                    ("added", &changes.added),
+                    ("removed", &changes.removed),
+                    ("changed", &changes.changed),
+                ] {
+                    lines.extend(
+                        persons
+                            .iter()
+                            .map(|person| format!("{what} {}", redact::Redacted(person))),
+                    );
+                }
+            }
+            let message = lines.join("\n");
+            #[cfg(not(target_arch = "wasm32"))]
+            println!("{message}");
+            #[cfg(target_arch = "wasm32")]
+            web_sys::console::log_1(&message.into());
+        }
+
+        let summary = changes.summary();
+        self.show_toast(summary, false);
+    }
+
+    /// Replaces all persons and loads their images.
+    fn set_persons(&mut self, persons: Vec<Person>) -> Command<Message> {
+        if self.cli.verbose > 1 {
+            for person in &persons {
+                println!("loaded {}", redact::Redacted(person));
+            }
+        }
+        self.persons_by_birthday = BirthdayIndex::new(&persons);
+        self.half_birthdays = index_half_birthdays(&persons, self.cli.half_birthdays);
+        self.milestones_by_date = MilestoneIndex::new(&persons, self.cli.milestones);
+        self.persons = persons;
+        self.loaded_at = Local::now();
+        self.skipped = self.source.as_ref().map_or(0, |source| {
+            source.lock().map_or(0, |source| source.skipped())
+        });
+        #[cfg(feature = "gpio")]
+        self.update_flag();
+
+        // try to generate reqwest client if needed
+        #[cfg(feature = "network")]
+        let loadable = self.persons.iter().any(|person| person.image_url.is_some());
+        #[cfg(feature = "network")]
+        if loadable && self.client.is_none() {
+            match Client::builder().build() {
+                Ok(client) => self.client = Some(client),
+                Err(error) => {
+                    if self.cli.verbose > 0 {
+                        println!("error while initializing web client: {error}");
+                    }
+                }
+            }
+        }
+
+        self.cards = None;
+        self.prepare_cards();
+        // the images of the replaced persons are not needed anymore
+        self.cancel_downloads(&[]);
+        self.manage_images()
+    }
+
+    /// Persists the persons after the one at `index` was changed in the admin mode and
+    /// loads its image if it is shown soon.
+    fn persons_changed(&mut self, index: usize) -> Command<Message> {
+        self.persons_by_birthday = BirthdayIndex::new(&self.persons);
+        self.half_birthdays = index_half_birthdays(&self.persons, self.cli.half_birthdays);
+        self.milestones_by_date = MilestoneIndex::new(&self.persons, self.cli.milestones);
+        // the name in the greeting link may have changed
+        self.persons[index].qr_code = None;
+
+        let result = match &self.source {
+            Some(source) => match source.lock() {
+                Ok(mut source) => source.save(&self.persons),
+                Err(_) => Err("die Quelle ist nach einem Fehler gesperrt".into()),
+            },
+            None => Err("Speichern ist hier nicht möglich".into()),
+        };
+        self.editor.set_status(match result {
+            Ok(()) => Ok(format!("{} gespeichert.", self.persons[index].name())),
+            Err(error) => Err(format!("Fehler beim Speichern: {error}")),
+        });
+
+        #[cfg(feature = "network")]
+        if self.client.is_none() {
+            self.client = Client::builder().build().ok();
+        }
+        self.cards = None;
+        self.prepare_cards();
+        self.manage_images()
+    }
+
+    /// Applies the display settings of the config file again if it was changed.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_config(&mut self) {
+        let Some(watch) = &mut self.config else {
+            return;
+        };
+        if !watch.changed() {
+            return;
+        }
+
+        match watch.load() {
+            Ok(config) => {
+                self.keymap = Keymap::with_overrides(&config.keys);
+                let display = Display::with_config(&self.cli, &config.display);
+                let greeting_changed = display.greeting_url != self.display.greeting_url;
+                self.display = display;
+                // the qr codes of the shown cards are generated again by `manage_images`
+                if greeting_changed {
+                    for person in &mut self.persons {
+                        person.qr_code = None;
+                    }
+                }
+                self.show_toast(String::from("Einstellungen neu geladen"), false);
+            }
+            Err(error) => self.show_toast(format!("Einstellungen nicht übernommen: {error}"), true),
+        }
+    }
+
+    /// Renders the greeting cards of the persons named `name`, or of the selected or all
+    /// shown cards, to `--greeting-dir` and returns their paths.
+    #[cfg(not(any(target_arch = "wasm32", feature = "glow")))]
+    fn write_greeting_cards(&mut self, name: Option<&str>) -> Result<Vec<PathBuf>, String> {
+        let today = self.clock.today();
+        // a greeting card always shows the photo above the banner
+        let settings = CardSettings {
+            slide: 0,
+            layout: person::Layout::PhotoTop,
+            ..self.card_settings()
+        };
+        let cards: Vec<(usize, NaiveDate, Banners)> = match name {
+            Some(name) => self
+                .persons
+                .iter()
+                .enumerate()
+                .filter(|(_, person)| {
+                    person.announced() && person.name().eq_ignore_ascii_case(name)
+                })
+                .map(|(index, person)| {
+                    let date = person.next_birthday(today);
+                    let banners =
+                        self.card_banners(person, date, today, Occasion::Birthday, &settings);
+                    (index, date, banners)
+                })
+                .collect(),
+            None => {
+                self.prepare_cards();
+                let day = self.cards.as_ref().map_or(&[][..], |day| &day.cards[..]);
+                let date = self.displayed_date();
+                // touched cards are the selected ones
+                let selected = day
+                    .iter()
+                    .any(|(index, _, _)| self.expanded.contains(index));
+                day.iter()
+                    .filter(|(index, _, _)| !selected || self.expanded.contains(index))
+                    .map(|(index, _, banners)| (*index, date, banners.clone()))
+                    .collect()
+            }
+        };
+        if cards.is_empty() {
+            return Err(match name {
+                Some(name) => format!("Niemand mit dem Namen {name} gefunden."),
+                None => String::from("Keine Karte für eine Glückwunschkarte."),
+            });
+        }
+
+        let dir = self
+            .cli
+            .greeting_dir
+            .clone()
+            .unwrap_or_else(std::env::temp_dir);
+        let views: Vec<(PathBuf, Element<Message>)> = cards
+            .into_iter()
+            .map(|(index, date, banners)| {
+                let person = &self.persons[index];
+                let file = format!(
+                    "glueckwunsch-{}-{}.png",
+                    person
+                        .name()
+                        .to_lowercase()
+                        .replace(char::is_whitespace, "-"),
+                    date.format("%Y")
+                );
+                (
+                    dir.join(file),
+                    self.view_greeting_card(person, date, banners, &settings),
+                )
+            })
+            .collect();
+        let paths = views.iter().map(|(path, _)| path.clone()).collect();
+        snapshot::write_views(self, views, GREETING_CARD_SIZE).map_err(|error| {
+            println!("error writing greeting card: {error}");
+            format!("Glückwunschkarte nicht gespeichert: {error}")
+        })?;
+        Ok(paths)
+    }
+
+    /// Generates a greeting card of `person` celebrating on `date` with the greeting of
+    /// `--greeting-template` above the card.
+    #[cfg(not(any(target_arch = "wasm32", feature = "glow")))]
+    fn view_greeting_card<'a>(
+        &self,
+        person: &'a Person,
+        date: NaiveDate,
+        banners: Banners,
+        settings: &CardSettings,
+    ) -> Element<'a, Message> {
+        let age = date
+            .years_since(person.birthday)
+            .map_or(String::new(), |years| years.to_string());
+        let greeting = person.fill_template(&self.cli.greeting_template, &age);
+        container(
+            column![
+                text(greeting).size(48),
+                person.view_with_banners(banners, settings)
+            ]
+            .spacing(40)
+            .align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(40)
+        .center_x()
+        .center_y()
+        .into()
+    }
+
+    /// Shows `text` at the bottom of the window for a few seconds, errors only unless `--silent`.
+    fn show_toast(&mut self, text: String, error: bool) {
+        #[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+        if error {
+            self.errors += 1;
+        }
+        if error && self.cli.silent {
+            return;
+        }
+        if self.toasts.len() == MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(Toast {
+            text,
+            error,
+            shown: Instant::now(),
+        });
+    }
+
+    /// Generates the line with the source of the persons and the state of the images.
+    fn view_status_bar(&self) -> Element<'_, Message> {
+        // the source is locked while it is loaded again
+        let source = match self
+            .source
+            .as_ref()
+            .and_then(|source| source.try_lock().ok())
+        {
+            Some(source) => source.name(),
+            None => self.cli.file.display().to_string(),
+        };
+        let images = || {
+            self.persons
+                .iter()
+                .flat_map(|person| person.image_data.values())
+        };
+        let loaded = images().filter(|image| image.is_ok()).count();
+        let failed = images().filter(|image| image.is_err()).count();
+        let queued = self.downloads.len();
+
+        let status = format!(
+            "{source} · geladen {} · {} Zeilen übersprungen · Bilder: {loaded} geladen, {queued} ausstehend, {failed} fehlgeschlagen",
+            self.loaded_at.format("%d.%m.%Y %H:%M"),
+            self.skipped
+        );
+        text(status).size(14).into()
+    }
+
+    /// Generates the screen explaining why the persons could not be loaded.
+    fn view_load_error(&self) -> Element<'_, Message> {
+        let Some(error) = &self.load_error else {
+            return self.view_loading();
+        };
+        let seconds = error
+            .retry_at
+            .saturating_duration_since(Instant::now())
+            .as_secs();
+        column![
+            text(format!(
+                "{} konnte nicht geladen werden",
+                self.cli.file.display()
+            ))
+            .size(30)
+            .style(self.error_color()),
+            text(&error.reason).size(20),
+            text(format!("Neuer Versuch in {seconds} Sekunden")).size(20),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    /// Generates the screen shown until the persons are loaded the first time.
+    fn view_loading(&self) -> Element<'_, Message> {
+        let mut progress = vec![text(format!("Lade {} …", self.cli.file.display()))
+            .size(30)
+            .into()];
+        match csv::parsed() {
+            0 => {}
+            rows => progress.push(text(format!("{rows} Zeilen gelesen")).size(20).into()),
+        }
+        column(progress)
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .into()
+    }
+
+    /// Generates the black screen for the quiet hours.
+    fn view_blank(&self, now: NaiveTime) -> Element<'_, Message> {
+        let clock = if self.cli.blank_clock {
+            now.format("%H:%M").to_string()
+        } else {
+            String::new()
+        };
+
+        container(text(clock).size(40))
+            .style(style::blank as fn(&iced::Theme) -> container::Appearance)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into()
+    }
+
+    /// Returns the banners of the card of `person` celebrating `occasion` on `date` as
+    /// seen on `today` with the banner of the script if there is one.
+    fn card_banners(
+        &self,
+        person: &Person,
+        date: NaiveDate,
+        today: NaiveDate,
+        occasion: Occasion,
+        settings: &CardSettings,
+    ) -> Banners {
+        let mut banners = person.banners(date, occasion, today, settings);
+        if let (Occasion::HalfBirthday, Some(template)) =
+            (occasion, &self.cli.half_birthday_template)
+        {
+            let age = date
+                .years_since(person.birthday)
+                .map_or(String::new(), |years| format!("{years}½"));
+            banners = vec![(settings.locale, person.fill_template(template, &age))];
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(script) = &self.script {
+            for (_, banner) in &mut banners {
+                *banner = script.banner(person, banner.clone());
+            }
+        }
+        banners
+    }
+
+    /// Returns who celebrates on which date.
+    fn calendar(&self) -> Calendar<'_> {
+        Calendar {
+            birthdays: &self.persons_by_birthday,
+            half_birthdays: &self.half_birthdays,
+            milestones: &self.milestones_by_date,
+        }
+    }
+
+    /// Returns the date whose cards are shown, the next one within the look ahead if
+    /// nobody celebrates today.
+    fn displayed_date(&self) -> NaiveDate {
+        self.calendar()
+            .displayed_date(self.clock.today(), self.shown_date, self.display.look_ahead)
+    }
+
+    /// Returns whether the shown screen changes by itself within seconds, cycling through
+    /// photos or languages or returning to today some time after it was touched.
+    fn animates(&self) -> bool {
+        let returns = !self.date_pinned && (self.shown_date.is_some() || !self.expanded.is_empty());
+        let cards = self.cards.as_ref().map_or(&[][..], |day| &day.cards);
+        let alternates = self.cli.alternate_every.is_some()
+            && self.cli.second_locale.is_some()
+            && !cards.is_empty();
+        let slides = cards.iter().any(|(index, _, _)| {
+            let photos = &self.persons[*index].image_data;
+            photos.values().filter(|photo| photo.is_ok()).count() > 1
+        });
+        returns || alternates || slides || self.screensaver_shown()
+    }
+
+    /// Returns the date and index of the next birthday if it is shown as a large card
+    /// because there are none on the shown date.
+    fn hero(&self) -> Option<(NaiveDate, usize)> {
+        let day = self.cards.as_ref()?;
+        if !self.display.hero || !day.cards.is_empty() || self.loading {
+            return None;
+        }
+        // the birthdays of the next year are found even on the day after the last one
+        let from = day.date.checked_add_days(Days::new(1))?;
+        let to = day.date.checked_add_days(Days::new(367))?;
+        self.persons_by_birthday.between(from, to).first().copied()
+    }
+
+    /// Returns whether the screensaver is shown because there are no cards.
+    fn screensaver_shown(&self) -> bool {
+        self.screensaver.is_some()
+            && self.hero().is_none()
+            && self.screen == Screen::Birthdays
+            && !self.loading
+            && self.cards.as_ref().is_some_and(|day| day.cards.is_empty())
+    }
+
+    /// Returns the settings of the cards in all languages.
+    fn card_settings(&self) -> CardSettings {
+        CardSettings {
+            silent: self.cli.silent,
+            error_color: self.error_color(),
+            locale: self.cli.locale,
+            second_locale: self.cli.second_locale,
+            months_until: self.cli.months_until,
+            slide: (Local::now().timestamp() / SLIDE_DURATION) as usize,
+            layout: self.display.layout,
+            photo_fit: self.display.photo_fit,
+            photo_height: self.display.photo_height,
+        }
+    }
+
+    /// Prepares the cards of `date` with the banners written on `today`.
+    fn day_cards(&self, date: NaiveDate, today: NaiveDate) -> DayCards {
+        let occasions = self.calendar().occasions(date);
+        let settings = self.card_settings();
+        let cards = occasions
+            .into_iter()
+            .map(|(index, occasion)| {
+                let banners =
+                    self.card_banners(&self.persons[index], date, today, occasion, &settings);
+                (index, occasion, banners)
+            })
+            .collect();
+        DayCards {
+            date,
+            written: today,
+            cards,
+        }
+    }
+
+    /// Prepares the cards of the shown date unless they are already.
+    fn prepare_cards(&mut self) {
+        let (date, today) = (self.displayed_date(), self.clock.today());
+        if !self
+            .cards
+            .as_ref()
+            .is_some_and(|cards| cards.date == date && cards.written == today)
+        {
+            self.cards = Some(self.day_cards(date, today));
+        }
+
+        // dates looked at on the touch screen were not announced
+        #[cfg(not(target_arch = "wasm32"))]
+        if let (Some(audit), Some(cards), None) = (&mut self.audit, &self.cards, self.shown_date) {
+            let persons = cards
+                .cards
+                .iter()
+                .map(|(index, _, _)| &self.persons[*index]);
+            audit.displayed(cards.date, persons);
+        }
+    }
+
+    /// Generates the cards of all persons whose birthday is today.
+    fn view_birthdays(&self) -> Element<'_, Message> {
+        let today = self.clock.today();
+        let date = self.displayed_date();
+
+        // the cards are only prepared here if a message changed the date without it
+        let prepared;
+        let day = match &self.cards {
+            Some(cards) if cards.date == date && cards.written == today => cards,
+            _ => {
+                prepared = self.day_cards(date, today);
+                &prepared
+            }
+        };
+        let settings = self.card_settings();
+        if let Some((next, index)) = self.hero() {
+            let person = &self.persons[index];
+            let banners = self.card_banners(person, next, date, Occasion::Birthday, &settings);
+            let countdown = self.cli.locale.countdown((next - date).num_days());
+            let badge = container(text(self.cli.locale.visual(&countdown)).size(30))
+                .padding([5, 20])
+                .style(style::countdown as fn(&iced::Theme) -> container::Appearance);
+            return column![badge, person.card(banners, &settings).view_hero()]
+                .spacing(20)
+                .align_items(Alignment::Center)
+                .into();
+        }
+        if let (true, Some(screensaver)) = (day.cards.is_empty(), &self.screensaver) {
+            return screensaver.view(&self.persons);
+        }
+        // alternating languages show one of them at a time
+        let settings = match (self.cli.alternate_every, self.cli.second_locale) {
+            (Some(seconds), Some(second)) => {
+                let cycle = Local::now().timestamp() / seconds.max(1) as i64;
+                CardSettings {
+                    locale: if cycle % 2 == 0 {
+                        settings.locale
+                    } else {
+                        second
+                    },
+                    second_locale: None,
+                    ..settings
+                }
+            }
+            _ => settings,
+        };
+
+        let mut elements: Vec<Element<Message>> = day
+            .cards
+            .iter()
+            .map(|(index, _, banners)| {
+                let index = *index;
+                let person = &self.persons[index];
+                let card = if self.expanded.contains(&index) {
+                    person.view_details(&settings, today)
+                } else {
+                    // cards in another language than the alternating ones keep their banner
+                    let shown: Banners = banners
+                        .iter()
+                        .filter(|(locale, _)| settings.locales().any(|shown| shown == *locale))
+                        .cloned()
+                        .collect();
+                    let banners = if shown.is_empty() {
+                        banners.clone()
+                    } else {
+                        shown
+                    };
+                    person.view_with_banners(banners, &settings)
+                };
+
+                // later birthdays of the look ahead count down to their day
+                let card = if date > today {
+                    let countdown = self.cli.locale.countdown((date - today).num_days());
+                    let badge = container(text(self.cli.locale.visual(&countdown)).size(14))
+                        .padding([2, 10])
+                        .style(style::countdown as fn(&iced::Theme) -> container::Appearance);
+                    column![badge, card]
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .into()
+                } else {
+                    card
+                };
+
+                match (&person.contact_url, self.cli.touch) {
+                    (_, true) => button(card)
+                        .style(theme::Button::Text)
+                        .padding(20)
+                        .on_press(Message::Ui(UiMessage::ToggleDetails(index)))
+                        .into(),
+                    (Some(url), false) => button(card)
+                        .style(theme::Button::Text)
+                        .on_press(Message::Ui(UiMessage::OpenLink(url.clone())))
+                        .into(),
+                    (None, false) => card,
+                }
+            })
+            .collect();
+        // the first card is on the right in languages written from right to left
+        if self.cli.locale.rtl() {
+            elements.reverse();
+        }
+
+        let cards = self.display.layout.arrange(elements);
+        if date == today {
+            cards
+        } else {
+            column![
+                text(format!("Geburtstage am {}", date.format("%d.%m.%Y"))).size(30),
+                cards
+            ]
+            .spacing(20)
+            .align_items(Alignment::Center)
+            .into()
+        }
+    }
+
+    /// Shows the next date with birthdays after the shown one if `forward` and the
+    /// previous one otherwise, but never a date before today.
+    fn browse(&mut self, forward: bool) {
+        let today = self.clock.today();
+        let from = self.shown_date.unwrap_or(today);
+        let date = self.calendar().browse(from, today, forward);
+
+        self.expanded.clear();
+        self.shown_date = (date != today).then_some(date);
+        self.date_pinned = false;
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> std::process::ExitCode {
+    native::run()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    web::run();
+}
+
+#[cfg(all(test, feature = "network", feature = "gui"))]
+mod tests {
+    use super::*;
+
+    use httptest::matchers::request::method_path;
+    use httptest::responders::{delay_and_then, status_code};
+    use httptest::{Expectation, Server};
+    use std::io::Cursor;
+
+    /// Returns a png image of one pixel.
+    fn png() -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(1, 1)
+            .write_to(&mut bytes, image::ImageOutputFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    /// Expects one request of `path`, answering it with `status` and `body` of
+    /// `content_type`.
+    fn expect(
+        server: &Server,
+        path: &'static str,
+        status: u16,
+        content_type: &'static str,
+        body: impl Into<Vec<u8>>,
+    ) {
+        server.expect(
+            Expectation::matching(method_path("GET", path)).respond_with(
+                status_code(status)
+                    .insert_header("Content-Type", content_type)
+                    .body(body.into()),
+            ),
+        );
+    }
+
+    /// Requests the image at `url` like the display does, giving up after `timeout`.
+    fn request(url: &str, timeout: Duration) -> Result<Handle, String> {
+        let runtime = crate::runtime::current_thread().unwrap();
+        let request = Client::new().get(url).timeout(timeout);
+        let (image_data, orig_url) =
+            runtime.block_on(request_birthday_image(request, url.to_string(), 0));
+        assert_eq!(orig_url, url);
+        image_data
+    }
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn loads_an_image() {
+        let server = Server::run();
+        expect(&server, "/photo.png", 200, "image/png", png());
+
+        assert!(request(&server.url_str("/photo.png"), TIMEOUT).is_ok());
+    }
+
+    #[test]
+    fn fails_on_error_status() {
+        let server = Server::run();
+        expect(&server, "/gone.png", 404, "text/plain", "");
+        expect(&server, "/broken.png", 500, "text/plain", "");
+
+        let error = request(&server.url_str("/gone.png"), TIMEOUT).unwrap_err();
+        assert_eq!(error, "[failed to load image]");
+        assert!(request(&server.url_str("/broken.png"), TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn fails_on_timeout() {
+        let server = Server::run();
+        server.expect(
+            Expectation::matching(method_path("GET", "/slow.png"))
+                .respond_with(delay_and_then(Duration::from_secs(3), status_code(200))),
+        );
+
+        let start = Instant::now();
+        assert!(request(&server.url_str("/slow.png"), Duration::from_millis(200)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn decodes_by_content_not_by_content_type() {
+        let server = Server::run();
+        expect(
+            &server,
+            "/login",
+            200,
+            "text/html",
+            "<html>Anmeldung</html>",
+        );
+        // servers sending photos with a generic type still work
+        expect(&server, "/photo", 200, "application/octet-stream", png());
+
+        let error = request(&server.url_str("/login"), TIMEOUT).unwrap_err();
+        assert!(error.contains("web page"), "{error}");
+        assert!(request(&server.url_str("/photo"), TIMEOUT).is_ok());
+    }
+
+    #[test]
+    fn aborts_downloads_larger_than_the_limit() {
+        let server = Server::run();
+        let video = vec![0; (photo::DEFAULT_MAX_DOWNLOAD + 1) * 1024 * 1024];
+        expect(&server, "/video.mp4", 200, "video/mp4", video);
+
+        let error = request(&server.url_str("/video.mp4"), TIMEOUT).unwrap_err();
+        assert!(error.contains("larger than 10 MB"), "{error}");
+    }
+
+    #[test]
+    fn requests_again_after_a_failure() {
+        let mut server = Server::run();
+        expect(&server, "/flaky.png", 503, "text/plain", "");
+
+        // a single request does not retry, the image is requested again once it is due
+        assert!(request(&server.url_str("/flaky.png"), TIMEOUT).is_err());
+        server.verify_and_clear();
+        expect(&server, "/flaky.png", 200, "image/png", png());
+        assert!(request(&server.url_str("/flaky.png"), TIMEOUT).is_ok());
+    }
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod day_transition {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::fixtures::{builder, date};
+
+    use chrono::{TimeZone, Utc};
+
+    const ANNA: &str = "https://example.com/anna.png";
+    const BEN: &str = "https://example.com/ben.png";
+
+    fn person(first_name: &str, birthday: NaiveDate, url: &str) -> Person {
+        builder(first_name, birthday)
+            .image_url(Some(url.to_string()))
+            .build()
+            .unwrap()
+    }
+
+    /// Moves the clock of `display` to `hour`:`minute` utc on `day`.
+    fn set_time(display: &mut BirthdayDisplay, day: NaiveDate, hour: u32, minute: u32) {
+        let time = Utc.from_utc_datetime(&day.and_hms_opt(hour, minute, 0).unwrap());
+        display.clock = Arc::new(FixedClock(time));
+    }
+
+    /// Returns a display showing Anna's birthday a minute before midnight with her photo
+    /// loaded, Ben has his birthday the next day.
+    fn display_before_midnight() -> BirthdayDisplay {
+        let (mut display, _) = BirthdayDisplay::new((Cli::default(), None, Services::default()));
+        set_time(&mut display, date(2024, 3, 10), 23, 59);
+        display.day = display.clock.today();
+        display.loading = false;
+        let _ = display.set_persons(vec![
+            person("Anna", date(1990, 3, 10), ANNA),
+            person("Ben", date(1990, 3, 11), BEN),
+        ]);
+        let photo = Handle::from_pixels(1, 1, vec![0; 4]);
+        let _ = display.update(Message::Data(DataMessage::ImageReceived(
+            Ok(photo),
+            ANNA.to_string(),
+        )));
+        display
+    }
+
+    fn shown(display: &BirthdayDisplay) -> (NaiveDate, Vec<String>) {
+        let cards = display.cards.as_ref().unwrap();
+        let banners = cards
+            .cards
+            .iter()
+            .map(|(_, _, banners)| banners[0].1.clone())
+            .collect();
+        (cards.date, banners)
+    }
+
+    #[test]
+    fn the_cards_the_ages_and_the_photos_change_at_midnight() {
+        let mut display = display_before_midnight();
+        assert_eq!(
+            shown(&display),
+            (
+                date(2024, 3, 10),
+                vec![String::from("Anna Muster wird heute 34 Jahre alt.")]
+            )
+        );
+        assert!(display.persons[0].image_data.contains_key(ANNA));
+
+        set_time(&mut display, date(2024, 3, 11), 0, 0);
+        let _ = display.update(Message::Timer(TimerMessage::UpdateDay(Instant::now())));
+
+        assert_eq!(display.day, date(2024, 3, 11));
+        assert_eq!(
+            shown(&display),
+            (
+                date(2024, 3, 11),
+                vec![String::from("Ben Muster wird heute 34 Jahre alt.")]
+            )
+        );
+        // Anna's photo is only needed again next year, Ben's is requested
+        assert!(display.persons[0].image_data.is_empty());
+        assert!(display.downloads.contains_key(BEN));
+    }
+
+    #[test]
+    fn any_message_of_the_new_day_leaves_a_date_looked_at() {
+        let mut display = display_before_midnight();
+        display.shown_date = Some(date(2024, 3, 20));
+
+        set_time(&mut display, date(2024, 3, 11), 0, 5);
+        let _ = display.update(Message::Data(DataMessage::DownloadCancelled));
+
+        assert_eq!(display.shown_date, None);
+        assert_eq!(shown(&display).0, date(2024, 3, 11));
+    }
+
+    #[test]
+    fn a_pinned_date_is_kept_until_it_is_today() {
+        let mut display = display_before_midnight();
+        display.shown_date = Some(date(2024, 3, 12));
+        display.date_pinned = true;
+
+        set_time(&mut display, date(2024, 3, 11), 0, 5);
+        let _ = display.update(Message::Data(DataMessage::DownloadCancelled));
+        assert_eq!(display.shown_date, Some(date(2024, 3, 12)));
+
+        set_time(&mut display, date(2024, 3, 12), 0, 5);
+        let _ = display.update(Message::Data(DataMessage::DownloadCancelled));
+        assert_eq!(display.shown_date, None);
+        assert!(!display.date_pinned);
+    }
 }
@@ -0,0 +1,174 @@
+use crate::csv::custom_date_format;
+use crate::error::BirthdayError;
+use crate::person::Person;
+use crate::source;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use clap::ValueEnum;
+
+/// Which file wins if a person differs between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Prefer {
+    /// the file given before `merge`
+    First,
+    /// the file given after `merge`
+    Second,
+}
+
+/// Describes the birthday and the links of `person` to tell conflicting entries apart.
+fn describe(person: &Person) -> String {
+    let mut description = person
+        .birthday
+        .format(custom_date_format::FORMAT)
+        .to_string();
+    for url in [&person.image_url, &person.contact_url]
+        .into_iter()
+        .flatten()
+    {
+        description.push_str(", ");
+        description.push_str(url);
+    }
+    description
+}
+
+/// Returns whether both values are set and differ.
+fn differ(a: &Option<String>, b: &Option<String>) -> bool {
+    matches!((a, b), (Some(a), Some(b)) if a != b)
+}
+
+/// Asks on the terminal which of the two entries of a person to keep.
+fn ask(first: &Person, second: &Person, names: (&Path, &Path)) -> io::Result<Prefer> {
+    let stdin = io::stdin();
+    loop {
+        println!("conflict for {}:", first.name());
+        println!("  1: {} ({})", describe(first), names.0.display());
+        println!("  2: {} ({})", describe(second), names.1.display());
+        print!("keep [1/2]? ");
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if stdin.lock().read_line(&mut answer)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        match answer.trim() {
+            "1" => return Ok(Prefer::First),
+            "2" => return Ok(Prefer::Second),
+            _ => {}
+        }
+    }
+}
+
+/// Writes the union of the persons of `first` and `second` to `output`.
+///
+/// Persons are the same if first and last name match. Missing links are taken from
+/// the other file while different birthdays or links are a conflict that is resolved
+/// with `prefer`, by asking on a terminal or otherwise aborts the merge.
+pub fn run(
+    first: Vec<Person>,
+    (first_path, second_path): (&Path, &Path),
+    output: &Path,
+    key: Option<&Path>,
+    prefer: Option<Prefer>,
+    quiet: bool,
+) -> Result<(), BirthdayError> {
+    let second = source::open(second_path, key).load(quiet)?;
+
+    let mut merged = first;
+    let mut index: HashMap<(String, String), usize> = merged
+        .iter()
+        .enumerate()
+        .map(|(position, person)| {
+            (
+                (person.last_name.clone(), person.first_name.clone()),
+                position,
+            )
+        })
+        .collect();
+
+    let interactive = prefer.is_none() && io::stdin().is_terminal();
+    let mut unresolved = Vec::new();
+    for person in second {
+        let key = (person.last_name.clone(), person.first_name.clone());
+        let Some(&position) = index.get(&key) else {
+            index.insert(key, merged.len());
+            merged.push(person);
+            continue;
+        };
+
+        let existing = &mut merged[position];
+        let conflict = existing.birthday != person.birthday
+            || differ(&existing.image_url, &person.image_url)
+            || differ(&existing.contact_url, &person.contact_url);
+        if !conflict {
+            if existing.image_url.is_none() && person.image_url.is_some() {
+                existing.image_url = person.image_url;
+                existing.source.text = None;
+            }
+            if existing.contact_url.is_none() && person.contact_url.is_some() {
+                existing.contact_url = person.contact_url;
+                existing.source.text = None;
+            }
+            // differing colors, notes and departments are not worth asking about, the
+            // first ones are kept
+            if existing.color.is_none() && person.color.is_some() {
+                existing.color = person.color;
+                existing.source.text = None;
+            }
+            if existing.notes.is_none() && person.notes.is_some() {
+                existing.notes = person.notes;
+                existing.source.text = None;
+            }
+            if existing.department.is_none() && person.department.is_some() {
+                existing.department = person.department;
+                existing.source.text = None;
+            }
+            // an opt-out in any of the sources is respected
+            if !existing.opt_out && person.opt_out {
+                existing.opt_out = true;
+                existing.source.text = None;
+            }
+            continue;
+        }
+
+        let choice = match prefer {
+            Some(prefer) => Some(prefer),
+            None if interactive => Some(
+                ask(existing, &person, (first_path, second_path))
+                    .map_err(|error| BirthdayError::io(Path::new("the standard input"), error))?,
+            ),
+            None => None,
+        };
+        match choice {
+            Some(Prefer::First) => {}
+            Some(Prefer::Second) => *existing = person,
+            None => unresolved.push(format!(
+                "{}: {} ({}) or {} ({})",
+                person.name(),
+                describe(existing),
+                first_path.display(),
+                describe(&person),
+                second_path.display()
+            )),
+        }
+    }
+
+    if !unresolved.is_empty() {
+        for conflict in &unresolved {
+            eprintln!("conflict for {conflict}");
+        }
+        return Err(format!(
+            "found {} conflicts, choose with --prefer first or --prefer second",
+            unresolved.len()
+        )
+        .into());
+    }
+
+    source::open(output, key).save(&merged)?;
+    if !quiet {
+        println!("wrote {} persons to {}", merged.len(), output.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use crate::error::BirthdayError;
+use std::env;
+use std::ffi::OsString;
+use std::process::{self, Command, Stdio};
+
+use winit::event_loop::EventLoop;
+
+/// Environment variable that starts this executable only to list the monitors.
+const LIST_VARIABLE: &str = "BIRTHDAY_DISPLAY_LIST_MONITORS";
+
+/// Position and size of a monitor in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Monitor {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Prints the monitors and exits if this process was started by [`monitors`].
+///
+/// This has to run before anything else as only one event loop can be created
+/// per process and the gui needs its own.
+pub fn list_if_requested() {
+    if env::var_os(LIST_VARIABLE).is_none() {
+        return;
+    }
+
+    let event_loop = EventLoop::new();
+    for monitor in event_loop.available_monitors() {
+        let position = monitor.position();
+        let size = monitor.size();
+        println!(
+            "{} {} {} {}",
+            position.x, position.y, size.width, size.height
+        );
+    }
+    process::exit(0);
+}
+
+/// Returns the available monitors by asking a new process of this executable.
+pub fn monitors() -> Result<Vec<Monitor>, BirthdayError> {
+    let output = env::current_exe()
+        .and_then(|executable| {
+            Command::new(executable)
+                .env(LIST_VARIABLE, "1")
+                .stdin(Stdio::null())
+                .stderr(Stdio::inherit())
+                .output()
+        })
+        .map_err(|error| BirthdayError::Gui(format!("error listing the monitors: {error}")))?;
+    if !output.status.success() {
+        return Err(BirthdayError::Gui("failed to list the monitors".into()));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            parse(line).ok_or_else(|| {
+                BirthdayError::Gui(format!("invalid monitor description \"{line}\""))
+            })
+        })
+        .collect()
+}
+
+/// Parses a monitor printed by [`list_if_requested`].
+fn parse(line: &str) -> Option<Monitor> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    match fields[..] {
+        [x, y, width, height] => Some(Monitor {
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+            width: width.parse().ok()?,
+            height: height.parse().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+/// Options that only the first process can use, e.g. as they bind a port or write a
+/// file, each followed by its value.
+const OWN_OPTIONS: [&str; 3] = ["--control-port", "--status-file", "--gpio-pin"];
+
+/// Options without a separate value that only the first process can use.
+const OWN_FLAGS: [&str; 3] = ["--all-monitors", "--multiple-instances", "--watchdog"];
+
+/// Returns `arguments` without the options the first process keeps to itself.
+fn child_arguments(arguments: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut arguments = arguments.into_iter();
+    let mut kept = Vec::new();
+    while let Some(argument) = arguments.next() {
+        let text = argument.to_string_lossy();
+        let name = text.split_once('=').map_or(&*text, |(name, _)| name);
+        if OWN_OPTIONS.contains(&name) {
+            if !text.contains('=') {
+                arguments.next();
+            }
+        } else if !OWN_FLAGS.contains(&name) {
+            kept.push(argument);
+        }
+    }
+    kept
+}
+
+/// Starts a copy of this process with the same arguments on every monitor but the first.
+///
+/// The control port, the watchdog, the status file and the gpio pin stay with this
+/// process.
+pub fn spawn_on_other_monitors(count: usize) -> Result<(), BirthdayError> {
+    let executable = env::current_exe().map_err(|error| {
+        BirthdayError::Gui(format!("error starting the other windows: {error}"))
+    })?;
+    let arguments = child_arguments(env::args_os().skip(1));
+
+    for index in 1..count {
+        Command::new(&executable)
+            .args(&arguments)
+            .arg("--monitor")
+            .arg(index.to_string())
+            .arg("--multiple-instances")
+            .spawn()
+            .map_err(|error| {
+                BirthdayError::Gui(format!(
+                    "error starting the window on monitor {index}: {error}"
+                ))
+            })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arguments(line: &str) -> Vec<OsString> {
+        line.split_whitespace().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn children_leave_out_the_options_of_the_first_process() {
+        assert_eq!(
+            child_arguments(arguments(
+                "--all-monitors --control-port 8080 --watchdog --status-file /run/status \
+                 --quiet persons.csv"
+            )),
+            arguments("--quiet persons.csv")
+        );
+        assert_eq!(
+            child_arguments(arguments(
+                "--control-port=8080 --watchdog=/run/watchdog --status-file=/run/status \
+                 --gpio-pin 17 --multiple-instances persons.csv --date 24.12.2026"
+            )),
+            arguments("persons.csv --date 24.12.2026")
+        );
+    }
+}
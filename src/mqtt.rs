@@ -0,0 +1,92 @@
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// A minimal MQTT 3.1.1 client publishing messages with QoS 0.
+pub struct Connection {
+    stream: TcpStream,
+}
+
+impl Connection {
+    /// Connects to the broker at `host` and `port` with optional `credentials`.
+    pub fn connect(
+        host: &str,
+        port: u16,
+        client_id: &str,
+        credentials: Option<(&str, &str)>,
+    ) -> io::Result<Self> {
+        let mut stream = TcpStream::connect((host, port))?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+
+        // clean session
+        let mut flags = 0x02;
+        if credentials.is_some() {
+            flags |= 0xc0;
+        }
+        let mut body = Vec::new();
+        write_string(&mut body, "MQTT");
+        body.extend([4, flags, 0, 60]);
+        write_string(&mut body, client_id);
+        if let Some((username, password)) = credentials {
+            write_string(&mut body, username);
+            write_string(&mut body, password);
+        }
+        write_packet(&mut stream, 0x10, &body)?;
+
+        let mut connack = [0; 4];
+        stream.read_exact(&mut connack)?;
+        match connack {
+            [0x20, 2, _, 0] => Ok(Self { stream }),
+            [0x20, 2, _, 4 | 5] => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "the broker rejected the credentials",
+            )),
+            [0x20, 2, _, code] => Err(io::Error::other(format!(
+                "the broker refused the connection with code {code}"
+            ))),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unexpected answer from the broker",
+            )),
+        }
+    }
+
+    /// Publishes `payload` on `topic`, keeping it for later subscribers if `retain` is set.
+    pub fn publish(&mut self, topic: &str, payload: &[u8], retain: bool) -> io::Result<()> {
+        let mut body = Vec::new();
+        write_string(&mut body, topic);
+        body.extend(payload);
+        write_packet(&mut self.stream, 0x30 | u8::from(retain), &body)
+    }
+
+    /// Closes the connection cleanly so the broker does not discard the last messages.
+    pub fn disconnect(mut self) -> io::Result<()> {
+        write_packet(&mut self.stream, 0xe0, &[])
+    }
+}
+
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend((value.len() as u16).to_be_bytes());
+    buffer.extend(value.as_bytes());
+}
+
+fn write_packet(stream: &mut TcpStream, header: u8, body: &[u8]) -> io::Result<()> {
+    let mut packet = vec![header];
+
+    // the remaining length is encoded with seven bits per byte
+    let mut length = body.len();
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+
+    packet.extend(body);
+    stream.write_all(&packet)
+}
@@ -0,0 +1,369 @@
+use crate::config::{self, Config};
+use crate::error::BirthdayError;
+#[cfg(all(feature = "gui", feature = "gpio"))]
+use crate::gpio;
+#[cfg(feature = "gui")]
+use crate::instance::{self, Instance};
+#[cfg(all(feature = "layer-shell", not(feature = "glow")))]
+use crate::layer;
+#[cfg(feature = "gui")]
+use crate::panic::{self, PanicDisplay};
+#[cfg(feature = "gui")]
+use crate::runtime;
+#[cfg(feature = "scripting")]
+use crate::script::{Script, ScriptedSource};
+#[cfg(all(feature = "gui", not(feature = "glow")))]
+use crate::snapshot;
+use crate::source::PersonSource;
+#[cfg(feature = "network")]
+use crate::sync;
+use crate::{
+    bundle, check, clock, export, merge, notify, person, redact, server, source, Cli, Commands,
+};
+#[cfg(feature = "gui")]
+use crate::{control::ControlPort, watchdog::Watchdog};
+#[cfg(feature = "gui")]
+use crate::{monitor, BirthdayDisplay, Services};
+
+#[cfg(feature = "gui")]
+use std::env;
+#[cfg(feature = "gui")]
+use std::panic::AssertUnwindSafe;
+#[cfg(feature = "gui")]
+use std::path::PathBuf;
+use std::process::ExitCode;
+#[cfg(feature = "scripting")]
+use std::sync::Arc;
+
+use clap::Parser;
+
+#[cfg(feature = "gui")]
+use iced::window::{Icon, Position};
+#[cfg(feature = "gui")]
+use iced::{Application, Settings};
+
+/// Shows `message` in a new process as the event loop of this one can not be restarted.
+#[cfg(feature = "gui")]
+fn show_panic(message: &str, file: &PathBuf) {
+    let result = env::current_exe().and_then(|executable| {
+        std::process::Command::new(executable)
+            .arg("--show-panic")
+            .arg(message)
+            .arg(file)
+            .status()
+    });
+    if let Err(error) = result {
+        eprintln!("error showing crash message: {error}");
+    }
+}
+
+/// Runs the command given on the command line or the display in a window with the
+/// persons of the csv file and prints the error it fails with.
+pub fn run() -> ExitCode {
+    #[cfg(feature = "gui")]
+    monitor::list_if_requested();
+
+    let cli: Cli = Cli::parse();
+    let error_json = cli.error_json;
+    match start(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            if error_json {
+                eprintln!("{}", error.to_json());
+            } else {
+                eprintln!("Error: {error}");
+            }
+            ExitCode::from(error.exit_code())
+        }
+    }
+}
+
+/// Runs what `cli` asks for.
+fn start(mut cli: Cli) -> Result<(), BirthdayError> {
+    redact::set_log_pii(cli.log_pii);
+    person::set_respect_optout(cli.respect_optout);
+    person::set_image_schemes(&cli.image_schemes);
+    #[cfg(all(feature = "network", feature = "gui"))]
+    crate::politeness::set_rate(cli.host_rate);
+    #[cfg(all(feature = "network", feature = "gui"))]
+    crate::photo::set_max_download(cli.max_image_size);
+    if bundle::is_bundle(&cli.file) {
+        bundle::open(&mut cli)?;
+    }
+    #[cfg(feature = "gui")]
+    if let Some(threads) = cli.worker_threads {
+        runtime::set_worker_threads(threads.into());
+    }
+
+    #[cfg(feature = "gui")]
+    if let Some(message) = cli.show_panic {
+        return PanicDisplay::run(Settings::with_flags(message)).map_err(BirthdayError::from);
+    }
+
+    #[cfg(feature = "network")]
+    if let Some(url) = cli.config.clone().filter(|path| config::is_url(path)) {
+        let cache = match cli.config_cache.clone() {
+            Some(cache) => cache,
+            None => config::default_cache()?,
+        };
+        config::fetch(&url.to_string_lossy(), &cache, cli.quiet)?;
+        cli.config = Some(cache);
+    }
+    #[cfg(not(feature = "network"))]
+    if cli.config.as_deref().is_some_and(config::is_url) {
+        return Err("downloading the config file needs the network feature".into());
+    }
+
+    let config = match &cli.config {
+        Some(path) => config::load(path, cli.profile.as_deref())?,
+        None => Config::default(),
+    };
+
+    let mut source = source::open(&cli.file, cli.key_file.as_deref());
+    // the display shares the script with the source for the banners and the day change
+    #[cfg(feature = "scripting")]
+    let script = match &cli.script {
+        Some(path) => {
+            let script = Arc::new(Script::load(path)?);
+            source = Box::new(ScriptedSource::new(source, script.clone()));
+            Some(script)
+        }
+        None => None,
+    };
+    if cli.implausible_birthdays != source::Implausible::Allow {
+        source = Box::new(source::PlausibleBirthdays::new(
+            source,
+            clock::for_date(cli.date),
+            cli.implausible_birthdays,
+        ));
+    }
+    if !config.departments.is_empty() {
+        source = Box::new(source::Departments::new(source, config.departments.clone()));
+    }
+
+    if let Some(path) = &cli.bundle {
+        return bundle::write(&cli, source.as_mut(), path);
+    }
+
+    if let Some(command) = &cli.command {
+        let persons = source.load(cli.quiet)?;
+        return match command {
+            Commands::Export {
+                anonymize,
+                output,
+                format,
+            } => {
+                let persons = if *anonymize {
+                    export::anonymize(&persons)?
+                } else {
+                    persons
+                };
+                match format {
+                    Some(format) => export::run(
+                        format,
+                        &persons,
+                        &cli.file,
+                        cli.eink,
+                        clock::for_date(cli.date).as_ref(),
+                    ),
+                    None => {
+                        export::write_persons(&persons, output.as_deref(), cli.key_file.as_deref())
+                    }
+                }
+            }
+            #[cfg(feature = "network")]
+            Commands::Sync { dry_run, target } => {
+                sync::run(target, &persons, &config, *dry_run, cli.quiet)
+            }
+            Commands::Notify { dry_run, daemon } => {
+                notify::run(persons, source.as_mut(), &config, *dry_run, *daemon, &cli)
+            }
+            Commands::Merge {
+                other,
+                output,
+                prefer,
+            } => merge::run(
+                persons,
+                (&cli.file, other),
+                output,
+                cli.key_file.as_deref(),
+                *prefer,
+                cli.quiet,
+            ),
+            Commands::Serve { listen, web_root } => server::run(
+                *listen,
+                source,
+                web_root.as_deref(),
+                cli.quiet,
+                clock::for_date(cli.date),
+            ),
+            Commands::Check => check::run(&persons, source.skipped(), cli.quiet),
+        };
+    }
+
+    #[cfg(all(feature = "gui", feature = "glow"))]
+    if cli.snapshot.is_some() {
+        return Err("snapshots need the default wgpu renderer".into());
+    }
+    #[cfg(all(feature = "gui", not(feature = "glow")))]
+    if let Some(path) = cli.snapshot.clone() {
+        let persons = source.load(cli.quiet)?;
+        let size = cli.snapshot_size;
+        let services = Services {
+            #[cfg(feature = "scripting")]
+            script,
+            ..Services::default()
+        };
+        return snapshot::write(cli, persons, services, &path, size);
+    }
+
+    show(
+        cli,
+        source,
+        #[cfg(feature = "scripting")]
+        script,
+    )
+}
+
+/// Tells that this build can only run the commands.
+#[cfg(not(feature = "gui"))]
+fn show(
+    _cli: Cli,
+    _source: Box<dyn PersonSource>,
+    #[cfg(feature = "scripting")] _script: Option<Arc<Script>>,
+) -> Result<(), BirthdayError> {
+    Err("this build has no gui, run one of the commands".into())
+}
+
+/// Shows the persons of `source` in a window, `script` makes the banners if it is given.
+#[cfg(feature = "gui")]
+fn show(
+    cli: Cli,
+    source: Box<dyn PersonSource>,
+    #[cfg(feature = "scripting")] script: Option<Arc<Script>>,
+) -> Result<(), BirthdayError> {
+    panic::install_hook(cli.status_file.clone());
+    if let Some(path) = &cli.status_file {
+        panic::write_status(path, "running");
+    }
+
+    let instance = if cli.multiple_instances {
+        None
+    } else {
+        match instance::acquire()
+            .map_err(|error| format!("error checking for a running display: {error}"))?
+        {
+            Instance::Primary(listener) => Some(listener),
+            Instance::AlreadyRunning => {
+                if !cli.quiet {
+                    println!("birthday_display is already running");
+                }
+                return Ok(());
+            }
+        }
+    };
+    let control = cli
+        .control_port
+        .map(|port| {
+            ControlPort::bind(port).map_err(|error| {
+                BirthdayError::Network(format!("error listening on control port {port}: {error}"))
+            })
+        })
+        .transpose()?;
+    let watchdog = cli
+        .watchdog
+        .clone()
+        .map(|file| {
+            Watchdog::new(file).map_err(|error| format!("error starting watchdog: {error}"))
+        })
+        .transpose()?;
+    #[cfg(feature = "gpio")]
+    let flag = cli
+        .gpio_pin
+        .map(|pin| {
+            gpio::Flag::new(pin).map_err(|error| format!("error opening gpio pin {pin}: {error}"))
+        })
+        .transpose()?;
+
+    let monitors = if cli.monitor.is_some() || cli.all_monitors {
+        monitor::monitors()?
+    } else {
+        Vec::new()
+    };
+    if cli.all_monitors {
+        monitor::spawn_on_other_monitors(monitors.len())?;
+    }
+    // this process takes the first monitor if there is a window on every one
+    let position = match cli.monitor.or(cli.all_monitors.then_some(0)) {
+        Some(index) => {
+            let monitor = monitors.get(index).ok_or_else(|| {
+                BirthdayError::Gui(format!(
+                    "monitor {index} not found, {} available",
+                    monitors.len()
+                ))
+            })?;
+            if cli.verbose >= 1 {
+                println!(
+                    "opening on monitor {index} at {},{} with size {}x{}",
+                    monitor.x, monitor.y, monitor.width, monitor.height
+                );
+            }
+            Position::Specific(monitor.x, monitor.y)
+        }
+        None => Position::default(),
+    };
+
+    let file = cli.file.clone();
+    let font = match &cli.font {
+        Some(path) => Some(std::fs::read(path).map_err(|error| BirthdayError::io(path, error))?),
+        None => None,
+    };
+    let icon = match &cli.icon {
+        Some(path) => {
+            let bytes = std::fs::read(path).map_err(|error| BirthdayError::io(path, error))?;
+            Some(Icon::from_file_data(&bytes, None).map_err(|error| {
+                BirthdayError::Data(format!("error reading {}: {error}", path.display()))
+            })?)
+        }
+        None => None,
+    };
+    let services = Services {
+        instance,
+        control,
+        watchdog,
+        #[cfg(feature = "gpio")]
+        flag,
+        #[cfg(feature = "scripting")]
+        script,
+    };
+    // the font is used until the process exits
+    let font = font.map(|font| &*Box::leak(font.into_boxed_slice()));
+    #[cfg(all(feature = "layer-shell", not(feature = "glow")))]
+    if let Some(layer) = cli.layer {
+        let size = cli.layer_size;
+        let flags = (cli, Some(source), services);
+        return catch_panic(&file, || layer::run(flags, layer, size, font));
+    }
+    let mut settings = Settings::with_flags((cli, Some(source), services));
+    settings.window.position = position;
+    settings.window.icon = icon;
+    settings.default_font = font;
+
+    catch_panic(&file, || Ok(BirthdayDisplay::run(settings)?))
+}
+
+/// Runs the gui with `run` and shows the message of a panic in it to the user.
+#[cfg(feature = "gui")]
+fn catch_panic(
+    file: &PathBuf,
+    run: impl FnOnce() -> Result<(), BirthdayError>,
+) -> Result<(), BirthdayError> {
+    match std::panic::catch_unwind(AssertUnwindSafe(run)) {
+        Ok(result) => result,
+        Err(_) => {
+            let message = panic::last_panic().unwrap_or_default();
+            show_panic(&message, file);
+            Err(BirthdayError::Gui(format!("panicked: {message}")))
+        }
+    }
+}
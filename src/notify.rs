@@ -0,0 +1,569 @@
+use crate::audit;
+use crate::clock::{self, Clock};
+use crate::config::{Config, Shift, WorkdaysConfig};
+use crate::error::BirthdayError;
+use crate::home_assistant::HomeAssistant;
+use crate::index::BirthdayIndex;
+use crate::person::Person;
+use crate::runtime;
+use crate::signal::SignalNotifier;
+#[cfg(feature = "network")]
+use crate::sms::SmsNotifier;
+use crate::source::PersonSource;
+use crate::state::State;
+use crate::weekly::WeeklyDigest;
+use crate::Cli;
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+
+/// How often the daemon checks whether a notifier is due.
+const TICK: Duration = Duration::from_secs(30);
+
+/// How long the daemon waits before a failed notification is sent again.
+const RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// How often a failed notification is sent again if the config file does not say otherwise.
+const DEFAULT_RETRIES: u32 = 3;
+
+/// The most days off in a row whose birthdays are moved to a workday.
+const MAX_DAYS_OFF: usize = 31;
+
+/// What a notifier is told about on a day.
+pub struct Notification<'a> {
+    pub today: NaiveDate,
+    /// All persons, e.g. to find the next birthdays.
+    pub persons: &'a [Person],
+    /// The persons celebrating on the dates of [`Notifier::dates`] the notifier is
+    /// interested in with the date of their birthday.
+    pub birthdays: Vec<(NaiveDate, &'a Person)>,
+}
+
+impl Notification<'_> {
+    /// Returns the message announcing the birthdays.
+    pub fn summary(&self) -> String {
+        let mut lines = vec![String::from("Geburtstage heute:")];
+        lines.extend(self.birthdays.iter().map(|(date, person)| {
+            let banner = person.banner(*date, Some(self.today));
+            match &person.department {
+                Some(department) => format!("{banner} ({department})"),
+                None => banner,
+            }
+        }));
+        lines.join("\n")
+    }
+}
+
+/// A channel the daily summary of the birthdays is sent to.
+pub trait Notifier {
+    /// Describes the notifier in the output of a dry run and in errors.
+    fn name(&self) -> String;
+
+    /// Returns whether the notifier is interested in the birthday of `person`.
+    fn select(&self, _person: &Person) -> bool {
+        true
+    }
+
+    /// Returns whether the notifier also fires on days without birthdays.
+    fn on_empty_days(&self) -> bool {
+        false
+    }
+
+    /// Returns the dates whose birthdays are announced on `today`, which include the
+    /// following or preceding days off with `workdays`, none if it does not fire today.
+    fn dates(&self, today: NaiveDate, workdays: Option<&WorkdaysConfig>) -> Vec<NaiveDate> {
+        match workdays {
+            Some(workdays) => announced_on(workdays, today),
+            None => vec![today],
+        }
+    }
+
+    /// Returns what would be sent for `notification` on a dry run.
+    fn preview(&self, notification: &Notification<'_>) -> String {
+        notification.summary()
+    }
+
+    /// Sends `notification`.
+    async fn send(&self, notification: &Notification<'_>) -> Result<(), BirthdayError>;
+}
+
+/// Returns the dates whose birthdays are announced on `today` with `workdays`, none if
+/// `today` is a day off.
+fn announced_on(workdays: &WorkdaysConfig, today: NaiveDate) -> Vec<NaiveDate> {
+    let workday = |date: NaiveDate| {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+            && !workdays.holidays.iter().any(|holiday| holiday.is(date))
+    };
+    if !workday(today) {
+        return Vec::new();
+    }
+
+    let mut dates = vec![today];
+    for _ in 0..MAX_DAYS_OFF {
+        let last = dates[dates.len() - 1];
+        // the days off after a workday are announced on it in advance and the ones
+        // before it afterwards
+        let next = match workdays.shift {
+            Shift::Preceding => last.succ_opt(),
+            Shift::Following => last.pred_opt(),
+        };
+        match next {
+            Some(date) if !workday(date) => dates.push(date),
+            _ => break,
+        }
+    }
+    dates.sort_unstable();
+    dates
+}
+
+/// Tells `notifier` about the birthdays of `persons` on `today` or only prints it on `dry_run`.
+///
+/// Sent notifications are recorded in `audit_log`.
+async fn notify(
+    notifier: &impl Notifier,
+    persons: &[Person],
+    today: NaiveDate,
+    workdays: Option<&WorkdaysConfig>,
+    audit_log: Option<&Path>,
+    dry_run: bool,
+) -> Result<(), BirthdayError> {
+    let dates = notifier.dates(today, workdays);
+    if dates.is_empty() {
+        return Ok(());
+    }
+    let index = BirthdayIndex::new(persons);
+    let notification = Notification {
+        today,
+        persons,
+        birthdays: dates
+            .iter()
+            .flat_map(|&date| index.on(date).iter().map(move |&position| (date, position)))
+            .map(|(date, position)| (date, &persons[position]))
+            .filter(|(_, person)| notifier.select(person))
+            .collect(),
+    };
+    // nobody is bothered on days without birthdays
+    if notification.birthdays.is_empty() && !notifier.on_empty_days() {
+        return Ok(());
+    }
+
+    if dry_run {
+        println!(
+            "{}:\n{}\n",
+            notifier.name(),
+            notifier.preview(&notification)
+        );
+        Ok(())
+    } else {
+        notifier.send(&notification).await?;
+        // the sensor updates of days without birthdays announce nobody
+        if let (Some(path), false) = (audit_log, notification.birthdays.is_empty()) {
+            let persons = notification.birthdays.iter().map(|(_, person)| *person);
+            audit::record(path, "notified", Some(&notifier.name()), today, persons);
+        }
+        Ok(())
+    }
+}
+
+/// The notifiers that can be configured.
+enum Backend {
+    #[cfg(feature = "network")]
+    Sms(SmsNotifier),
+    Signal(SignalNotifier),
+    HomeAssistant(HomeAssistant),
+    Weekly(WeeklyDigest),
+}
+
+/// A notifier of the config file with the time it fires at.
+struct Registration {
+    backend: Backend,
+    /// The local time of day, right after the change of the day if missing.
+    at: Option<NaiveTime>,
+    retries: u32,
+    /// The last day the notifier fired or gave up.
+    fired: Option<NaiveDate>,
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl Registration {
+    fn new(backend: Backend, at: Option<NaiveTime>, retries: Option<u32>) -> Self {
+        Self {
+            backend,
+            at,
+            retries: retries.unwrap_or(DEFAULT_RETRIES),
+            fired: None,
+            failures: 0,
+            retry_at: None,
+        }
+    }
+
+    fn name(&self) -> String {
+        match &self.backend {
+            #[cfg(feature = "network")]
+            Backend::Sms(notifier) => notifier.name(),
+            Backend::Signal(notifier) => notifier.name(),
+            Backend::HomeAssistant(notifier) => notifier.name(),
+            Backend::Weekly(notifier) => notifier.name(),
+        }
+    }
+
+    async fn fire(
+        &self,
+        persons: &[Person],
+        today: NaiveDate,
+        workdays: Option<&WorkdaysConfig>,
+        audit_log: Option<&Path>,
+        dry_run: bool,
+    ) -> Result<(), BirthdayError> {
+        match &self.backend {
+            #[cfg(feature = "network")]
+            Backend::Sms(notifier) => {
+                notify(notifier, persons, today, workdays, audit_log, dry_run).await
+            }
+            Backend::Signal(notifier) => {
+                notify(notifier, persons, today, workdays, audit_log, dry_run).await
+            }
+            Backend::HomeAssistant(notifier) => {
+                notify(notifier, persons, today, workdays, audit_log, dry_run).await
+            }
+            Backend::Weekly(notifier) => {
+                notify(notifier, persons, today, workdays, audit_log, dry_run).await
+            }
+        }
+    }
+
+    /// Returns whether the notifier fires at or before `time` of the day.
+    fn time_reached(&self, time: NaiveTime) -> bool {
+        self.at.is_none_or(|at| at <= time)
+    }
+}
+
+/// Creates the notifiers of the config file.
+fn register(config: &Config) -> Result<Vec<Registration>, BirthdayError> {
+    let mut registrations = Vec::new();
+    #[cfg(not(feature = "network"))]
+    if config.sms.is_some() {
+        return Err("sms notifications need the network feature".into());
+    }
+    #[cfg(feature = "network")]
+    if let Some(sms) = &config.sms {
+        for notifier in SmsNotifier::new(sms)? {
+            registrations.push(Registration::new(
+                Backend::Sms(notifier),
+                sms.at,
+                sms.retries,
+            ));
+        }
+    }
+    if let Some(signal) = &config.signal {
+        registrations.push(Registration::new(
+            Backend::Signal(SignalNotifier::new(signal)),
+            signal.at,
+            signal.retries,
+        ));
+    }
+    if let Some(home_assistant) = &config.home_assistant {
+        registrations.push(Registration::new(
+            Backend::HomeAssistant(HomeAssistant::new(home_assistant)),
+            home_assistant.at,
+            home_assistant.retries,
+        ));
+    }
+    if let Some(weekly) = &config.weekly {
+        registrations.push(Registration::new(
+            Backend::Weekly(WeeklyDigest::new(weekly)?),
+            weekly.at,
+            weekly.retries,
+        ));
+    }
+
+    if registrations.is_empty() {
+        Err("no notifiers are configured in the config file".into())
+    } else {
+        Ok(registrations)
+    }
+}
+
+/// Returns the local date and time, the times of the notifiers are local so the
+/// birthdays of the local day are sent, also west of utc in the evening.
+fn local_day(clock: &dyn Clock) -> (NaiveDate, NaiveTime) {
+    let now = clock.local();
+    (now.date_naive(), now.time())
+}
+
+/// Fires every notifier once a day at its time until the process is stopped.
+///
+/// The days the notifiers fired are kept in `--state-file` so restarting the daemon does
+/// not send the messages twice. Without it, or for notifiers not found in it, those
+/// whose time already passed when the daemon starts wait for the next day.
+async fn dispatch(
+    mut registrations: Vec<Registration>,
+    mut persons: Vec<Person>,
+    source: &mut dyn PersonSource,
+    workdays: Option<&WorkdaysConfig>,
+    clock: &dyn Clock,
+    dry_run: bool,
+    cli: &Cli,
+) -> ! {
+    let (state_file, audit_log, quiet) = (
+        cli.state_file.as_deref(),
+        cli.audit_log.as_deref(),
+        cli.quiet,
+    );
+    let (today, time) = local_day(clock);
+    let state = state_file.map(|path| State::load(path, quiet));
+    for registration in &mut registrations {
+        let notified = state
+            .as_ref()
+            .and_then(|state| state.notified.get(&registration.name()));
+        registration.fired = match notified {
+            Some(&day) => Some(day),
+            None => registration.time_reached(time).then_some(today),
+        };
+    }
+
+    loop {
+        tokio::time::sleep(TICK).await;
+
+        if source.changed() {
+            match source.load(quiet) {
+                Ok(loaded) => persons = loaded,
+                Err(error) => {
+                    if !quiet {
+                        eprintln!("error reloading {}: {error}", source.name());
+                    }
+                }
+            }
+        }
+
+        let (today, time) = local_day(clock);
+        for registration in &mut registrations {
+            if registration.fired == Some(today)
+                || !registration.time_reached(time)
+                || registration
+                    .retry_at
+                    .is_some_and(|retry_at| Instant::now() < retry_at)
+            {
+                continue;
+            }
+
+            match registration
+                .fire(&persons, today, workdays, audit_log, dry_run)
+                .await
+            {
+                Ok(()) => {
+                    registration.fired = Some(today);
+                    registration.failures = 0;
+                    registration.retry_at = None;
+                }
+                Err(error) if registration.failures < registration.retries => {
+                    registration.failures += 1;
+                    registration.retry_at = Some(Instant::now() + RETRY_DELAY);
+                    if !quiet {
+                        eprintln!(
+                            "error notifying {}, retrying in {} minutes: {error}",
+                            registration.name(),
+                            RETRY_DELAY.as_secs() / 60
+                        );
+                    }
+                }
+                Err(error) => {
+                    registration.fired = Some(today);
+                    registration.failures = 0;
+                    registration.retry_at = None;
+                    if !quiet {
+                        eprintln!(
+                            "error notifying {}, giving up: {error}",
+                            registration.name()
+                        );
+                    }
+                }
+            }
+
+            // a dry run must not keep the real notifications from being sent
+            if let (Some(path), false) = (state_file, dry_run) {
+                if registration.fired == Some(today) {
+                    let name = registration.name();
+                    State::update(path, quiet, |state| {
+                        state.notified.insert(name, today);
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Notifies every configured channel about the birthdays of today once or, as `daemon`,
+/// every day at the time of the notifier.
+pub fn run(
+    persons: Vec<Person>,
+    source: &mut dyn PersonSource,
+    config: &Config,
+    dry_run: bool,
+    daemon: bool,
+    cli: &Cli,
+) -> Result<(), BirthdayError> {
+    let registrations = register(config)?;
+
+    let runtime = runtime::current_thread()?;
+    let workdays = config.workdays.as_ref();
+    let clock = clock::for_date(cli.date);
+
+    runtime.block_on(async {
+        if daemon {
+            let clock = clock.as_ref();
+            dispatch(
+                registrations,
+                persons,
+                source,
+                workdays,
+                clock,
+                dry_run,
+                cli,
+            )
+            .await
+        }
+
+        let (today, _) = local_day(clock.as_ref());
+        let audit_log = cli.audit_log.as_deref();
+        // a failing notifier must not keep the others from sending their messages, the
+        // first failure is returned and the later ones are printed
+        let mut failure = None;
+        for registration in &registrations {
+            let result = registration
+                .fire(&persons, today, workdays, audit_log, dry_run)
+                .await;
+            match (result, &failure) {
+                (Ok(()), _) => {}
+                (Err(error), None) => failure = Some(error),
+                (Err(error), Some(_)) => {
+                    eprintln!("error notifying {}: {error}", registration.name());
+                }
+            }
+        }
+        failure.map_or(Ok(()), Err)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Holiday;
+    use crate::csv::CsvFile;
+    use crate::fixtures::{date, person};
+
+    use std::io::Read;
+    use std::net::{Ipv4Addr, TcpListener};
+    use std::thread;
+
+    #[test]
+    fn a_failing_notifier_does_not_stop_the_others() {
+        // a broker that accepts the connection and returns what it received
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let broker = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut connect = [0; 2];
+            stream.read_exact(&mut connect).unwrap();
+            let mut rest = vec![0; usize::from(connect[1])];
+            stream.read_exact(&mut rest).unwrap();
+            std::io::Write::write_all(&mut stream, &[0x20, 2, 0, 0]).unwrap();
+            let mut received = Vec::new();
+            let _ = stream.read_to_end(&mut received);
+            received
+        });
+        let config: Config = toml::from_str(&format!(
+            "[signal]\n\
+             account = \"+4915112345678\"\n\
+             group = \"Z3JvdXA=\"\n\
+             command = \"/nonexistent/signal-cli\"\n\
+             [home_assistant]\n\
+             host = \"127.0.0.1\"\n\
+             port = {port}\n"
+        ))
+        .unwrap();
+        let cli = Cli {
+            date: Some(date(2024, 3, 10)),
+            ..Cli::default()
+        };
+        let persons = vec![person("Anna", date(1990, 3, 10))];
+        let mut source = CsvFile::new(Path::new("/nonexistent.csv"));
+
+        let result = run(persons, &mut source, &config, false, false, &cli);
+        assert!(matches!(result, Err(BirthdayError::Io { .. })));
+        // an empty connection fails the broker if home assistant was never notified
+        drop(std::net::TcpStream::connect((Ipv4Addr::LOCALHOST, port)));
+        let received = broker.join().unwrap();
+        assert!(String::from_utf8_lossy(&received).contains("Anna"));
+    }
+
+    fn workdays(shift: Shift, holidays: &[Holiday]) -> WorkdaysConfig {
+        WorkdaysConfig {
+            shift,
+            holidays: holidays.to_vec(),
+        }
+    }
+
+    #[test]
+    fn announces_the_weekend_on_friday() {
+        let days_off = workdays(Shift::Preceding, &[]);
+        assert_eq!(
+            announced_on(&days_off, date(2026, 10, 16)),
+            [date(2026, 10, 16), date(2026, 10, 17), date(2026, 10, 18)]
+        );
+        assert_eq!(
+            announced_on(&days_off, date(2026, 10, 19)),
+            [date(2026, 10, 19)]
+        );
+    }
+
+    #[test]
+    fn announces_the_weekend_on_monday_afterwards() {
+        let days_off = workdays(Shift::Following, &[]);
+        assert_eq!(
+            announced_on(&days_off, date(2026, 10, 19)),
+            [date(2026, 10, 17), date(2026, 10, 18), date(2026, 10, 19)]
+        );
+        assert_eq!(
+            announced_on(&days_off, date(2026, 10, 16)),
+            [date(2026, 10, 16)]
+        );
+    }
+
+    #[test]
+    fn announces_a_holiday_next_to_the_weekend_with_it() {
+        // the 26th of october 2026 is a monday
+        let days_off = workdays(Shift::Preceding, &[Holiday::Yearly(26, 10)]);
+        assert_eq!(
+            announced_on(&days_off, date(2026, 10, 23)),
+            [
+                date(2026, 10, 23),
+                date(2026, 10, 24),
+                date(2026, 10, 25),
+                date(2026, 10, 26)
+            ]
+        );
+        let days_off = workdays(Shift::Following, &[Holiday::Once(date(2026, 10, 23))]);
+        assert_eq!(
+            announced_on(&days_off, date(2026, 10, 26)),
+            [
+                date(2026, 10, 23),
+                date(2026, 10, 24),
+                date(2026, 10, 25),
+                date(2026, 10, 26)
+            ]
+        );
+    }
+
+    #[test]
+    fn announces_nothing_on_a_day_off() {
+        let days_off = workdays(Shift::Preceding, &[Holiday::Once(date(2026, 10, 14))]);
+        assert!(announced_on(&days_off, date(2026, 10, 14)).is_empty());
+        assert!(announced_on(&days_off, date(2026, 10, 17)).is_empty());
+        let days_off = workdays(Shift::Following, &[]);
+        assert!(announced_on(&days_off, date(2026, 10, 18)).is_empty());
+    }
+}
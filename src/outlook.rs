@@ -0,0 +1,189 @@
+use crate::config::OutlookConfig;
+use crate::error::BirthdayError;
+use crate::percent;
+use crate::sync::{BirthdayEvent, Calendar, Change, RemoteEvent};
+
+use chrono::{Datelike, Days, NaiveDate};
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Value};
+
+const API: &str = "https://graph.microsoft.com/v1.0";
+
+/// Extended property storing the key of the person an event belongs to.
+const KEY_PROPERTY: &str =
+    "String {8f3a3c44-6d2b-4b8e-9a57-2f0d5c1e7b61} Name birthday_display_key";
+
+/// An Outlook calendar accessed through Microsoft Graph with an app registration.
+pub struct OutlookCalendar {
+    client: Client,
+    events_url: String,
+    access_token: String,
+}
+
+impl OutlookCalendar {
+    /// Requests an access token for the app registration of `config`.
+    pub async fn connect(config: &OutlookConfig) -> Result<Self, BirthdayError> {
+        let client = Client::builder().build()?;
+
+        let response = client
+            .post(format!(
+                "https://login.microsoftonline.com/{}/oauth2/v2.0/token",
+                percent::encode(&config.tenant_id)
+            ))
+            .form(&[
+                ("client_id", config.client_id.as_str()),
+                ("client_secret", config.client_secret.as_str()),
+                ("scope", "https://graph.microsoft.com/.default"),
+                ("grant_type", "client_credentials"),
+            ])
+            .send()
+            .await?;
+        let status = response.status();
+        let token: Value = response.json().await?;
+        let access_token = match token["access_token"].as_str() {
+            Some(access_token) if status.is_success() => access_token.to_string(),
+            _ => {
+                let description = token["error_description"].as_str().unwrap_or_default();
+                return Err(BirthdayError::Network(format!(
+                    "authentication failed with {status}: {description}"
+                )));
+            }
+        };
+
+        let user = percent::encode(&config.user);
+        let events_url = match &config.calendar {
+            Some(calendar) => format!(
+                "{API}/users/{user}/calendars/{}/events",
+                percent::encode(calendar)
+            ),
+            None => format!("{API}/users/{user}/calendar/events"),
+        };
+
+        Ok(Self {
+            client,
+            events_url,
+            access_token,
+        })
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Value, BirthdayError> {
+        let response = request.bearer_auth(&self.access_token).send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(BirthdayError::Network(format!(
+                "microsoft graph returned {status}: {text}"
+            )));
+        }
+
+        // deletions have an empty body
+        if text.is_empty() {
+            Ok(Value::Null)
+        } else {
+            serde_json::from_str(&text).map_err(|error| {
+                BirthdayError::Network(format!("microsoft graph returned invalid json: {error}"))
+            })
+        }
+    }
+}
+
+impl Calendar for OutlookCalendar {
+    async fn events(&self) -> Result<Vec<RemoteEvent>, BirthdayError> {
+        let mut events = Vec::new();
+        let mut request = self.client.get(&self.events_url).query(&[
+            (
+                "$filter",
+                format!("singleValueExtendedProperties/Any(ep: ep/id eq '{KEY_PROPERTY}' and ep/value ne null)"),
+            ),
+            (
+                "$expand",
+                format!("singleValueExtendedProperties($filter=id eq '{KEY_PROPERTY}')"),
+            ),
+            ("$top", String::from("100")),
+        ]);
+        loop {
+            let page = self.send(request).await?;
+
+            let items = page["value"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+            events.extend(items.iter().filter_map(parse_event));
+
+            // the next link already contains the query
+            match page["@odata.nextLink"].as_str() {
+                Some(next) => request = self.client.get(next),
+                None => return Ok(events),
+            }
+        }
+    }
+
+    async fn apply(&self, change: &Change) -> Result<(), BirthdayError> {
+        let event_url = |id: &str| format!("{}/{}", self.events_url, percent::encode(id));
+        let request = match change {
+            Change::Create(event) => self.client.post(&self.events_url).json(&body(event)),
+            Change::Update(id, event) => self.client.patch(event_url(id)).json(&body(event)),
+            Change::Delete(remote) => self.client.delete(event_url(&remote.id)),
+        };
+        self.send(request).await.map(drop)
+    }
+}
+
+/// Generates the json representation of a yearly all-day event.
+fn body(event: &BirthdayEvent) -> Value {
+    let end = event.date + Days::new(1);
+    json!({
+        "subject": event.summary,
+        "isAllDay": true,
+        "showAs": "free",
+        "start": { "dateTime": event.date.format("%Y-%m-%dT00:00:00").to_string(), "timeZone": "UTC" },
+        "end": { "dateTime": end.format("%Y-%m-%dT00:00:00").to_string(), "timeZone": "UTC" },
+        "recurrence": {
+            "pattern": {
+                "type": "absoluteYearly",
+                "interval": 1,
+                "month": event.date.month(),
+                "dayOfMonth": event.date.day(),
+            },
+            "range": { "type": "noEnd", "startDate": event.date.format("%Y-%m-%d").to_string() },
+        },
+        "singleValueExtendedProperties": [{ "id": KEY_PROPERTY, "value": event.key }],
+    })
+}
+
+/// Reads an event created by [`body`].
+fn parse_event(item: &Value) -> Option<RemoteEvent> {
+    let start = item["start"]["dateTime"].as_str()?;
+    let date = NaiveDate::parse_from_str(start.get(..10)?, "%Y-%m-%d").ok()?;
+    let key = item["singleValueExtendedProperties"]
+        .as_array()?
+        .iter()
+        .find_map(|property| property["value"].as_str())?;
+
+    Some(RemoteEvent {
+        id: item["id"].as_str()?.to_string(),
+        event: BirthdayEvent {
+            key: key.to_string(),
+            summary: item["subject"].as_str().unwrap_or_default().to_string(),
+            date,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::date;
+
+    #[test]
+    fn reads_the_events_it_creates() {
+        let event = BirthdayEvent {
+            key: String::from("Muster,Anna,10.03.1990"),
+            summary: String::from("Geburtstag Anna Muster"),
+            date: date(2024, 3, 10),
+        };
+        let mut item = body(&event);
+        item["id"] = "event-1".into();
+
+        let remote = parse_event(&item).unwrap();
+        assert_eq!(remote.id, "event-1");
+        assert_eq!(remote.event, event);
+    }
+}
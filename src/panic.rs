@@ -0,0 +1,103 @@
+use crate::runtime;
+
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::Local;
+
+use iced::widget::{column, container, text};
+use iced::{Alignment, Application, Color, Command, Element, Length, Theme};
+
+/// The message of the last panic of this process.
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = match info.payload().downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match info.payload().downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => String::from("unknown error"),
+        },
+    };
+
+    match info.location() {
+        Some(location) => format!("{payload} ({location})"),
+        None => payload,
+    }
+}
+
+/// Writes `status` to `path` and reports failures on stderr.
+pub fn write_status(path: &PathBuf, status: &str) {
+    let line = format!("{} {status}\n", Local::now().to_rfc3339());
+    if let Err(error) = fs::write(path, line) {
+        eprintln!("error writing status file: {error}");
+    }
+}
+
+/// Installs a panic hook that remembers the message for [`last_panic`] and writes
+/// it to `status_file` before running the default hook.
+pub fn install_hook(status_file: Option<PathBuf>) {
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let message = panic_message(info);
+        if let Some(path) = &status_file {
+            write_status(path, &format!("panicked: {message}"));
+        }
+        if let Ok(mut last_panic) = LAST_PANIC.lock() {
+            last_panic.replace(message);
+        }
+
+        default_hook(info);
+    }));
+}
+
+/// Returns the message of the last panic.
+pub fn last_panic() -> Option<String> {
+    LAST_PANIC.lock().ok()?.clone()
+}
+
+/// Minimal application showing why the display stopped working.
+pub struct PanicDisplay {
+    message: String,
+}
+
+impl Application for PanicDisplay {
+    type Executor = runtime::Executor;
+    type Message = ();
+    type Theme = Theme;
+    type Flags = String;
+
+    fn new(message: String) -> (Self, Command<()>) {
+        (Self { message }, iced::window::maximize(true))
+    }
+
+    fn title(&self) -> String {
+        String::from("Birthday Display")
+    }
+
+    fn update(&mut self, _message: ()) -> Command<()> {
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'_, ()> {
+        container(
+            column![
+                text("Die Geburtstagsanzeige ist abgestürzt.").size(30),
+                text(&self.message)
+                    .size(20)
+                    .style(Color::from_rgb(0.7, 0.0, 0.0)),
+            ]
+            .spacing(20)
+            .align_items(Alignment::Center),
+        )
+        .padding(20)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+    }
+}
@@ -0,0 +1,194 @@
+use std::io::Write;
+
+use iced_native::Color;
+
+/// The standard fonts every pdf viewer provides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// A single page pdf document drawn with coordinates in points from the bottom left.
+pub struct Page {
+    width: f32,
+    height: f32,
+    content: Vec<u8>,
+}
+
+/// Converts `text` to the bytes of the WinAnsi encoding of the standard fonts.
+fn encode(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for character in text.chars() {
+        match character {
+            '(' | ')' | '\\' => bytes.extend([b'\\', character as u8]),
+            // WinAnsi matches Latin-1 for the letters used in names
+            ' '..='~' | '\u{a0}'..='\u{ff}' => bytes.push(character as u32 as u8),
+            '€' => bytes.push(0x80),
+            '…' => bytes.push(0x85),
+            '–' => bytes.push(0x96),
+            _ => bytes.push(b'?'),
+        }
+    }
+    bytes
+}
+
+/// Estimates the width of `text` in Helvetica of the given `size`.
+pub fn text_width(text: &str, size: f32, font: Font) -> f32 {
+    let em: f32 = text
+        .chars()
+        .map(|character| match character {
+            'i' | 'j' | 'l' | 'I' | '.' | ',' | ':' | ';' | '\'' | '|' | '!' => 0.28,
+            'f' | 't' | 'r' | ' ' | '(' | ')' | '-' => 0.33,
+            'm' | 'w' | 'M' | 'W' => 0.83,
+            'A'..='Z' | 'Ä' | 'Ö' | 'Ü' => 0.67,
+            _ => 0.56,
+        })
+        .sum();
+    let bold_factor = if font == Font::Bold { 1.05 } else { 1.0 };
+
+    em * size * bold_factor
+}
+
+/// Shortens `text` with an ellipsis until it fits into `width`.
+pub fn fit_text(text: &str, size: f32, font: Font, width: f32) -> String {
+    if text_width(text, size, font) <= width {
+        return text.to_string();
+    }
+
+    let mut shortened: String = text.to_string();
+    while !shortened.is_empty() && text_width(&format!("{shortened}…"), size, font) > width {
+        shortened.pop();
+    }
+    format!("{}…", shortened.trim_end())
+}
+
+impl Page {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            content: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Draws `text` with its baseline starting at `x`, `y`.
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: Font, color: Color, text: &str) {
+        let _ = write!(
+            self.content,
+            "BT {} {} {} rg /{} {size:.1} Tf {x:.2} {y:.2} Td (",
+            color.r,
+            color.g,
+            color.b,
+            font.resource()
+        );
+        self.content.extend(encode(text));
+        self.content.extend_from_slice(b") Tj ET\n");
+    }
+
+    /// Draws `text` horizontally centered on `x`.
+    pub fn centered_text(
+        &mut self,
+        x: f32,
+        y: f32,
+        size: f32,
+        font: Font,
+        color: Color,
+        text: &str,
+    ) {
+        let width = text_width(text, size, font);
+        self.text(x - width / 2.0, y, size, font, color, text);
+    }
+
+    /// Draws a rectangle with its bottom left corner at `x`, `y`.
+    pub fn rectangle(
+        &mut self,
+        (x, y, width, height): (f32, f32, f32, f32),
+        fill: Option<Color>,
+        stroke: Option<Color>,
+    ) {
+        if let Some(color) = fill {
+            let _ = write!(self.content, "{} {} {} rg ", color.r, color.g, color.b);
+        }
+        if let Some(color) = stroke {
+            let _ = write!(
+                self.content,
+                "{} {} {} RG 0.5 w ",
+                color.r, color.g, color.b
+            );
+        }
+        let operator = match (fill, stroke) {
+            (Some(_), Some(_)) => "B",
+            (Some(_), None) => "f",
+            (None, Some(_)) => "S",
+            (None, None) => "n",
+        };
+        let _ = writeln!(
+            self.content,
+            "{x:.2} {y:.2} {width:.2} {height:.2} re {operator}"
+        );
+    }
+
+    /// Returns the complete pdf file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Contents 4 0 R \
+                 /Resources << /Font << /F1 5 0 R /F2 6 0 R >> >> >>",
+                self.width, self.height
+            )
+            .into_bytes(),
+            [
+                format!("<< /Length {} >>\nstream\n", self.content.len()).as_bytes(),
+                &self.content,
+                b"endstream",
+            ]
+            .concat(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        ];
+
+        let mut file = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(file.len());
+            let _ = writeln!(file, "{} 0 obj", index + 1);
+            file.extend(object);
+            file.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref = file.len();
+        let _ = write!(file, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(file, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            file,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        );
+
+        file
+    }
+}
@@ -1,60 +1,1131 @@
-use crate::csv::custom_date_format;
-use crate::Message;
+use crate::collation;
+use crate::csv::{custom_date_format, SourceRecord};
+use crate::locale::{Age, Locale};
+use crate::style;
+#[cfg(feature = "gui")]
+use crate::{Message, UiMessage};
 
-use chrono::{NaiveDate, Utc};
-use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
-use iced::widget::image::Handle;
-use iced::widget::{column, container, text, Column, Image};
-use iced::{Alignment, Color, Element, Length};
+use chrono::{Datelike, NaiveDate};
+use clap::ValueEnum;
+use serde::{Deserialize, Deserializer};
 
-/// Represents a person with a birthday an a custom image to be displayed.
-#[derive(Debug, Deserialize)]
-pub struct Person {
+#[cfg(feature = "gui")]
+use iced::theme;
+#[cfg(feature = "gui")]
+use iced::widget::{button, column, container, row, text, Column, Image, Row};
+#[cfg(feature = "gui")]
+use iced::{Alignment, ContentFit, Element, Length, Size};
+use iced_native::alignment::Horizontal;
+use iced_native::image::Handle;
+use iced_native::Color;
+
+/// Children younger than this many months are counted in months by default.
+pub const MONTHS_UNTIL: u32 = 24;
+
+/// Whether the persons that opted out are left out of the display and the notifications.
+static RESPECT_OPTOUT: AtomicBool = AtomicBool::new(true);
+
+/// Announces the birthdays of persons that opted out as well unless `enabled`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_respect_optout(enabled: bool) {
+    RESPECT_OPTOUT.store(enabled, Ordering::Relaxed);
+}
+
+/// The kinds of image urls that can be loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImageScheme {
+    Http,
+    Https,
+    /// a photo on the disk or a network share
+    File,
+    /// a photo embedded in the file
+    Data,
+}
+
+impl ImageScheme {
+    /// Returns the scheme `url` starts with if it is one of these.
+    pub fn of(url: &str) -> Option<Self> {
+        let (scheme, _) = url.split_once(':')?;
+        <Self as ValueEnum>::from_str(scheme, true).ok()
+    }
+
+    /// Returns whether the image is downloaded instead of read directly.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "network", feature = "gui"))]
+    pub fn is_remote(self) -> bool {
+        matches!(self, Self::Http | Self::Https)
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// The image schemes that are accepted as one bit per [`ImageScheme`], all by default.
+static IMAGE_SCHEMES: AtomicU8 = AtomicU8::new(u8::MAX);
+
+/// Rejects the persons with an image url of another scheme than `schemes`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_image_schemes(schemes: &[ImageScheme]) {
+    let bits = schemes.iter().fold(0, |bits, scheme| bits | scheme.bit());
+    IMAGE_SCHEMES.store(bits, Ordering::Relaxed);
+}
+
+/// Returns the image schemes that are accepted.
+fn image_schemes() -> impl Iterator<Item = ImageScheme> {
+    let bits = IMAGE_SCHEMES.load(Ordering::Relaxed);
+    ImageScheme::value_variants()
+        .iter()
+        .copied()
+        .filter(move |scheme| bits & scheme.bit() != 0)
+}
+
+/// What a card celebrates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Occasion {
+    Birthday,
+    /// Six months after the birthday.
+    HalfBirthday,
+    /// The number of days the person is alive.
+    Milestone(u64),
+}
+
+/// How the cards of a display are laid out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Layout {
+    /// The banners above the photo, the cards next to each other.
+    #[default]
+    PhotoTop,
+    /// The photo left of the banners.
+    PhotoLeft,
+    /// Only the banners, e.g. for files without photos.
+    TextOnly,
+    /// A line per person with a small photo, the cards below each other.
+    CompactList,
+}
+
+/// How a photo is fitted into the room of its card.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PhotoFit {
+    /// The whole photo as large as it fits.
+    #[default]
+    Contain,
+    /// Cropped to fill the room of the photo.
+    Cover,
+    /// The same height for every photo, narrow ones with room beside them.
+    FixedHeight,
+}
+
+impl FromStr for PhotoFit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "contain" => Ok(Self::Contain),
+            "cover" => Ok(Self::Cover),
+            "fixed-height" => Ok(Self::FixedHeight),
+            _ => Err(format!(
+                "unknown photo fit \"{s}\", expected one of contain, cover, fixed-height"
+            )),
+        }
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "photo-top" => Ok(Self::PhotoTop),
+            "photo-left" => Ok(Self::PhotoLeft),
+            "text-only" => Ok(Self::TextOnly),
+            "compact-list" => Ok(Self::CompactList),
+            _ => Err(format!(
+                "unknown layout \"{s}\", expected one of photo-top, photo-left, text-only, compact-list"
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Layout {
+    /// Arranges the `cards` of a day.
+    pub fn arrange(self, cards: Vec<Element<'_, Message>>) -> Element<'_, Message> {
+        match self {
+            Self::CompactList => Column::with_children(cards).spacing(10).into(),
+            _ => Row::with_children(cards).spacing(15).into(),
+        }
+    }
+
+    /// Returns about how much room `count` cards take up at a scale factor of 1.
+    pub fn size(self, count: usize) -> Size {
+        let count = count.max(1) as f32;
+        match self {
+            Self::PhotoTop => Size::new(count * 315.0, 550.0),
+            Self::PhotoLeft => Size::new(count * 635.0, 350.0),
+            Self::TextOnly => Size::new(count * 415.0, 150.0),
+            Self::CompactList => Size::new(500.0, count * 110.0),
+        }
+    }
+}
+
+/// The largest height of the photo of the card of the next birthday.
+#[cfg(feature = "gui")]
+const HERO_PHOTO_HEIGHT: f32 = 500.0;
+
+/// Settings for generating the card of a person.
+#[derive(Debug, Clone, Copy)]
+pub struct CardSettings {
+    /// Hide errors.
+    pub silent: bool,
+    /// Text color of errors.
+    pub error_color: Color,
+    /// The language of the banner.
+    pub locale: Locale,
+    /// The language of a second banner below the first one.
+    pub second_locale: Option<Locale>,
+    /// Children younger than this many months are counted in months.
+    pub months_until: u32,
+    /// Counts up while the photos of the persons are cycled through.
+    pub slide: usize,
+    pub layout: Layout,
+    pub photo_fit: PhotoFit,
+    /// The largest part of the height of a card that its photo may take up.
+    pub photo_height: Option<f32>,
+}
+
+impl CardSettings {
+    /// Returns the languages of the banners from top to bottom.
+    pub fn locales(&self) -> impl Iterator<Item = Locale> {
+        std::iter::once(self.locale).chain(self.second_locale)
+    }
+}
+
+/// How a person is addressed, told by the single character of the gender column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gender {
+    Male,
+    Female,
+    /// Any other character, e.g. `d`, leaves out the title.
+    Other,
+}
+
+impl From<char> for Gender {
+    fn from(gender: char) -> Self {
+        match gender {
+            'm' | 'M' => Self::Male,
+            'f' | 'F' | 'w' | 'W' => Self::Female,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl From<Gender> for char {
+    /// The letter written to the files, the one the editor asks for.
+    fn from(gender: Gender) -> Self {
+        match gender {
+            Gender::Male => 'm',
+            Gender::Female => 'w',
+            Gender::Other => 'd',
+        }
+    }
+}
+
+/// The highest age a birthday is believed to be right for.
+#[cfg(not(target_arch = "wasm32"))]
+pub const MAX_AGE: u32 = 120;
+
+/// Why the fields of a person were rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidPerson {
+    EmptyName,
+    Color(String),
+    ImageUrl(String),
+    Url(String),
+}
+
+impl std::fmt::Display for InvalidPerson {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyName => write!(f, "the first and last name must not be empty"),
+            Self::Color(color) => write!(f, "invalid color \"{color}\""),
+            Self::ImageUrl(url) => {
+                let schemes: Vec<String> = image_schemes()
+                    .filter_map(|scheme| scheme.to_possible_value())
+                    .map(|scheme| format!("{}:", scheme.get_name()))
+                    .collect();
+                write!(
+                    f,
+                    "unsupported image url \"{url}\", expected one starting with {}",
+                    schemes.join(", ")
+                )
+            }
+            Self::Url(url) => write!(
+                f,
+                "invalid url \"{url}\", expected one like https://example.com/photo.jpg"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvalidPerson {}
+
+/// Returns whether `url` starts with a scheme like `https:` and contains no spaces.
+fn plausible_url(url: &str) -> bool {
+    let Some((scheme, _)) = url.split_once(':') else {
+        return false;
+    };
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Collects the fields of a [`Person`] and checks them, so the csv and json files, the
+/// editor and the scripts create persons the same way.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PersonBuilder {
     last_name: String,
     first_name: String,
     #[serde(deserialize_with = "custom_date_format::deserialize")]
-    pub(crate) birthday: NaiveDate,
+    birthday: NaiveDate,
     gender: char,
+    image_url: Option<String>,
+    contact_url: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_color")]
+    color: Option<String>,
+    notes: Option<String>,
+    department: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    opt_out: bool,
+}
+
+impl PersonBuilder {
+    /// Several urls separated by spaces are shown one after another.
+    pub fn image_url(mut self, url: Option<String>) -> Self {
+        self.image_url = url;
+        self
+    }
+
+    pub fn contact_url(mut self, url: Option<String>) -> Self {
+        self.contact_url = url;
+        self
+    }
+
+    pub fn color(mut self, color: Option<String>) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn notes(mut self, notes: Option<String>) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    pub fn department(mut self, department: Option<String>) -> Self {
+        self.department = department;
+        self
+    }
+
+    pub fn opt_out(mut self, opt_out: bool) -> Self {
+        self.opt_out = opt_out;
+        self
+    }
+
+    /// Creates the person without loaded image data if the fields are valid.
+    pub fn build(self) -> Result<Person, InvalidPerson> {
+        let person = Person {
+            last_name: collation::normalize(&self.last_name),
+            first_name: collation::normalize(&self.first_name),
+            birthday: self.birthday,
+            gender: self.gender,
+            image_url: self.image_url,
+            contact_url: self.contact_url,
+            color: self.color,
+            notes: self.notes,
+            department: self.department,
+            opt_out: self.opt_out,
+            image_data: HashMap::new(),
+            qr_code: None,
+            source: SourceRecord::default(),
+        };
+        person.check()?;
+        Ok(person)
+    }
+}
+
+impl TryFrom<PersonBuilder> for Person {
+    type Error = InvalidPerson;
+
+    fn try_from(builder: PersonBuilder) -> Result<Self, Self::Error> {
+        builder.build()
+    }
+}
+
+/// Represents a person with a birthday an a custom image to be displayed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "PersonBuilder")]
+pub struct Person {
+    pub(crate) last_name: String,
+    pub(crate) first_name: String,
+    pub(crate) birthday: NaiveDate,
+    pub(crate) gender: char,
     pub(crate) image_url: Option<String>,
-    #[serde(skip)]
-    pub(crate) image_data: Option<Result<Handle, String>>,
+    /// Link to open when the card is clicked, e.g. an intranet profile or a chat.
+    pub(crate) contact_url: Option<String>,
+    /// The accent color of the card as written in the file, see [`style::parse_color`].
+    pub(crate) color: Option<String>,
+    /// Free text shown below the banner, e.g. gift hints.
+    pub(crate) notes: Option<String>,
+    /// The department or team shown as a small label on the card.
+    pub(crate) department: Option<String>,
+    /// Whether the person does not want their birthday to be announced.
+    pub(crate) opt_out: bool,
+    /// The loaded images by their url.
+    pub(crate) image_data: HashMap<String, Result<Handle, String>>,
+    /// The qr code with the greeting link once it was generated.
+    pub(crate) qr_code: Option<Result<Handle, String>>,
+    pub(crate) source: SourceRecord,
 }
 
 impl Person {
-    /// Generates a visual representation of the person for iced.
-    pub fn view(&self, silent: bool) -> Element<Message> {
-        let pronoun = match self.gender {
-            'm' | 'M' => "Herr ",
-            'f' | 'F' | 'w' | 'W' => "Frau ",
-            _ => "",
+    /// Starts building a person with the mandatory fields, see [`PersonBuilder`].
+    pub fn builder(
+        last_name: impl Into<String>,
+        first_name: impl Into<String>,
+        birthday: NaiveDate,
+        gender: Gender,
+    ) -> PersonBuilder {
+        PersonBuilder {
+            last_name: last_name.into(),
+            first_name: first_name.into(),
+            birthday,
+            gender: gender.into(),
+            image_url: None,
+            contact_url: None,
+            color: None,
+            notes: None,
+            department: None,
+            opt_out: false,
+        }
+    }
+
+    /// Checks the fields like [`PersonBuilder::build`], e.g. after a script changed them.
+    pub fn check(&self) -> Result<(), InvalidPerson> {
+        if self.last_name.trim().is_empty() || self.first_name.trim().is_empty() {
+            return Err(InvalidPerson::EmptyName);
+        }
+        if let Some(color) = &self.color {
+            style::parse_color(color).map_err(|_| InvalidPerson::Color(color.clone()))?;
+        }
+        for url in self.image_urls() {
+            let allowed = ImageScheme::of(url)
+                .is_some_and(|scheme| image_schemes().any(|allowed| allowed == scheme));
+            if !allowed || !plausible_url(url) {
+                return Err(InvalidPerson::ImageUrl(url.to_string()));
+            }
+        }
+        match &self.contact_url {
+            Some(url) if !plausible_url(url) => Err(InvalidPerson::Url(url.clone())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns how the person is addressed.
+    pub fn gender(&self) -> Gender {
+        Gender::from(self.gender)
+    }
+
+    /// Returns whether the birthday of the person is shown and notified about, which is
+    /// not the case if they opted out and `--respect-optout` is on.
+    pub fn announced(&self) -> bool {
+        !self.opt_out || !RESPECT_OPTOUT.load(Ordering::Relaxed)
+    }
+
+    /// Returns `template` with "{first_name}", "{last_name}", "{name}" and "{age}" replaced.
+    pub fn fill_template(&self, template: &str, age: &str) -> String {
+        template
+            .replace("{first_name}", &self.first_name)
+            .replace("{last_name}", &self.last_name)
+            .replace("{name}", &self.name())
+            .replace("{age}", age)
+    }
+
+    /// Returns why the birthday can not be right on `today`, e.g. if 2103 was typed
+    /// instead of 2013.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn implausible_birthday(&self, today: NaiveDate) -> Option<String> {
+        match today.years_since(self.birthday) {
+            None => Some(String::from("lies in the future")),
+            Some(age) if age > MAX_AGE => Some(format!("is more than {MAX_AGE} years ago")),
+            Some(_) => None,
+        }
+    }
+
+    /// Returns the first and the last name separated by a space.
+    pub fn name(&self) -> String {
+        format!("{} {}", self.first_name, self.last_name)
+    }
+
+    /// Returns the date of the first birthday on or after `today`.
+    ///
+    /// Birthdays on the 29th of February are only celebrated in leap years.
+    pub fn next_birthday(&self, today: NaiveDate) -> NaiveDate {
+        (today.year()..)
+            .filter_map(|year| self.birthday.with_year(year))
+            .find(|date| *date >= today)
+            .expect("a leap year follows within eight years")
+    }
+
+    /// Returns day and month of the half birthday six months after the birthday.
+    ///
+    /// Half birthdays after the end of a shorter month fall on its last day, the one of
+    /// February on the 28th.
+    pub fn half_birthday(&self) -> (u32, u32) {
+        let month = (self.birthday.month() + 5) % 12 + 1;
+        let day = (1..=self.birthday.day())
+            .rev()
+            .find(|&day| NaiveDate::from_ymd_opt(2001, month, day).is_some())
+            .unwrap_or(1);
+        (day, month)
+    }
+
+    /// Returns the urls of the photos of the person, the image column may contain several
+    /// separated by spaces.
+    pub fn image_urls(&self) -> Vec<&str> {
+        self.image_url
+            .as_deref()
+            .map_or(Vec::new(), |urls| urls.split_whitespace().collect())
+    }
+
+    fn pronoun(&self) -> &'static str {
+        match self.gender() {
+            Gender::Male => "Herr ",
+            Gender::Female => "Frau ",
+            Gender::Other => "",
+        }
+    }
+
+    /// Returns the shown photo or, if none could be loaded, the error of the first one.
+    ///
+    /// The loaded photos are shown one after another, errors are empty if `silent`.
+    fn photo(&self, settings: &CardSettings) -> Option<Result<&Handle, &str>> {
+        let urls = self.image_urls();
+        let loaded: Vec<&Handle> = urls
+            .iter()
+            .filter_map(|url| self.image_data.get(*url)?.as_ref().ok())
+            .collect();
+        match loaded.as_slice() {
+            [] => {
+                let error = urls
+                    .iter()
+                    .find_map(|url| self.image_data.get(*url)?.as_ref().err())?;
+                Some(Err(if settings.silent { "" } else { error }))
+            }
+            handles => Some(Ok(handles[settings.slide % handles.len()])),
+        }
+    }
+
+    /// Returns the sentence announcing the birthday on `date` as seen on `today`.
+    ///
+    /// Without `today` the date is always written out.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn banner(&self, date: NaiveDate, today: Option<NaiveDate>) -> String {
+        self.banner_in(Locale::default(), date, today, MONTHS_UNTIL)
+    }
+
+    /// Returns the sentence of [`Person::banner`] in the language of `locale`, counting
+    /// the age in months if it is less than `months_until`.
+    pub fn banner_in(
+        &self,
+        locale: Locale,
+        date: NaiveDate,
+        today: Option<NaiveDate>,
+        months_until: u32,
+    ) -> String {
+        let age = self.age_on(date, months_until, Age::Years);
+        locale.banner(self.gender(), &self.name(), date, Some(date) == today, age)
+    }
+
+    /// Returns the sentence announcing the half birthday on `date` like [`Person::banner_in`].
+    pub fn half_birthday_in(
+        &self,
+        locale: Locale,
+        date: NaiveDate,
+        today: Option<NaiveDate>,
+        months_until: u32,
+    ) -> String {
+        let age = self.age_on(date, months_until, Age::HalfYears);
+        locale.banner(self.gender(), &self.name(), date, Some(date) == today, age)
+    }
+
+    /// Returns the age on `date` in months if it is less than `months_until` and
+    /// the full years passed to `years` otherwise.
+    fn age_on(&self, date: NaiveDate, months_until: u32, years: fn(u32) -> Age) -> Option<Age> {
+        self.months_on(date).map(|months| {
+            if months < months_until {
+                Age::Months(months)
+            } else {
+                years(months / 12)
+            }
+        })
+    }
+
+    /// Returns the number of full months the person is old on `date`.
+    fn months_on(&self, date: NaiveDate) -> Option<u32> {
+        let months = (date.year() - self.birthday.year()) * 12 + date.month() as i32
+            - self.birthday.month() as i32
+            - i32::from(date.day() < self.birthday.day());
+        u32::try_from(months).ok()
+    }
+
+    /// Returns the banners of the card in the languages of `settings` announcing
+    /// `occasion` on `date` as seen on `today`.
+    pub fn banners(
+        &self,
+        date: NaiveDate,
+        occasion: Occasion,
+        today: NaiveDate,
+        settings: &CardSettings,
+    ) -> Vec<(Locale, String)> {
+        let today = Some(today);
+        settings
+            .locales()
+            .map(|locale| {
+                let banner = match occasion {
+                    Occasion::Birthday => {
+                        self.banner_in(locale, date, today, settings.months_until)
+                    }
+                    Occasion::HalfBirthday => {
+                        self.half_birthday_in(locale, date, today, settings.months_until)
+                    }
+                    Occasion::Milestone(days) => locale.milestone(
+                        self.gender(),
+                        &self.name(),
+                        date,
+                        Some(date) == today,
+                        days,
+                    ),
+                };
+                (locale, banner)
+            })
+            .collect()
+    }
+
+    /// Returns what the card of the person shows with custom `banners` in their language
+    /// above the image.
+    pub fn card(&self, banners: Vec<(Locale, String)>, settings: &CardSettings) -> Card<'_> {
+        Card {
+            banners: banners
+                .into_iter()
+                .map(|(locale, banner)| {
+                    let alignment = if locale.rtl() {
+                        Horizontal::Right
+                    } else {
+                        Horizontal::Center
+                    };
+                    (locale.visual(&banner), alignment)
+                })
+                .collect(),
+            department: self.department.as_deref(),
+            notes: self.notes.as_deref(),
+            photo: self.photo(settings),
+            qr_code: match &self.qr_code {
+                Some(Ok(qr_code)) => Some(qr_code),
+                _ => None,
+            },
+            accent: self.accent(),
+            error_color: settings.error_color,
+            photo_fit: settings.photo_fit,
+            photo_height: settings.photo_height,
+        }
+    }
+
+    /// Generates the card of the person with custom `banners` in their language above the image.
+    #[cfg(feature = "gui")]
+    pub fn view_with_banners(
+        &self,
+        banners: Vec<(Locale, String)>,
+        settings: &CardSettings,
+    ) -> Element<'_, Message> {
+        self.card(banners, settings).view(settings.layout)
+    }
+
+    /// Returns the accent color of the card if the person has one.
+    pub fn accent(&self) -> Option<Color> {
+        self.color
+            .as_deref()
+            .and_then(|color| style::parse_color(color).ok())
+    }
+
+    /// Generates a detailed representation of the person with an enlarged image, telling
+    /// the age on `today`.
+    #[cfg(feature = "gui")]
+    pub fn view_details(&self, settings: &CardSettings, today: NaiveDate) -> Element<'_, Message> {
+        let mut column: Column<Message> = column![
+            text(format!("{}{}", self.pronoun(), self.name())).size(30),
+            text(format!(
+                "geboren am {}",
+                self.birthday.format(custom_date_format::FORMAT)
+            ))
+            .size(20),
+        ];
+        if let Some(department) = &self.department {
+            column = column.push(text(department).size(20));
+        }
+        if let Some(age) = today.years_since(self.birthday) {
+            column = column.push(text(format!("{age} Jahre alt")).size(20));
+        }
+        if let Some(notes) = &self.notes {
+            column = column.push(text(notes).size(20));
+        }
+
+        if let Some(photo) = self.photo(settings) {
+            let fit = settings.photo_fit;
+            column = column.push(view_photo(photo, 600.0, None, fit, settings.error_color));
+        }
+        if let Some(url) = &self.contact_url {
+            column = column.push(
+                button(text("Kontakt öffnen").size(20))
+                    .on_press(Message::Ui(UiMessage::OpenLink(url.clone()))),
+            );
+        }
+
+        column.align_items(Alignment::Center).spacing(20).into()
+    }
+}
+
+/// What the card of a person shows, built without the gui so it can be tested.
+#[derive(Debug, Clone)]
+pub struct Card<'a> {
+    /// The banners from top to bottom as they are drawn with their alignment.
+    pub banners: Vec<(String, Horizontal)>,
+    pub department: Option<&'a str>,
+    pub notes: Option<&'a str>,
+    /// The shown photo or the error why none could be loaded.
+    pub photo: Option<Result<&'a Handle, &'a str>>,
+    pub qr_code: Option<&'a Handle>,
+    pub accent: Option<Color>,
+    pub error_color: Color,
+    pub photo_fit: PhotoFit,
+    /// The largest part of the height of the card that the photo may take up.
+    pub photo_height: Option<f32>,
+}
+
+#[cfg(feature = "gui")]
+impl<'a> Card<'a> {
+    /// Generates the widgets of the card in `layout`.
+    pub fn view(self, layout: Layout) -> Element<'a, Message> {
+        let accent = self.accent;
+        let content = match layout {
+            Layout::PhotoTop => self.view_photo_top(),
+            Layout::PhotoLeft => self.view_photo_left(),
+            Layout::TextOnly => self.texts(20, Alignment::Center).into(),
+            Layout::CompactList => self.view_compact(),
         };
-        let banner_str = match Utc::now().date_naive().years_since(self.birthday) {
-            Some(age) => format!(
-                "{}{} {} wird heute {} Jahre alt.",
-                pronoun, self.first_name, self.last_name, age
+        match accent {
+            Some(color) => container(content)
+                .padding(10)
+                .style(theme::Container::Custom(Box::new(style::Accent(color))))
+                .into(),
+            None => content,
+        }
+    }
+
+    /// Shows the photo with a width of `width` in a card of `layout`.
+    fn view_photo(&self, width: f32, layout: Layout) -> Option<Element<'a, Message>> {
+        let max_height = self
+            .photo_height
+            .map(|fraction| fraction * layout.size(1).height);
+        self.photo
+            .map(|photo| view_photo(photo, width, max_height, self.photo_fit, self.error_color))
+    }
+
+    /// Generates the large card of the next birthday on a day without any.
+    pub fn view_hero(self) -> Element<'a, Message> {
+        // the photo is kept small enough for the banner and the countdown to fit
+        let (fit, error_color) = (self.photo_fit, self.error_color);
+        let photo = self
+            .photo
+            .map(|photo| view_photo(photo, 500.0, Some(HERO_PHOTO_HEIGHT), fit, error_color));
+        let mut column = self.texts(40, Alignment::Center);
+        if let Some(photo) = photo {
+            column = column.push(photo);
+        }
+        column.into()
+    }
+
+    fn view_photo_top(self) -> Element<'a, Message> {
+        let (photo, qr_code) = (self.view_photo(300.0, Layout::PhotoTop), self.qr_code);
+        let mut column = self.texts(20, Alignment::Center);
+        if let Some(photo) = photo {
+            column = column.push(photo);
+        }
+        if let Some(qr_code) = qr_code {
+            column = column.push(Image::new(qr_code.clone()).width(Length::Fixed(150.0)));
+        }
+        column.into()
+    }
+
+    fn view_photo_left(self) -> Element<'a, Message> {
+        let (photo, qr_code) = (self.view_photo(300.0, Layout::PhotoLeft), self.qr_code);
+        let mut column = self.texts(20, Alignment::Start);
+        if let Some(qr_code) = qr_code {
+            column = column.push(Image::new(qr_code.clone()).width(Length::Fixed(150.0)));
+        }
+        let mut row = Row::new().spacing(20).align_items(Alignment::Center);
+        if let Some(photo) = photo {
+            row = row.push(photo);
+        }
+        row.push(column).into()
+    }
+
+    fn view_compact(self) -> Element<'a, Message> {
+        let photo = self.view_photo(80.0, Layout::CompactList);
+        let texts = self.texts(16, Alignment::Start).spacing(5);
+        match photo {
+            Some(photo) => row![photo, texts]
+                .spacing(15)
+                .align_items(Alignment::Center)
+                .into(),
+            None => texts.into(),
+        }
+    }
+
+    /// Returns the banners in `size` followed by the department and the notes.
+    fn texts(self, size: u16, alignment: Alignment) -> Column<'a, Message> {
+        let mut column: Column<Message> = Column::new();
+        for (banner, horizontal) in self.banners {
+            column = column.push(text(banner).size(size).horizontal_alignment(horizontal));
+        }
+        if let Some(department) = self.department {
+            column = column.push(
+                container(text(department).size(size * 7 / 10))
+                    .padding([2, 10])
+                    .style(style::chip as fn(&iced::Theme) -> container::Appearance),
+            );
+        }
+        if let Some(notes) = self.notes {
+            column = column.push(text(notes).size(size * 4 / 5));
+        }
+        column.align_items(alignment).spacing(20)
+    }
+}
+
+/// Shows `photo` fitted by `fit` into a width of `width` and at most `max_height` or its
+/// error in `error_color`.
+///
+/// Cropped and equally high photos are given the room of a portrait unless `max_height`
+/// is smaller, wide photos of the same height are letterboxed.
+#[cfg(feature = "gui")]
+fn view_photo<'a>(
+    photo: Result<&Handle, &'a str>,
+    width: f32,
+    max_height: Option<f32>,
+    fit: PhotoFit,
+    error_color: Color,
+) -> Element<'a, Message> {
+    let handle = match photo {
+        Ok(handle) => handle.clone(),
+        Err(error) => {
+            return container(text(error).size(20).style(error_color))
+                .width(Length::Fixed(width))
+                .into()
+        }
+    };
+    let height = max_height.unwrap_or(width * 4.0 / 3.0);
+    match fit {
+        PhotoFit::Contain => {
+            let photo = container(Image::new(handle)).width(Length::Fixed(width));
+            match max_height {
+                Some(max_height) => photo.max_height(max_height).center_x().into(),
+                None => photo.into(),
+            }
+        }
+        PhotoFit::Cover => Image::new(handle)
+            .width(Length::Fixed(width))
+            .height(Length::Fixed(height))
+            .content_fit(ContentFit::Cover)
+            .into(),
+        PhotoFit::FixedHeight => container(Image::new(handle).height(Length::Fixed(height)))
+            .width(Length::Fixed(width))
+            .center_x()
+            .into(),
+    }
+}
+
+/// Deserializes an optional color, rejecting the record if it can not be parsed.
+pub fn deserialize_color<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(color) if !color.trim().is_empty() => {
+            style::parse_color(&color).map_err(serde::de::Error::custom)?;
+            Ok(Some(color))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Deserializes a flag that is set by any text in its column, e.g. `x`.
+fn deserialize_flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<String>::deserialize(deserializer)?.is_some_and(|flag| !flag.trim().is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, date};
+
+    fn settings(second_locale: Option<Locale>) -> CardSettings {
+        CardSettings {
+            silent: false,
+            error_color: Color::BLACK,
+            locale: Locale::German,
+            second_locale,
+            months_until: MONTHS_UNTIL,
+            slide: 0,
+            layout: Layout::default(),
+            photo_fit: PhotoFit::default(),
+            photo_height: None,
+        }
+    }
+
+    /// Describes what `card` shows line by line.
+    fn snapshot(card: &Card) -> String {
+        let mut lines: Vec<String> = card
+            .banners
+            .iter()
+            .map(|(banner, alignment)| format!("banner {alignment:?}: {banner}"))
+            .collect();
+        lines.extend(
+            card.department
+                .map(|department| format!("department: {department}")),
+        );
+        lines.extend(card.notes.map(|notes| format!("notes: {notes}")));
+        lines.extend(card.photo.map(|photo| match photo {
+            Ok(_) => String::from("photo"),
+            Err(error) => format!("photo error: {error}"),
+        }));
+        lines.extend(card.qr_code.map(|_| String::from("qr code")));
+        lines.extend(card.accent.map(|_| String::from("accent")));
+        lines.join("\n")
+    }
+
+    /// Describes the card of `person` celebrating `occasion` on `day` as seen on `today`.
+    fn render(
+        person: &Person,
+        occasion: Occasion,
+        day: NaiveDate,
+        today: NaiveDate,
+        settings: &CardSettings,
+    ) -> String {
+        let banners = person.banners(day, occasion, today, settings);
+        snapshot(&person.card(banners, settings))
+    }
+
+    fn max() -> Person {
+        Person::builder("Mustermann", "Max", date(1984, 3, 10), Gender::Male)
+            .image_url(Some(String::from("https://example.com/max.jpg")))
+            .color(Some(String::from("#336699")))
+            .notes(Some(String::from("mag Kaffee")))
+            .department(Some(String::from("Vertrieb")))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn german_birthday_today() {
+        let mut person = max();
+        person.image_data.insert(
+            String::from("https://example.com/max.jpg"),
+            Err(String::from("404 Not Found")),
+        );
+        let day = date(2024, 3, 10);
+
+        assert_eq!(
+            render(&person, Occasion::Birthday, day, day, &settings(None)),
+            "banner Center: Herr Max Mustermann wird heute 40 Jahre alt.\n\
+             department: Vertrieb\n\
+             notes: mag Kaffee\n\
+             photo error: 404 Not Found\n\
+             accent"
+        );
+    }
+
+    #[test]
+    fn german_birthday_on_another_day_and_in_months() {
+        let day = date(2024, 3, 10);
+        let baby = Person::builder("Klein", "Lena", date(2023, 8, 10), Gender::Female)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            render(
+                &max(),
+                Occasion::Birthday,
+                day,
+                date(2024, 3, 8),
+                &settings(None)
             ),
-            None => format!(
-                "{}{} {} hat heute Geburtstag.",
-                pronoun, self.first_name, self.last_name
+            "banner Center: Herr Max Mustermann wird am 10.03. 40 Jahre alt.\n\
+             department: Vertrieb\n\
+             notes: mag Kaffee\n\
+             accent"
+        );
+        assert_eq!(
+            render(&baby, Occasion::Birthday, day, day, &settings(None)),
+            "banner Center: Frau Lena Klein wird heute 7 Monate alt."
+        );
+    }
+
+    #[test]
+    fn ageless_banners() {
+        // the age is unknown before the person was born, e.g. for a placeholder year
+        let person = Person::builder("Doe", "Alex", date(2030, 5, 1), Gender::Other)
+            .build()
+            .unwrap();
+        let day = date(2024, 5, 1);
+
+        assert_eq!(
+            render(&person, Occasion::Birthday, day, day, &settings(None)),
+            "banner Center: Alex Doe hat heute Geburtstag."
+        );
+        assert_eq!(
+            render(
+                &person,
+                Occasion::Birthday,
+                day,
+                date(2024, 4, 30),
+                &settings(Some(Locale::English))
             ),
-        };
-        let mut column: Column<Message> = column![text(banner_str).size(20)];
-
-        if let Some(maybe_image) = &self.image_data {
-            let element: Element<Message> = match maybe_image {
-                Ok(image_data) => {
-                    let image: Image = Image::new((*image_data).clone());
-                    image.into()
-                }
-                Err(error) => {
-                    let text = if silent { text("") } else { text(error) };
-                    text.size(20).style(Color::from_rgb(0.7, 0.0, 0.0)).into()
-                }
+            "banner Center: Alex Doe hat am 01.05. Geburtstag.\n\
+             banner Center: Alex Doe celebrates their birthday on May 1."
+        );
+    }
+
+    #[test]
+    fn milestone_and_half_birthday_banners() {
+        let mut person = max();
+        person.department = None;
+        person.notes = None;
+        person.color = None;
+        let day = date(2011, 7, 27);
+
+        assert_eq!(
+            render(
+                &person,
+                Occasion::Milestone(10_000),
+                day,
+                day,
+                &settings(None)
+            ),
+            "banner Center: Herr Max Mustermann ist heute 10000 Tage alt."
+        );
+        assert_eq!(
+            render(
+                &person,
+                Occasion::HalfBirthday,
+                date(2024, 9, 10),
+                date(2024, 9, 10),
+                &settings(None)
+            ),
+            "banner Center: Herr Max Mustermann wird heute 40½ Jahre alt."
+        );
+    }
+
+    #[test]
+    fn right_to_left_banners_are_aligned_right() {
+        let mut person = max();
+        person.department = None;
+        person.notes = None;
+        person.color = None;
+        let day = date(2024, 3, 10);
+        let card = person.card(
+            person.banners(
+                day,
+                Occasion::Birthday,
+                day,
+                &settings(Some(Locale::Hebrew)),
+            ),
+            &settings(Some(Locale::Hebrew)),
+        );
+
+        assert_eq!(card.banners.len(), 2);
+        assert_eq!(card.banners[0].1, Horizontal::Center);
+        assert_eq!(card.banners[1].1, Horizontal::Right);
+        assert!(card.photo.is_none());
+    }
+
+    #[test]
+    fn birthdays_in_the_future_or_too_long_ago_are_implausible() {
+        let today = date(2024, 3, 10);
+        let implausible = |birthday| fixtures::person("Kim", birthday).implausible_birthday(today);
+        assert!(implausible(date(2103, 3, 10)).is_some());
+        assert!(implausible(date(2024, 3, 11)).is_some());
+        assert!(implausible(date(1903, 3, 10)).is_some());
+        assert!(implausible(date(1903, 3, 11)).is_none());
+        assert!(implausible(date(2024, 3, 10)).is_none());
+    }
+}
+
+#[cfg(test)]
+mod properties {
+    use super::*;
+    use crate::fixtures::{self, date_ranges, dates};
+
+    use proptest::prelude::*;
+
+    fn person(birthday: NaiveDate) -> Person {
+        fixtures::person("Kim", birthday)
+    }
+
+    proptest! {
+        #![proptest_config(fixtures::config())]
+
+        #[test]
+        fn counts_the_same_years_as_chrono(birthday in dates(), date in dates()) {
+            let months = person(birthday).months_on(date);
+
+            prop_assert_eq!(months.is_some(), date >= birthday);
+            prop_assert_eq!(months.map(|months| months / 12), date.years_since(birthday));
+        }
+
+        #[test]
+        fn turns_the_difference_of_the_years_on_the_birthday(
+            (birthday, date) in date_ranges(150 * 366),
+        ) {
+            let person = person(birthday);
+            let next = person.next_birthday(date);
+
+            let years = (next.year() - birthday.year()) as u32;
+            prop_assert_eq!(person.age_on(next, 0, Age::Years), Some(Age::Years(years)));
+            let expected = if years * 12 < MONTHS_UNTIL {
+                Age::Months(years * 12)
+            } else {
+                Age::Years(years)
             };
-            column = column.push(container(element).width(Length::Fixed(300.0)));
+            prop_assert_eq!(person.age_on(next, MONTHS_UNTIL, Age::Years), Some(expected));
         }
 
-        column.align_items(Alignment::Center).spacing(20).into()
+        #[test]
+        fn half_birthdays_are_six_months_later_on_an_existing_day(birthday in dates()) {
+            let (day, month) = person(birthday).half_birthday();
+
+            prop_assert_eq!(month, (birthday.month() + 5) % 12 + 1);
+            prop_assert!(day <= birthday.day());
+            // the half birthday exists in every year
+            prop_assert!(NaiveDate::from_ymd_opt(2001, month, day).is_some());
+        }
     }
 }
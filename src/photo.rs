@@ -0,0 +1,235 @@
+use std::io::Cursor;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+#[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use base64::Engine;
+use exif::{In, Reader, Tag};
+use iced_native::image::Handle;
+use image::imageops::FilterType;
+use image::DynamicImage;
+
+/// The longest side of a decoded photo, the details show it 600 pixels wide and
+/// larger photos only cost memory even at a scale factor of two.
+const MAX_SIZE: u32 = 1200;
+
+/// The megabytes a downloaded photo may have without `--max-image-size`.
+#[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+pub const DEFAULT_MAX_DOWNLOAD: usize = 10;
+
+/// The bytes a downloaded photo may have, larger downloads are aborted.
+#[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+static MAX_DOWNLOAD: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_DOWNLOAD * 1024 * 1024);
+
+/// Aborts downloads of photos larger than `megabytes`, e.g. of a video linked by mistake.
+#[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+pub fn set_max_download(megabytes: usize) {
+    MAX_DOWNLOAD.store(megabytes.saturating_mul(1024 * 1024), Ordering::Relaxed);
+}
+
+/// Why the answer of an image server could not be read.
+#[cfg(feature = "network")]
+#[derive(Debug)]
+pub enum BodyError {
+    Request(reqwest::Error),
+    /// The answer has more than this many megabytes.
+    // the browser reads every answer completely
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    TooLarge(usize),
+}
+
+/// Reads the answer of an image server, giving up as soon as it gets larger than
+/// allowed instead of keeping all of it in memory first.
+#[cfg(all(not(target_arch = "wasm32"), feature = "network"))]
+pub async fn read_body(mut response: reqwest::Response) -> Result<bytes::Bytes, BodyError> {
+    let limit = MAX_DOWNLOAD.load(Ordering::Relaxed);
+    let too_large = BodyError::TooLarge(limit / (1024 * 1024));
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit as u64)
+    {
+        return Err(too_large);
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(BodyError::Request)? {
+        // the length is missing or wrong if the server compresses or streams the answer
+        if body.len() + chunk.len() > limit {
+            return Err(too_large);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.into())
+}
+
+/// The browser limits the memory of the page itself.
+#[cfg(all(target_arch = "wasm32", feature = "network"))]
+pub async fn read_body(response: reqwest::Response) -> Result<bytes::Bytes, BodyError> {
+    response.bytes().await.map_err(BodyError::Request)
+}
+
+/// Decodes the downloaded `bytes` of a photo upright and shrinks it to [`MAX_SIZE`].
+///
+/// Phones store photos as they were taken and note the rotation in the EXIF
+/// orientation, so the pixels are turned accordingly before the handle is created.
+pub fn decode(bytes: &[u8]) -> Result<Handle, String> {
+    let image = image::load_from_memory(bytes).map_err(|error| error.to_string())?;
+    let mut image = orient(image, orientation(bytes));
+    if image.width() > MAX_SIZE || image.height() > MAX_SIZE {
+        image = image.resize(MAX_SIZE, MAX_SIZE, FilterType::Triangle);
+    }
+    let image = image.into_rgba8();
+    let (width, height) = image.dimensions();
+    Ok(Handle::from_pixels(width, height, image.into_raw()))
+}
+
+/// Decodes `bytes` like [`decode`] on a thread of the blocking pool, so many photos
+/// arriving at once neither stall the gui nor the other downloads.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn decode_in_background(
+    bytes: impl AsRef<[u8]> + Send + 'static,
+) -> Result<Handle, String> {
+    tokio::task::spawn_blocking(move || decode(bytes.as_ref()))
+        .await
+        .map_err(|error| error.to_string())?
+}
+
+/// The browser has no threads to decode the photos on.
+#[cfg(target_arch = "wasm32")]
+pub async fn decode_in_background(bytes: impl AsRef<[u8]>) -> Result<Handle, String> {
+    decode(bytes.as_ref())
+}
+
+/// Returns the bytes of a photo on the disk given as `file:///srv/photos/anna.jpg`.
+///
+/// Returns `None` if `url` is not a file url.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_file_url(url: &str) -> Option<Result<Vec<u8>, String>> {
+    let path = file_url_path(url)?;
+    Some(path.and_then(|path| std::fs::read(path).map_err(|error| error.to_string())))
+}
+
+/// Returns the path of a file url with its escaped characters like `%20` decoded.
+///
+/// On Windows `file:///C:/Fotos/anna.jpg` is the drive path `C:/Fotos/anna.jpg` and
+/// `file://server/share/anna.jpg` the network share `\\server\share\anna.jpg`.
+#[cfg(not(target_arch = "wasm32"))]
+fn file_url_path(url: &str) -> Option<Result<PathBuf, String>> {
+    let rest = url.strip_prefix("file://")?;
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let path = percent_decode(path);
+    let local = host.is_empty() || host == "localhost";
+
+    if cfg!(windows) {
+        let path = match path.strip_prefix('/') {
+            Some(drive) if local && drive.as_bytes().get(1) == Some(&b':') => drive.to_string(),
+            _ if local => path,
+            _ => format!(r"\\{host}{}", path.replace('/', "\\")),
+        };
+        return Some(Ok(PathBuf::from(path)));
+    }
+    Some(if local {
+        Ok(PathBuf::from(path))
+    } else {
+        Err(format!(
+            "can not read files of {host}, mount the share instead"
+        ))
+    })
+}
+
+/// Decodes the `%XX` escapes of a url, keeping invalid ones as they are.
+#[cfg(not(target_arch = "wasm32"))]
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| text.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Returns the bytes of an image embedded in the file as `data:image/png;base64,...`.
+///
+/// Returns `None` if `url` is not a data url.
+pub fn decode_data_url(url: &str) -> Option<Result<Vec<u8>, String>> {
+    let data = url.strip_prefix("data:")?;
+    Some(
+        data.split_once(',')
+            .filter(|(media_type, _)| media_type.ends_with(";base64"))
+            .ok_or_else(|| String::from("only base64 encoded data urls are supported"))
+            .and_then(|(_, content)| {
+                base64::engine::general_purpose::STANDARD
+                    .decode(content)
+                    .map_err(|error| error.to_string())
+            }),
+    )
+}
+
+/// Checks by the first bytes that a download is a photo and tells what the server sent
+/// instead, e.g. the login page of a proxy.
+///
+/// The content type is only used for the message as some servers send photos as
+/// `application/octet-stream`.
+#[cfg(feature = "network")]
+pub fn sniff(bytes: &[u8], content_type: Option<&str>) -> Result<(), String> {
+    if image::guess_format(bytes).is_ok() {
+        return Ok(());
+    }
+    let media_type = content_type
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|media_type| media_type.trim().to_ascii_lowercase());
+    let text = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_ascii_lowercase();
+    Err(match media_type.as_deref() {
+        _ if bytes.is_empty() => String::from("the server sent an empty answer instead of a photo"),
+        Some("text/html") => {
+            String::from("the server sent a web page instead of a photo, maybe a login")
+        }
+        _ if text.trim_start().starts_with('<') && text.contains("<html") => {
+            String::from("the server sent a web page instead of a photo, maybe a login")
+        }
+        Some(media_type) => format!("the server sent {media_type} instead of a photo"),
+        None => String::from("the server sent no photo"),
+    })
+}
+
+/// Returns the EXIF orientation of the photo between 1 and 8, 1 if it is upright or unknown.
+fn orientation(bytes: &[u8]) -> u32 {
+    Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(Tag::Orientation, In::PRIMARY)?
+                .value
+                .get_uint(0)
+        })
+        .unwrap_or(1)
+}
+
+/// Turns `image` stored with `orientation` upright.
+// https://magnushoff.com/articles/jpeg-orientation/
+fn orient(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+
+use reqwest::Url;
+use tokio::time::Instant;
+
+/// How many photos are downloaded per second from one host without `--host-rate`.
+pub const DEFAULT_RATE: u32 = 4;
+
+/// The downloads per second and host, 0 for no limit.
+static RATE: AtomicU32 = AtomicU32::new(DEFAULT_RATE);
+
+/// When the next download from each host may start.
+static NEXT: OnceLock<Mutex<HashMap<String, Instant>>> = OnceLock::new();
+
+/// Starts at most `rate` downloads per second from one host, all at once if 0.
+pub fn set_rate(rate: u32) {
+    RATE.store(rate, Ordering::Relaxed);
+}
+
+/// Returns a random part of up to half of `interval`, so many displays started at the
+/// same time do not keep asking the server in the same moment.
+fn jitter(interval: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    interval.mul_f64(random as f64 / u64::MAX as f64 / 2.0)
+}
+
+/// Waits until the next download from the host of `url` may start.
+pub async fn wait(url: &str) {
+    let rate = RATE.load(Ordering::Relaxed);
+    let host = Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_lowercase));
+    let (Some(host), true) = (host, rate > 0) else {
+        return;
+    };
+
+    let interval = Duration::from_secs(1) / rate;
+    let start = {
+        let mut next = NEXT
+            .get_or_init(Mutex::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        let start = next.get(&host).map_or(now, |&next| next.max(now));
+        next.insert(host, start + interval + jitter(interval));
+        start
+    };
+    tokio::time::sleep_until(start).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spaces_the_downloads_from_one_host() {
+        let runtime = crate::runtime::current_thread().unwrap();
+        let started = std::time::Instant::now();
+        runtime.block_on(async {
+            wait("https://photos.example.com/anna.jpg").await;
+            wait("https://other.example.com/ben.jpg").await;
+        });
+        assert!(started.elapsed() < Duration::from_secs(1) / DEFAULT_RATE);
+
+        runtime.block_on(wait("https://PHOTOS.example.com/cem.jpg"));
+        assert!(started.elapsed() >= Duration::from_secs(1) / DEFAULT_RATE);
+    }
+}
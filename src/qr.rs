@@ -0,0 +1,47 @@
+use crate::percent::encode;
+use crate::person::Person;
+
+use qrcode::{Color, QrCode};
+
+use iced_native::image::Handle;
+
+/// The number of pixels per module of the qr code.
+const SCALE: usize = 6;
+
+/// The number of light modules around the qr code required by scanners.
+const QUIET_ZONE: usize = 4;
+
+/// Fills the placeholders `{first_name}`, `{last_name}` and `{name}` of `template`
+/// with the url encoded names of `person`.
+pub fn greeting_link(template: &str, person: &Person) -> String {
+    template
+        .replace("{first_name}", &encode(&person.first_name))
+        .replace("{last_name}", &encode(&person.last_name))
+        .replace("{name}", &encode(&person.name()))
+}
+
+/// Generates an image of a qr code containing `data`.
+pub fn generate(data: &str) -> Result<Handle, String> {
+    let code = QrCode::new(data).map_err(|error| error.to_string())?;
+    let modules = code.width();
+    let colors = code.to_colors();
+
+    let size = (modules + 2 * QUIET_ZONE) * SCALE;
+    let mut pixels = vec![u8::MAX; size * size * 4];
+    for (index, color) in colors.into_iter().enumerate() {
+        if color == Color::Light {
+            continue;
+        }
+
+        let x = (index % modules + QUIET_ZONE) * SCALE;
+        let y = (index / modules + QUIET_ZONE) * SCALE;
+        for row in y..y + SCALE {
+            let start = (row * size + x) * 4;
+            for pixel in pixels[start..start + SCALE * 4].chunks_exact_mut(4) {
+                pixel.copy_from_slice(&[0, 0, 0, u8::MAX]);
+            }
+        }
+    }
+
+    Ok(Handle::from_pixels(size as u32, size as u32, pixels))
+}
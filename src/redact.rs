@@ -0,0 +1,60 @@
+use crate::csv::custom_date_format;
+use crate::person::Person;
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether log messages may contain the personal data of the persons.
+static LOG_PII: AtomicBool = AtomicBool::new(false);
+
+/// Allows log messages to contain names, birthdays and links of persons.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_log_pii(enabled: bool) {
+    LOG_PII.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether personal data is written to the log as it is.
+pub fn log_pii() -> bool {
+    LOG_PII.load(Ordering::Relaxed)
+}
+
+/// Hashes `text` with FNV-1a so the same person has the same placeholder in every run.
+fn hash(text: &str) -> u32 {
+    text.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Formats the name and birthday of a person for log messages, replaced by a hash of the
+/// name unless `--log-pii` is given.
+pub struct Redacted<'a>(pub &'a Person);
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if log_pii() {
+            let birthday = self.0.birthday.format(custom_date_format::FORMAT);
+            write!(f, "{} ({birthday})", self.0.name())
+        } else {
+            write!(f, "person {:08x}", hash(&self.0.name()))
+        }
+    }
+}
+
+/// Formats a link of a person for log messages, only keeping the host unless `--log-pii` is given.
+pub struct RedactedUrl<'a>(pub &'a str);
+
+impl fmt::Display for RedactedUrl<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if log_pii() {
+            return write!(f, "{}", self.0);
+        }
+        // the path and the query often contain names or ids
+        match self.0.split_once("://") {
+            Some((scheme, rest)) => {
+                let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+                write!(f, "{scheme}://{host}/[{:08x}]", hash(self.0))
+            }
+            None => write!(f, "[{:08x}]", hash(self.0)),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::error::BirthdayError;
+
+#[cfg(feature = "gui")]
+use std::io;
+#[cfg(feature = "gui")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "gui")]
+use iced::futures::Future;
+use tokio::runtime::{Builder, Runtime};
+
+/// The number of worker threads of the runtime of the gui, one per cpu core if 0.
+#[cfg(feature = "gui")]
+static WORKER_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Runs the futures of the gui on `threads` worker threads, has to be called before
+/// the window is opened.
+#[cfg(feature = "gui")]
+pub fn set_worker_threads(threads: usize) {
+    WORKER_THREADS.store(threads, Ordering::Relaxed);
+}
+
+/// Returns a runtime on the calling thread for the commands, which wait for one future
+/// after another.
+pub fn current_thread() -> Result<Runtime, BirthdayError> {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|error| format!("error starting the runtime: {error}").into())
+}
+
+/// Runs the downloads, file reads and subscriptions of the gui on a tokio runtime with
+/// the number of worker threads given by [`set_worker_threads`].
+///
+/// Blocking work like decoding photos still runs on the separate blocking threads that
+/// tokio starts when they are needed.
+#[cfg(feature = "gui")]
+pub struct Executor(Runtime);
+
+#[cfg(feature = "gui")]
+impl iced::Executor for Executor {
+    fn new() -> io::Result<Self> {
+        let mut builder = Builder::new_multi_thread();
+        let threads = WORKER_THREADS.load(Ordering::Relaxed);
+        if threads > 0 {
+            builder.worker_threads(threads);
+        }
+        builder.enable_all().build().map(Self)
+    }
+
+    fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+        drop(self.0.spawn(future));
+    }
+
+    fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _guard = self.0.enter();
+        f()
+    }
+}
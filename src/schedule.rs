@@ -0,0 +1,273 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+
+const TIME_FORMAT: &str = "%H:%M";
+
+/// A duration like `90s`, `15m`, `1h` or `2d`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interval(pub Duration);
+
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid interval \"{s}\", expected one like 30m or 1h");
+        let s = s.trim();
+        let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+        let number: u64 = number.parse().map_err(|_| invalid())?;
+        let seconds = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            "d" => 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        if number == 0 {
+            return Err(invalid());
+        }
+        Ok(Self(Duration::from_secs(
+            number.checked_mul(seconds).ok_or_else(invalid)?,
+        )))
+    }
+}
+
+/// A daily recurring time range like `22:00-06:00` which may span midnight.
+///
+/// A range with the same start and end like `00:00-00:00` covers the whole day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl TimeRange {
+    /// Returns whether `time` is inside of the range including its start.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start == self.end {
+            true
+        } else if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            self.start <= time || time < self.end
+        }
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| String::from("expected a range like 22:00-06:00"))?;
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), TIME_FORMAT)
+                .map_err(|error| format!("invalid time \"{time}\": {error}"))
+        };
+
+        Ok(Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        })
+    }
+}
+
+/// Brightness of the display during a time range on some weekdays.
+///
+/// Parsed from `[DAYS] HH:MM-HH:MM=PERCENT` like `Mon-Fri 18:00-22:00=40`
+/// where the days are a comma separated list of weekdays or weekday ranges.
+/// Without days the rule applies to every day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimRule {
+    weekdays: Vec<Weekday>,
+    range: TimeRange,
+    brightness: f32,
+}
+
+impl DimRule {
+    fn applies(&self, now: NaiveDateTime) -> bool {
+        self.weekdays.contains(&now.weekday()) && self.range.contains(now.time())
+    }
+}
+
+/// Returns the brightness between 0 and 1 of the first rule in `rules` that applies at `now`.
+pub fn brightness(rules: &[DimRule], now: NaiveDateTime) -> f32 {
+    rules
+        .iter()
+        .find(|rule| rule.applies(now))
+        .map_or(1.0, |rule| rule.brightness)
+}
+
+fn parse_weekdays(s: &str) -> Result<Vec<Weekday>, String> {
+    let parse =
+        |day: &str| Weekday::from_str(day.trim()).map_err(|_| format!("invalid weekday \"{day}\""));
+
+    let mut weekdays = Vec::new();
+    for item in s.split(',') {
+        match item.split_once('-') {
+            Some((first, last)) => {
+                let (mut day, last) = (parse(first)?, parse(last)?);
+                while day != last {
+                    weekdays.push(day);
+                    day = day.succ();
+                }
+                weekdays.push(last);
+            }
+            None => weekdays.push(parse(item)?),
+        }
+    }
+    Ok(weekdays)
+}
+
+impl FromStr for DimRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rule, percent) = s
+            .rsplit_once('=')
+            .ok_or_else(|| String::from("expected a rule like \"Mon-Fri 18:00-22:00=40\""))?;
+        let percent: f32 = percent
+            .trim()
+            .trim_end_matches('%')
+            .parse()
+            .map_err(|error| format!("invalid brightness \"{percent}\": {error}"))?;
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(String::from("the brightness must be between 0 and 100"));
+        }
+
+        let (weekdays, range) = match rule.trim().split_once(char::is_whitespace) {
+            Some((days, range)) => (parse_weekdays(days)?, range),
+            None => (
+                vec![
+                    Weekday::Mon,
+                    Weekday::Tue,
+                    Weekday::Wed,
+                    Weekday::Thu,
+                    Weekday::Fri,
+                    Weekday::Sat,
+                    Weekday::Sun,
+                ],
+                rule,
+            ),
+        };
+
+        Ok(Self {
+            weekdays,
+            range: range.parse()?,
+            brightness: percent / 100.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::date;
+
+    #[test]
+    fn parses_intervals_in_all_units() {
+        assert_eq!("90s".parse(), Ok(Interval(Duration::from_secs(90))));
+        assert_eq!("15m".parse(), Ok(Interval(Duration::from_secs(15 * 60))));
+        assert_eq!(
+            "2d".parse(),
+            Ok(Interval(Duration::from_secs(2 * 24 * 60 * 60)))
+        );
+        assert!("0m".parse::<Interval>().is_err());
+        assert!("10".parse::<Interval>().is_err());
+    }
+
+    #[test]
+    fn rejects_intervals_too_long_to_count() {
+        assert!(format!("{}d", u64::MAX / 1000).parse::<Interval>().is_err());
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn a_range_contains_its_start_but_not_its_end() {
+        let range: TimeRange = "08:00-17:30".parse().unwrap();
+        assert!(range.contains(time(8, 0)));
+        assert!(range.contains(time(17, 29)));
+        assert!(!range.contains(time(17, 30)));
+        assert!(!range.contains(time(7, 59)));
+    }
+
+    #[test]
+    fn a_range_wraps_around_midnight() {
+        let range: TimeRange = "22:00-06:00".parse().unwrap();
+        assert!(range.contains(time(22, 0)));
+        assert!(range.contains(time(0, 0)));
+        assert!(range.contains(time(5, 59)));
+        assert!(!range.contains(time(6, 0)));
+        assert!(!range.contains(time(12, 0)));
+        assert!(!range.contains(time(21, 59)));
+    }
+
+    #[test]
+    fn a_range_without_length_covers_the_whole_day() {
+        let range: TimeRange = "00:00-00:00".parse().unwrap();
+        assert!(range.contains(time(0, 0)));
+        assert!(range.contains(time(12, 0)));
+        assert!(range.contains(time(23, 59)));
+        assert!("22:00".parse::<TimeRange>().is_err());
+        assert!("22:00-25:00".parse::<TimeRange>().is_err());
+    }
+
+    #[test]
+    fn parses_the_weekdays_of_dim_rules() {
+        let rule = |s: &str| s.parse::<DimRule>().unwrap().weekdays;
+        assert_eq!(
+            rule("Mon-Fri 18:00-22:00=40"),
+            [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri
+            ]
+        );
+        assert_eq!(
+            rule("Fri-Mon,Wed 18:00-22:00=40"),
+            [
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun,
+                Weekday::Mon,
+                Weekday::Wed
+            ]
+        );
+        assert_eq!(rule("18:00-22:00=40").len(), 7);
+        assert_eq!(
+            "sat 18:00-22:00=40%".parse::<DimRule>().unwrap().brightness,
+            0.4
+        );
+        assert!("Someday 18:00-22:00=40".parse::<DimRule>().is_err());
+        assert!("Mon 18:00-22:00=140".parse::<DimRule>().is_err());
+        assert!("Mon 18:00-22:00".parse::<DimRule>().is_err());
+    }
+
+    #[test]
+    fn the_first_rule_of_the_weekday_sets_the_brightness() {
+        let rules: Vec<DimRule> = [
+            "Sat,Sun 00:00-00:00=20",
+            "22:00-06:00=40",
+            "Fri 18:00-22:00=60",
+        ]
+        .iter()
+        .map(|rule| rule.parse().unwrap())
+        .collect();
+        // the 16th of october 2026 is a friday
+        let at = |day, hour| date(2026, 10, day).and_time(time(hour, 0));
+        assert_eq!(brightness(&rules, at(16, 12)), 1.0);
+        assert_eq!(brightness(&rules, at(16, 19)), 0.6);
+        assert_eq!(brightness(&rules, at(16, 23)), 0.4);
+        assert_eq!(brightness(&rules, at(17, 12)), 0.2);
+        assert_eq!(brightness(&rules, at(17, 23)), 0.2);
+        assert_eq!(brightness(&rules, at(19, 3)), 0.4);
+        assert_eq!(brightness(&rules, at(19, 19)), 1.0);
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::BirthdayError;
+use crate::person::Person;
+use crate::Message;
+
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+use chrono::Local;
+
+use iced::widget::{column, text, Image};
+use iced::{Alignment, ContentFit, Element, Length};
+use iced_native::image::Handle;
+
+/// How many seconds every photo is shown.
+const DURATION: i64 = 10;
+
+/// The file extensions of the photos read from a folder.
+#[cfg(not(target_arch = "wasm32"))]
+const EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "gif", "webp", "bmp"];
+
+/// Shows photos one after another on days without birthdays.
+#[derive(Debug, Default)]
+pub struct Screensaver {
+    /// The file urls of the photos of a folder, the photos of the persons are shown if
+    /// there are none.
+    folder: Vec<String>,
+    /// The loaded photos of the folder, only the shown and the next one are kept.
+    images: HashMap<String, Result<Handle, String>>,
+}
+
+impl Screensaver {
+    /// Reads the names of the photos in `dir`, which are shown instead of the ones of
+    /// the persons.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_folder(dir: &Path) -> Result<Self, BirthdayError> {
+        let entries = std::fs::read_dir(dir).map_err(|error| BirthdayError::io(dir, error))?;
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .and_then(|extension| extension.to_str())
+                    .is_some_and(|extension| {
+                        EXTENSIONS.contains(&extension.to_lowercase().as_str())
+                    })
+            })
+            .collect();
+        if paths.is_empty() {
+            return Err(BirthdayError::Data(format!(
+                "{} contains no photos",
+                dir.display()
+            )));
+        }
+        paths.sort();
+        let folder = paths
+            .iter()
+            .map(|path| {
+                let path = path
+                    .to_string_lossy()
+                    .replace('\\', "/")
+                    .replace('%', "%25");
+                // drive paths on Windows start without a slash
+                if path.starts_with('/') {
+                    format!("file://{path}")
+                } else {
+                    format!("file:///{path}")
+                }
+            })
+            .collect();
+        Ok(Self {
+            folder,
+            images: HashMap::new(),
+        })
+    }
+
+    /// Returns the urls of the photos in the order they are shown with the index of the
+    /// person they belong to, leaving out photos that could not be loaded.
+    fn slides<'a>(&'a self, persons: &'a [Person]) -> Vec<(&'a str, Option<usize>)> {
+        if !self.folder.is_empty() {
+            return self
+                .folder
+                .iter()
+                .filter(|url| !matches!(self.images.get(*url), Some(Err(_))))
+                .map(|url| (url.as_str(), None))
+                .collect();
+        }
+        persons
+            .iter()
+            .enumerate()
+            .filter(|(_, person)| person.announced())
+            .filter_map(|(index, person)| {
+                let url = *person.image_urls().first()?;
+                match person.image_data.get(url) {
+                    Some(Err(_)) => None,
+                    _ => Some((url, Some(index))),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the shown slide followed by the next one.
+    fn due<'a>(&'a self, persons: &'a [Person]) -> Vec<(&'a str, Option<usize>)> {
+        let slides = self.slides(persons);
+        if slides.is_empty() {
+            return slides;
+        }
+        let shown = (Local::now().timestamp() / DURATION) as usize;
+        let mut due = vec![slides[shown % slides.len()]];
+        if slides.len() > 1 {
+            due.push(slides[(shown + 1) % slides.len()]);
+        }
+        due
+    }
+
+    /// Returns the indices of the persons whose photos are shown now and next.
+    pub fn persons(&self, persons: &[Person]) -> Vec<usize> {
+        self.due(persons)
+            .into_iter()
+            .filter_map(|(_, index)| index)
+            .collect()
+    }
+
+    /// Drops the photos of the folder that are not shown now or next and returns the urls
+    /// of the ones that still have to be loaded.
+    pub fn plan(&mut self, persons: &[Person]) -> Vec<String> {
+        let due: Vec<String> = self
+            .due(persons)
+            .into_iter()
+            .filter(|(_, index)| index.is_none())
+            .map(|(url, _)| url.to_string())
+            .collect();
+        self.images
+            .retain(|url, image| due.contains(url) || image.is_err());
+        due.into_iter()
+            .filter(|url| !self.images.contains_key(url))
+            .collect()
+    }
+
+    /// Keeps `image_data` if it is a photo of the folder.
+    pub fn received(&mut self, url: &str, image_data: &Result<Handle, String>) {
+        if self.folder.iter().any(|photo| photo == url) {
+            self.images.insert(url.to_string(), image_data.clone());
+        }
+    }
+
+    /// Shows the current photo filling the window with the name of the person below it.
+    pub fn view<'a>(&'a self, persons: &'a [Person]) -> Element<'a, Message> {
+        let Some(&(url, index)) = self.due(persons).first() else {
+            return column![].into();
+        };
+        let image = match index {
+            Some(index) => persons[index].image_data.get(url),
+            None => self.images.get(url),
+        };
+        let mut column = column![].spacing(20).align_items(Alignment::Center);
+        if let Some(Ok(handle)) = image {
+            column = column.push(
+                Image::new(handle.clone())
+                    .height(Length::Fill)
+                    .content_fit(ContentFit::Contain),
+            );
+        }
+        if let Some(index) = index {
+            column = column.push(text(persons[index].name()).size(30));
+        }
+        column.into()
+    }
+}
@@ -0,0 +1,257 @@
+use crate::csv::custom_date_format;
+use crate::error::BirthdayError;
+use crate::person::Person;
+use crate::redact::Redacted;
+use crate::source::PersonSource;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope, AST};
+
+/// How many operations a hook may run before it is stopped, so an endless loop does
+/// not freeze the window which calls `banner` for every card.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// How deep the functions of a script may call each other.
+const MAX_CALL_LEVELS: usize = 32;
+
+/// A rhai script customizing the persons and their cards.
+///
+/// Every hook is optional:
+/// - `transform(person)` returns the changed person or `()` to hide it
+/// - `banner(person, text)` returns the text shown above the image of the card
+/// - `day_change(date, birthdays)` is called when a new day starts
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Self, BirthdayError> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(MAX_CALL_LEVELS);
+        let ast = engine.compile_file(path.to_path_buf()).map_err(|error| {
+            BirthdayError::Data(format!("error in {}: {error}", path.display()))
+        })?;
+        Ok(Self { engine, ast })
+    }
+
+    fn has_hook(&self, name: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|function| function.name == name)
+    }
+
+    /// Calls the hook `name` if the script defines it.
+    fn call(&self, name: &str, args: impl FuncArgs) -> Option<Result<Dynamic, String>> {
+        if !self.has_hook(name) {
+            return None;
+        }
+        // only the hook runs, not the statements outside of the functions
+        let options = CallFnOptions::new().eval_ast(false);
+        Some(
+            self.engine
+                .call_fn_with_options(options, &mut Scope::new(), &self.ast, name, args)
+                .map_err(|error| format!("error in {name}: {error}")),
+        )
+    }
+
+    /// Returns whether the persons are changed by the script.
+    pub fn transforms(&self) -> bool {
+        self.has_hook("transform")
+    }
+
+    /// Passes every person to the `transform` hook, keeping it unchanged on errors.
+    pub fn transform(&self, persons: Vec<Person>, quiet: bool) -> Vec<Person> {
+        if !self.transforms() {
+            return persons;
+        }
+        persons
+            .into_iter()
+            .filter_map(|mut person| {
+                let result = self
+                    .call("transform", (to_map(&person),))?
+                    .and_then(|value| {
+                        if value.is_unit() {
+                            return Ok(false);
+                        }
+                        let map = value
+                            .try_cast::<Map>()
+                            .ok_or("transform must return a person or ()")?;
+                        update(&mut person, &map)?;
+                        Ok(true)
+                    });
+
+                match result {
+                    Ok(keep) => keep.then_some(person),
+                    Err(error) => {
+                        if !quiet {
+                            eprintln!("error transforming {}: {error}", Redacted(&person));
+                        }
+                        Some(person)
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the banner of `person` from the `banner` hook or the default `text` if the
+    /// hook fails or runs too long.
+    pub fn banner(&self, person: &Person, text: String) -> String {
+        match self.call("banner", (to_map(person), text.clone())) {
+            Some(Ok(value)) => value.into_string().unwrap_or(text),
+            _ => text,
+        }
+    }
+
+    /// Tells the `day_change` hook about the birthdays of `date`.
+    pub fn day_change(&self, date: NaiveDate, birthdays: &[&Person], quiet: bool) {
+        let date = date.format(custom_date_format::FORMAT).to_string();
+        let birthdays: Array = birthdays
+            .iter()
+            .map(|person| Dynamic::from_map(to_map(person)))
+            .collect();
+        if let Some(Err(error)) = self.call("day_change", (date, birthdays)) {
+            if !quiet {
+                eprintln!("{error}");
+            }
+        }
+    }
+}
+
+fn to_map(person: &Person) -> Map {
+    let optional = |value: &Option<String>| value.clone().map_or(Dynamic::UNIT, Dynamic::from);
+
+    let mut map = Map::new();
+    map.insert("last_name".into(), person.last_name.clone().into());
+    map.insert("first_name".into(), person.first_name.clone().into());
+    map.insert(
+        "birthday".into(),
+        person
+            .birthday
+            .format(custom_date_format::FORMAT)
+            .to_string()
+            .into(),
+    );
+    map.insert("gender".into(), person.gender.to_string().into());
+    map.insert("image_url".into(), optional(&person.image_url));
+    map.insert("contact_url".into(), optional(&person.contact_url));
+    map.insert("color".into(), optional(&person.color));
+    map.insert("notes".into(), optional(&person.notes));
+    map.insert("department".into(), optional(&person.department));
+    map.insert("opt_out".into(), person.opt_out.into());
+    map
+}
+
+/// Copies the fields of `map` returned by the script to `person`.
+fn update(person: &mut Person, map: &Map) -> Result<(), String> {
+    let field = |key: &str| -> Result<Option<String>, String> {
+        match map.get(key) {
+            None => Ok(None),
+            Some(value) if value.is_unit() => Ok(None),
+            Some(value) => value
+                .clone()
+                .into_string()
+                .map(Some)
+                .map_err(|_| format!("\"{key}\" must be a string")),
+        }
+    };
+
+    if let Some(last_name) = field("last_name")? {
+        person.last_name = last_name;
+    }
+    if let Some(first_name) = field("first_name")? {
+        person.first_name = first_name;
+    }
+    if let Some(birthday) = field("birthday")? {
+        person.birthday = NaiveDate::parse_from_str(&birthday, custom_date_format::FORMAT)
+            .map_err(|error| format!("invalid birthday \"{birthday}\": {error}"))?;
+    }
+    if let Some(gender) = field("gender")? {
+        person.gender = gender.chars().next().unwrap_or(' ');
+    }
+    // the optional fields are removed by setting them to ()
+    if map.contains_key("image_url") {
+        person.image_url = field("image_url")?;
+    }
+    if map.contains_key("contact_url") {
+        person.contact_url = field("contact_url")?;
+    }
+    if map.contains_key("color") {
+        person.color = field("color")?;
+    }
+    if map.contains_key("notes") {
+        person.notes = field("notes")?;
+    }
+    if map.contains_key("department") {
+        person.department = field("department")?;
+    }
+    if let Some(opt_out) = map.get("opt_out") {
+        person.opt_out = opt_out
+            .as_bool()
+            .map_err(|_| String::from("opt_out has to be a bool"))?;
+    }
+    person.check().map_err(|error| error.to_string())
+}
+
+/// Applies the `transform` hook of a script to the persons of another source.
+pub struct ScriptedSource {
+    inner: Box<dyn PersonSource>,
+    script: Arc<Script>,
+}
+
+impl ScriptedSource {
+    pub fn new(inner: Box<dyn PersonSource>, script: Arc<Script>) -> Self {
+        Self { inner, script }
+    }
+}
+
+impl PersonSource for ScriptedSource {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn load(&mut self, quiet: bool) -> Result<Vec<Person>, BirthdayError> {
+        let persons = self.inner.load(quiet)?;
+        Ok(self.script.transform(persons, quiet))
+    }
+
+    fn changed(&self) -> bool {
+        self.inner.changed()
+    }
+
+    fn skipped(&self) -> usize {
+        self.inner.skipped()
+    }
+
+    // saving the transformed persons would lose the hidden ones
+    fn save(&mut self, persons: &[Person]) -> Result<(), BirthdayError> {
+        if self.script.transforms() {
+            Err(format!("{} is transformed by a script", self.name()).into())
+        } else {
+            self.inner.save(persons)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{date, person};
+
+    #[test]
+    fn endless_banners_fall_back_to_the_default_text() {
+        let path =
+            std::env::temp_dir().join(format!("birthday_display-test-{}.rhai", std::process::id()));
+        std::fs::write(&path, "fn banner(person, text) { loop {} }").unwrap();
+        let script = Script::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let anna = person("Anna", date(1990, 3, 10));
+        let banner = script.unwrap().banner(&anna, String::from("Alles Gute"));
+        assert_eq!(banner, "Alles Gute");
+    }
+}
@@ -0,0 +1,69 @@
+use crate::collation::{self, SortKey};
+use crate::locale::Locale;
+use crate::person::Person;
+use crate::{Message, UiMessage};
+
+use chrono::NaiveDate;
+
+use iced::widget::text_input::{self, Id};
+use iced::widget::{column, scrollable, text, Column};
+use iced::{Alignment, Element, Length};
+
+/// Returns the id of the search input to be able to focus it.
+pub fn input_id() -> Id {
+    Id::new("search")
+}
+
+/// Generates the search overlay listing all announced persons whose name contains `query`.
+pub fn view<'a>(
+    query: &str,
+    persons: impl IntoIterator<Item = &'a Person>,
+    today: NaiveDate,
+) -> Element<'a, Message> {
+    let mut matches: Vec<(i64, &Person)> = persons
+        .into_iter()
+        .filter(|person| person.announced())
+        .filter(|person| collation::contains(&person.name(), query))
+        .map(|person| ((person.next_birthday(today) - today).num_days(), person))
+        .collect();
+    matches.sort_by_cached_key(|(days, person)| (*days, SortKey::new(&person.name())));
+
+    let results: Vec<Element<Message>> = matches
+        .into_iter()
+        .map(|(days, person)| {
+            let until = match days {
+                0 => String::from("heute"),
+                _ => Locale::German.countdown(days),
+            };
+            let age = match today.years_since(person.birthday) {
+                Some(age) => format!(", {age} Jahre"),
+                None => String::new(),
+            };
+            text(format!(
+                "{}: {}{}, Geburtstag {}",
+                person.name(),
+                person.birthday.format("%d.%m.%Y"),
+                age,
+                until
+            ))
+            .size(20)
+            .into()
+        })
+        .collect();
+
+    let input = text_input::TextInput::new("Name suchen …", query, |query| {
+        Message::Ui(UiMessage::SearchChanged(query))
+    })
+    .id(input_id())
+    .size(30)
+    .padding(10)
+    .width(Length::Fixed(600.0));
+
+    column![
+        input,
+        scrollable(Column::with_children(results).spacing(10)).height(Length::Fill)
+    ]
+    .spacing(20)
+    .align_items(Alignment::Center)
+    .into()
+}
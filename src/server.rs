@@ -0,0 +1,287 @@
+use crate::clock::Clock;
+use crate::csv::{self, UnparsedRecords};
+use crate::error::BirthdayError;
+use crate::person::Person;
+use crate::source::PersonSource;
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use base64::Engine;
+
+/// Appended to the key of a websocket handshake before hashing it.
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC11B85";
+
+/// How often the data file and the date are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a browser may take to accept an event before it is disconnected.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The websocket connections of the browsers waiting for events.
+type Clients = Arc<Mutex<Vec<TcpStream>>>;
+
+/// The persons of the source as csv file for the web build.
+type Data = Arc<Mutex<Vec<u8>>>;
+
+/// Serves the persons of `source` and the files of the web build in `web_root` on `listen`
+/// and pushes `day-change` and `data-refresh` events to the browsers connected to `/events`.
+pub fn run(
+    listen: SocketAddr,
+    mut source: Box<dyn PersonSource>,
+    web_root: Option<&Path>,
+    quiet: bool,
+    clock: Arc<dyn Clock>,
+) -> Result<(), BirthdayError> {
+    let data = Data::new(Mutex::new(serialize(source.as_mut(), quiet)?));
+    let listener = TcpListener::bind(listen)
+        .map_err(|error| BirthdayError::Network(format!("error listening on {listen}: {error}")))?;
+    if !quiet {
+        println!("serving on http://{listen}");
+    }
+
+    let clients = Clients::default();
+    {
+        let clients = clients.clone();
+        let data = data.clone();
+        thread::spawn(move || watch(source, &data, &clients, quiet, clock.as_ref()));
+    }
+
+    for stream in listener.incoming().flatten() {
+        let clients = clients.clone();
+        let data = data.clone();
+        let web_root = web_root.map(Path::to_path_buf);
+        thread::spawn(move || {
+            if let Err(error) = handle(stream, &data, web_root.as_deref(), &clients) {
+                if !quiet {
+                    println!("error handling request: {error}");
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Reads the announced persons of `source` in the format the web build loads, the ones
+/// that opted out are not sent to the clients.
+fn serialize(source: &mut dyn PersonSource, quiet: bool) -> Result<Vec<u8>, BirthdayError> {
+    let persons: Vec<Person> = source
+        .load(quiet)?
+        .into_iter()
+        .filter(Person::announced)
+        .collect();
+    csv::serialize_persons(&persons, &UnparsedRecords::default()).map_err(|error| {
+        BirthdayError::Data(format!("error serializing {}: {error}", source.name()))
+    })
+}
+
+/// Tells the clients when the day of `clock` changes or the persons of `source` are
+/// modified.
+fn watch(
+    mut source: Box<dyn PersonSource>,
+    data: &Data,
+    clients: &Clients,
+    quiet: bool,
+    clock: &dyn Clock,
+) {
+    let mut today = clock.today();
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let now = clock.today();
+        if now != today {
+            today = now;
+            broadcast(clients, "day-change");
+        }
+
+        if source.changed() {
+            match serialize(source.as_mut(), quiet) {
+                Ok(content) => {
+                    *data
+                        .lock()
+                        .expect("no thread panics while holding the lock") = content;
+                    broadcast(clients, "data-refresh");
+                }
+                Err(error) => {
+                    if !quiet {
+                        println!("error reloading {}: {error}", source.name());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Sends `event` to all clients and forgets the disconnected and stalled ones.
+///
+/// The clients are written to without holding the lock, so a slow browser does not
+/// keep new ones from connecting.
+fn broadcast(clients: &Clients, event: &str) {
+    let frame = text_frame(event);
+    let mut sending = std::mem::take(
+        &mut *clients
+            .lock()
+            .expect("no thread panics while holding the lock"),
+    );
+    sending.retain_mut(|stream| stream.write_all(&frame).is_ok());
+    // clients that connected in the meantime were added to the empty list
+    clients
+        .lock()
+        .expect("no thread panics while holding the lock")
+        .append(&mut sending);
+}
+
+/// Encodes an unmasked websocket frame with the text `payload`.
+fn text_frame(payload: &str) -> Vec<u8> {
+    let mut frame = vec![0x81];
+    match payload.len() {
+        length @ 0..=125 => frame.push(length as u8),
+        length => {
+            frame.push(126);
+            frame.extend((length as u16).to_be_bytes());
+        }
+    }
+    frame.extend(payload.as_bytes());
+    frame
+}
+
+/// Answers a single http request.
+fn handle(
+    stream: TcpStream,
+    data: &Data,
+    web_root: Option<&Path>,
+    clients: &Clients,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or("/"));
+
+    let mut websocket_key = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                websocket_key = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    let mut stream = reader.into_inner();
+    let path = target.split('?').next().unwrap_or("/");
+    if method != "GET" {
+        return respond(&mut stream, "405 Method Not Allowed", "text/plain", b"");
+    }
+
+    match (path, websocket_key) {
+        ("/events", Some(key)) => {
+            let mut accept = key.into_bytes();
+            accept.extend(WEBSOCKET_GUID.as_bytes());
+            let accept = base64::engine::general_purpose::STANDARD
+                .encode(sha1_smol::Sha1::from(accept).digest().bytes());
+            write!(
+                stream,
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                 Connection: Upgrade\r\nSec-WebSocket-Accept: {accept}\r\n\r\n"
+            )?;
+            stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+            clients
+                .lock()
+                .expect("no thread panics while holding the lock")
+                .push(stream);
+            Ok(())
+        }
+        // the web build loads this file by default
+        ("/birthdays.csv", _) => {
+            let content = data
+                .lock()
+                .expect("no thread panics while holding the lock")
+                .clone();
+            respond(&mut stream, "200 OK", "text/csv; charset=utf-8", &content)
+        }
+        _ => match web_root.and_then(|root| static_file(root, path)) {
+            Some(file) => match fs::read(&file) {
+                Ok(content) => respond(&mut stream, "200 OK", content_type(&file), &content),
+                Err(_) => respond(&mut stream, "404 Not Found", "text/plain", b""),
+            },
+            None => respond(&mut stream, "404 Not Found", "text/plain", b""),
+        },
+    }
+}
+
+/// Returns the file in `root` for the url `path` unless it leaves the directory.
+fn static_file(root: &Path, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let file = root.join(relative);
+    if file.is_dir() {
+        Some(file.join("index.html"))
+    } else {
+        Some(file)
+    }
+}
+
+fn content_type(file: &Path) -> &'static str {
+    match file.extension().and_then(|extension| extension.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript",
+        Some("wasm") => "application/wasm",
+        Some("css") => "text/css",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv::CsvFile;
+
+    #[test]
+    fn sends_only_the_announced_persons() {
+        let path =
+            std::env::temp_dir().join(format!("birthday_display-test-{}.csv", std::process::id()));
+        fs::write(
+            &path,
+            "Muster,Anna,10.03.1990,f\nMuster,Ben,11.04.1991,m,,,,Blumen,Vertrieb,x\n",
+        )
+        .unwrap();
+
+        let content = serialize(&mut CsvFile::new(&path), true).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(content, b"Muster,Anna,10.03.1990,f\n");
+    }
+}
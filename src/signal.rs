@@ -0,0 +1,113 @@
+use crate::config::SignalConfig;
+use crate::error::BirthdayError;
+use crate::notify::{Notification, Notifier};
+
+use std::path::Path;
+
+use tokio::process::Command;
+
+/// Sends messages to a Signal group with a registered signal-cli account.
+pub struct SignalNotifier {
+    command: String,
+    account: String,
+    group: String,
+}
+
+impl SignalNotifier {
+    pub fn new(config: &SignalConfig) -> Self {
+        Self {
+            command: config
+                .command
+                .clone()
+                .unwrap_or_else(|| String::from("signal-cli")),
+            account: config.account.clone(),
+            group: config.group.clone(),
+        }
+    }
+}
+
+impl Notifier for SignalNotifier {
+    fn name(&self) -> String {
+        format!("signal group {}", self.group)
+    }
+
+    async fn send(&self, notification: &Notification<'_>) -> Result<(), BirthdayError> {
+        let output = Command::new(&self.command)
+            .args([
+                "--account",
+                &self.account,
+                "send",
+                "--group-id",
+                &self.group,
+            ])
+            .args(["--message", &notification.summary()])
+            .output()
+            .await
+            // a missing signal-cli is a mistake of the config, not of the network
+            .map_err(|error| BirthdayError::io(Path::new(&self.command), error))?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(BirthdayError::Network(format!(
+                "{} failed with {}: {}",
+                self.command,
+                output.status,
+                stderr.trim()
+            )))
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::fixtures::{date, person};
+
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn sends_the_summary_to_the_group() {
+        let dir = std::env::temp_dir().join(format!(
+            "birthday_display-test-signal-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let command = dir.join("signal-cli");
+        let arguments = dir.join("arguments");
+        fs::write(
+            &command,
+            format!(
+                "#!/bin/sh\nprintf '%s\\n' \"$@\" > {}\n",
+                arguments.display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&command, fs::Permissions::from_mode(0o755)).unwrap();
+        let notifier = SignalNotifier {
+            command: command.display().to_string(),
+            account: String::from("+491701234567"),
+            group: String::from("Z3JvdXA="),
+        };
+        let anna = person("Anna", date(1990, 3, 10));
+        let notification = Notification {
+            today: date(2024, 3, 10),
+            persons: &[],
+            birthdays: vec![(date(2024, 3, 10), &anna)],
+        };
+
+        let runtime = crate::runtime::current_thread().unwrap();
+        runtime.block_on(notifier.send(&notification)).unwrap();
+        let sent = fs::read_to_string(&arguments).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            sent,
+            format!(
+                "--account\n+491701234567\nsend\n--group-id\nZ3JvdXA=\n--message\n{}\n",
+                notification.summary()
+            )
+        );
+    }
+}
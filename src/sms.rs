@@ -0,0 +1,80 @@
+use crate::config::SmsConfig;
+use crate::error::BirthdayError;
+use crate::notify::{Notification, Notifier};
+use crate::person::Person;
+
+use reqwest::Client;
+
+/// Sends text messages to one recipient through a Twilio compatible http api.
+pub struct SmsNotifier {
+    client: Client,
+    endpoint: String,
+    account_sid: String,
+    auth_token: String,
+    from: String,
+    to: String,
+    /// The names of the persons the recipient is told about, everyone if empty.
+    persons: Vec<String>,
+}
+
+impl SmsNotifier {
+    /// Creates a notifier for every recipient of `config`.
+    pub fn new(config: &SmsConfig) -> Result<Vec<Self>, reqwest::Error> {
+        let client = Client::builder().build()?;
+        let endpoint = config.endpoint.clone().unwrap_or_else(|| {
+            format!(
+                "https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
+                config.account_sid
+            )
+        });
+
+        Ok(config
+            .recipients
+            .iter()
+            .map(|recipient| Self {
+                client: client.clone(),
+                endpoint: endpoint.clone(),
+                account_sid: config.account_sid.clone(),
+                auth_token: config.auth_token.clone(),
+                from: config.from.clone(),
+                to: recipient.to.clone(),
+                persons: recipient.persons.clone(),
+            })
+            .collect())
+    }
+}
+
+impl Notifier for SmsNotifier {
+    fn name(&self) -> String {
+        format!("sms to {}", self.to)
+    }
+
+    fn select(&self, person: &Person) -> bool {
+        self.persons.is_empty() || self.persons.contains(&person.name())
+    }
+
+    async fn send(&self, notification: &Notification<'_>) -> Result<(), BirthdayError> {
+        let message = notification.summary();
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .form(&[
+                ("To", self.to.as_str()),
+                ("From", self.from.as_str()),
+                ("Body", message.as_str()),
+            ])
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            let text = response.text().await.unwrap_or_default();
+            Err(BirthdayError::Network(format!(
+                "sms gateway returned {status}: {text}"
+            )))
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use crate::error::BirthdayError;
+#[cfg(not(feature = "network"))]
+use crate::load_local_image;
+use crate::person::Person;
+#[cfg(feature = "network")]
+use crate::request_birthday_image;
+use crate::runtime;
+use crate::{BirthdayDisplay, Cli, DataMessage, Message, Services};
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use iced::application::StyleSheet;
+use iced::theme;
+use iced::{Application, Element, Point, Size, Theme};
+use iced_native::{renderer, user_interface, UserInterface};
+use iced_wgpu::{wgpu, Backend, Renderer, Settings, Viewport};
+
+/// The format of the texture the view is rendered to.
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// A graphics device without a window.
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+}
+
+impl Gpu {
+    async fn request() -> Result<Self, BirthdayError> {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+            .ok_or_else(|| BirthdayError::Gui("no graphics adapter found".into()))?;
+
+        // the renderer needs two bind groups which every adapter supports
+        let limits = wgpu::Limits {
+            max_bind_groups: 2,
+            ..wgpu::Limits::downlevel_defaults()
+        };
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("snapshot device"),
+                    features: wgpu::Features::empty(),
+                    limits,
+                },
+                None,
+            )
+            .await
+            .map_err(|error| BirthdayError::Gui(error.to_string()))?;
+
+        Ok(Self { device, queue })
+    }
+
+    /// Draws `view` with `theme` and returns the rgba pixels of the image.
+    fn render(
+        &self,
+        view: Element<'_, Message>,
+        theme: &Theme,
+        (width, height): (u32, u32),
+    ) -> Vec<u8> {
+        let appearance = theme.appearance(&theme::Application::default());
+        let viewport = Viewport::with_physical_size(Size::new(width, height), 1.0);
+
+        let mut renderer = Renderer::new(Backend::new(&self.device, Settings::default(), FORMAT));
+        let mut interface = UserInterface::build(
+            view,
+            viewport.logical_size(),
+            user_interface::Cache::default(),
+            &mut renderer,
+        );
+        // the cursor is outside of the image so nothing is highlighted
+        interface.draw(
+            &mut renderer,
+            theme,
+            &renderer::Style {
+                text_color: appearance.text_color,
+            },
+            Point::new(-1.0, -1.0),
+        );
+
+        let extent = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("snapshot texture"),
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("snapshot encoder"),
+            });
+        let [r, g, b, a] = appearance.background_color.into_linear();
+        let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("snapshot background"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: f64::from(r),
+                        g: f64::from(g),
+                        b: f64::from(b),
+                        a: f64::from(a),
+                    }),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        let mut staging_belt = wgpu::util::StagingBelt::new(10 * 1024);
+        renderer.with_primitives(|backend, primitives| {
+            backend.present::<&str>(
+                &self.device,
+                &mut staging_belt,
+                &mut encoder,
+                &view,
+                primitives,
+                &viewport,
+                &[],
+            );
+        });
+
+        // rows of the copied texture have to be aligned
+        let unpadded_row = width * 4;
+        let padded_row = unpadded_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("snapshot buffer"),
+            size: u64::from(padded_row * height),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(padded_row),
+                    rows_per_image: None,
+                },
+            },
+            extent,
+        );
+
+        staging_belt.finish();
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::Maintain::Wait);
+
+        let data = slice.get_mapped_range();
+        data.chunks_exact(padded_row as usize)
+            .flat_map(|row| &row[..unpadded_row as usize])
+            .copied()
+            .collect()
+    }
+}
+
+/// Renders the birthdays of today, or of the day given with `--date`, and writes them
+/// as png image to `path`.
+pub fn write(
+    mut cli: Cli,
+    persons: Vec<Person>,
+    services: Services,
+    path: &Path,
+    size: (u32, u32),
+) -> Result<(), BirthdayError> {
+    // the snapshot should not depend on the time it is taken
+    cli.blank_between = None;
+    cli.dim.clear();
+    let verbosity = cli.verbose;
+
+    let (mut display, _) = BirthdayDisplay::new((cli, None, services));
+    // the photos of the shown date are requested with the persons
+    display.loading = false;
+    let _ = display.set_persons(persons);
+
+    let runtime = runtime::current_thread()?;
+    let urls: Vec<String> = display.downloads.keys().cloned().collect();
+    #[cfg(feature = "network")]
+    if let Some(client) = display.client.clone() {
+        for url in urls {
+            let (data, url) =
+                runtime.block_on(request_birthday_image(client.get(&url), url, verbosity));
+            let _ = display.update(Message::Data(DataMessage::ImageReceived(data, url)));
+        }
+    }
+    #[cfg(not(feature = "network"))]
+    for url in urls {
+        let (data, url) = runtime.block_on(load_local_image(url, verbosity));
+        let _ = display.update(Message::Data(DataMessage::ImageReceived(data, url)));
+    }
+
+    let gpu = runtime.block_on(Gpu::request())?;
+    let pixels = gpu.render(display.view(), &display.theme(), size);
+    image::save_buffer(path, &pixels, size.0, size.1, image::ColorType::Rgba8)
+        .map_err(|error| BirthdayError::io(path, io::Error::other(error)))?;
+
+    Ok(())
+}
+
+/// Renders `views` of the running `display` with its theme and writes each of them as
+/// png image of `size` to its path.
+pub fn write_views(
+    display: &BirthdayDisplay,
+    views: Vec<(PathBuf, Element<'_, Message>)>,
+    size: (u32, u32),
+) -> Result<(), BirthdayError> {
+    // the gui already runs on the tokio runtime, which can not wait for the gpu itself
+    let gpu = iced::futures::executor::block_on(Gpu::request())?;
+    let theme = display.theme();
+    for (path, view) in views {
+        let pixels = gpu.render(view, &theme, size);
+        image::save_buffer(&path, &pixels, size.0, size.1, image::ColorType::Rgba8)
+            .map_err(|error| BirthdayError::io(&path, io::Error::other(error)))?;
+    }
+    Ok(())
+}
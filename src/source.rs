@@ -0,0 +1,256 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::clock::Clock;
+use crate::error::BirthdayError;
+use crate::person::Person;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::redact::Redacted;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+#[cfg(not(target_arch = "wasm32"))]
+use clap::ValueEnum;
+
+/// A place the persons are loaded from and optionally saved to.
+// the browser downloads the persons itself instead of loading a source
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+pub trait PersonSource: Send {
+    /// Describes the source in messages, e.g. with the path of the file.
+    fn name(&self) -> String;
+
+    /// Reads all persons, skipping invalid ones unless the whole source is invalid.
+    fn load(&mut self, quiet: bool) -> Result<Vec<Person>, BirthdayError>;
+
+    /// Returns whether the persons changed since they were loaded last.
+    fn changed(&self) -> bool {
+        false
+    }
+
+    /// Returns how many records could not be read as a person when the source was loaded last.
+    fn skipped(&self) -> usize {
+        0
+    }
+
+    /// Replaces the stored persons with `persons`.
+    fn save(&mut self, _persons: &[Person]) -> Result<(), BirthdayError> {
+        Err(format!("{} can not be changed", self.name()).into())
+    }
+}
+
+/// Opens the source at `path`, choosing the format by the extension of the file.
+///
+/// Encrypted files are decrypted with `key` or a passphrase asked for on the terminal.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open(path: &Path, key: Option<&Path>) -> Box<dyn PersonSource> {
+    if let Some(tool) = crate::encrypted::Tool::detect(path) {
+        return Box::new(crate::encrypted::EncryptedFile::new(path, tool, key));
+    }
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => Box::new(crate::json::JsonFile::new(path)),
+        #[cfg(feature = "sqlite")]
+        Some(extension) if crate::sqlite::EXTENSIONS.contains(&extension) => {
+            Box::new(crate::sqlite::SqliteDatabase::new(path))
+        }
+        _ => Box::new(crate::csv::CsvFile::new(path)),
+    }
+}
+
+/// Shows only the persons of some departments of another source, e.g. for a profile of a
+/// screen in one building.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct Departments {
+    inner: Box<dyn PersonSource>,
+    departments: Vec<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Departments {
+    pub fn new(inner: Box<dyn PersonSource>, departments: Vec<String>) -> Self {
+        Self { inner, departments }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PersonSource for Departments {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn load(&mut self, quiet: bool) -> Result<Vec<Person>, BirthdayError> {
+        let mut persons = self.inner.load(quiet)?;
+        persons.retain(|person| {
+            person.department.as_deref().is_some_and(|department| {
+                self.departments
+                    .iter()
+                    .any(|shown| shown.trim().eq_ignore_ascii_case(department.trim()))
+            })
+        });
+        Ok(persons)
+    }
+
+    fn changed(&self) -> bool {
+        self.inner.changed()
+    }
+
+    fn skipped(&self) -> usize {
+        self.inner.skipped()
+    }
+
+    // saving only some of the persons would lose the other ones
+    fn save(&mut self, _persons: &[Person]) -> Result<(), BirthdayError> {
+        Err(format!("only some departments of {} are shown", self.name()).into())
+    }
+}
+
+/// What happens to persons born in the future or more than
+/// [`MAX_AGE`](crate::person::MAX_AGE) years ago.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Implausible {
+    /// show them without a message
+    Allow,
+    /// show them and print a warning
+    #[default]
+    Warn,
+    /// leave them out like unreadable rows
+    Skip,
+}
+
+/// Checks the birthdays of the persons of another source, so typos like 2103 instead of
+/// 2013 do not show up as nonsense banners.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct PlausibleBirthdays {
+    inner: Box<dyn PersonSource>,
+    clock: Arc<dyn Clock>,
+    severity: Implausible,
+    /// The persons left out when the source was loaded last with their position in it,
+    /// kept to be saved again.
+    skipped: Vec<(usize, Person)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PlausibleBirthdays {
+    pub fn new(inner: Box<dyn PersonSource>, clock: Arc<dyn Clock>, severity: Implausible) -> Self {
+        Self {
+            inner,
+            clock,
+            severity,
+            skipped: Vec::new(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PersonSource for PlausibleBirthdays {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn load(&mut self, quiet: bool) -> Result<Vec<Person>, BirthdayError> {
+        let mut persons = self.inner.load(quiet)?;
+        let today = self.clock.today();
+        self.skipped.clear();
+        let mut position = 0;
+        persons.retain(|person| {
+            position += 1;
+            let Some(reason) = person.implausible_birthday(today) else {
+                return true;
+            };
+            let skip = self.severity == Implausible::Skip;
+            if !quiet && self.severity != Implausible::Allow {
+                let action = if skip { "skipping" } else { "check the year" };
+                eprintln!("the birthday of {} {reason}, {action}", Redacted(person));
+            }
+            if skip {
+                self.skipped.push((position - 1, person.clone()));
+            }
+            !skip
+        });
+        Ok(persons)
+    }
+
+    fn changed(&self) -> bool {
+        self.inner.changed()
+    }
+
+    fn skipped(&self) -> usize {
+        self.inner.skipped() + self.skipped.len()
+    }
+
+    // the skipped persons are put back where they were loaded, so they are not lost and
+    // the rows the inner source could not read stay in place
+    fn save(&mut self, persons: &[Person]) -> Result<(), BirthdayError> {
+        let mut persons = persons.to_vec();
+        for (position, person) in &self.skipped {
+            persons.insert((*position).min(persons.len()), person.clone());
+        }
+        self.inner.save(&persons)
+    }
+}
+
+/// Returns the time `path` was last modified to detect changes of file sources.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Writes `content` to a temporary file first which then replaces `path` so an
+/// interrupted write does not destroy the existing file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn replace_file(path: &Path, content: &[u8]) -> Result<(), BirthdayError> {
+    let mut temporary_path = path.as_os_str().to_owned();
+    temporary_path.push(".tmp");
+    let temporary_path = Path::new(&temporary_path);
+
+    // the content is on the disk before the rename so a crash leaves either file
+    let write = || {
+        let mut file = std::fs::File::create(temporary_path)?;
+        std::io::Write::write_all(&mut file, content)?;
+        file.sync_all()
+    };
+    write().map_err(|error| BirthdayError::io(temporary_path, error))?;
+    std::fs::rename(temporary_path, path).map_err(|error| BirthdayError::io(path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::csv::CsvFile;
+
+    use std::fs;
+
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn saves_skipped_persons_where_they_were() {
+        let path = std::env::temp_dir().join(format!(
+            "birthday_display-test-plausible-{}.csv",
+            std::process::id()
+        ));
+        let content = "Muster,Anna,10.03.1990,f\n\
+                       Muster,Ben,11.04.2103,m\n\
+                       not a person\n\
+                       Muster,Carla,12.05.1991,f\n";
+        fs::write(&path, content).unwrap();
+        let clock = FixedClock(Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap());
+        let mut source = PlausibleBirthdays::new(
+            Box::new(CsvFile::new(&path)),
+            Arc::new(clock),
+            Implausible::Skip,
+        );
+
+        let persons = source.load(true).unwrap();
+        assert_eq!(persons.len(), 2);
+        source.save(&persons).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(saved, content);
+    }
+}
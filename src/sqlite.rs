@@ -0,0 +1,213 @@
+use crate::error::BirthdayError;
+use crate::person::Person;
+use crate::redact;
+use crate::source::{self, PersonSource};
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OpenFlags};
+
+/// The extensions of the files opened as [`SqliteDatabase`].
+pub const EXTENSIONS: [&str; 3] = ["db", "sqlite", "sqlite3"];
+
+/// Creates the table of the persons if a database is written the first time.
+const CREATE: &str = "CREATE TABLE IF NOT EXISTS persons (
+    last_name TEXT NOT NULL,
+    first_name TEXT NOT NULL,
+    birthday TEXT NOT NULL,
+    gender TEXT NOT NULL,
+    image_url TEXT,
+    contact_url TEXT,
+    color TEXT,
+    notes TEXT,
+    department TEXT,
+    opt_out INTEGER NOT NULL DEFAULT 0
+)";
+
+/// The persons in the table `persons` of a sqlite database, the birthday is stored as
+/// "YYYY-MM-DD" and the gender as one letter.
+pub struct SqliteDatabase {
+    path: PathBuf,
+    /// The rows that could not be read as a person, which are kept when saving.
+    skipped: Vec<i64>,
+    modified: Option<SystemTime>,
+}
+
+impl SqliteDatabase {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            skipped: Vec::new(),
+            modified: None,
+        }
+    }
+
+    fn error(&self, error: rusqlite::Error) -> BirthdayError {
+        BirthdayError::Data(format!("error in {}: {error}", self.path.display()))
+    }
+}
+
+/// Reads the columns of `row` selected by [`SELECT`] as a person.
+fn read_person(row: &rusqlite::Row<'_>) -> Result<Person, String> {
+    let column = |index| -> Result<Option<String>, String> {
+        row.get(index).map_err(|error| error.to_string())
+    };
+    let birthday: NaiveDate = row.get(3).map_err(|error| error.to_string())?;
+    Person::builder(
+        column(1)?.unwrap_or_default(),
+        column(2)?.unwrap_or_default(),
+        birthday,
+        column(4)?
+            .and_then(|gender| gender.chars().next())
+            .unwrap_or_default()
+            .into(),
+    )
+    .image_url(column(5)?)
+    .contact_url(column(6)?)
+    .color(column(7)?)
+    .notes(column(8)?)
+    .department(column(9)?)
+    .opt_out(row.get(10).map_err(|error| error.to_string())?)
+    .build()
+    .map_err(|error| error.to_string())
+}
+
+/// Selects the rowid and the columns of all persons in the order they were added.
+const SELECT: &str = "SELECT rowid, last_name, first_name, birthday, gender, image_url, \
+    contact_url, color, notes, department, opt_out FROM persons ORDER BY rowid";
+
+impl PersonSource for SqliteDatabase {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&mut self, quiet: bool) -> Result<Vec<Person>, BirthdayError> {
+        self.modified = source::modified(&self.path);
+        let connection = Connection::open_with_flags(&self.path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|error| self.error(error))?;
+        let mut statement = connection
+            .prepare(SELECT)
+            .map_err(|error| self.error(error))?;
+        let mut rows = statement.query([]).map_err(|error| self.error(error))?;
+
+        let mut persons = Vec::new();
+        let mut skipped = Vec::new();
+        while let Some(row) = rows.next().map_err(|error| self.error(error))? {
+            let rowid: i64 = row.get(0).map_err(|error| self.error(error))?;
+            match read_person(row) {
+                Ok(person) => persons.push(person),
+                Err(error) => {
+                    skipped.push(rowid);
+                    if !quiet {
+                        if redact::log_pii() {
+                            eprintln!("error reading row {rowid}: {error}");
+                        } else {
+                            // the message of the error may contain the invalid value
+                            eprintln!("error reading row {rowid}, pass --log-pii for details");
+                        }
+                    }
+                }
+            }
+        }
+        drop(rows);
+        self.skipped = skipped;
+        Ok(persons)
+    }
+
+    fn changed(&self) -> bool {
+        source::modified(&self.path) != self.modified
+    }
+
+    fn skipped(&self) -> usize {
+        self.skipped.len()
+    }
+
+    /// Replaces the persons in one transaction, the rows that could not be read are kept.
+    fn save(&mut self, persons: &[Person]) -> Result<(), BirthdayError> {
+        let mut connection = Connection::open(&self.path).map_err(|error| self.error(error))?;
+        let transaction = connection
+            .transaction()
+            .map_err(|error| self.error(error))?;
+        transaction
+            .execute(CREATE, [])
+            .map_err(|error| self.error(error))?;
+        let skipped: Vec<String> = self.skipped.iter().map(ToString::to_string).collect();
+        transaction
+            .execute(
+                &format!(
+                    "DELETE FROM persons WHERE rowid NOT IN ({})",
+                    skipped.join(",")
+                ),
+                [],
+            )
+            .map_err(|error| self.error(error))?;
+        for person in persons {
+            transaction
+                .execute(
+                    "INSERT INTO persons (last_name, first_name, birthday, gender, image_url, \
+                     contact_url, color, notes, department, opt_out) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    params![
+                        person.last_name,
+                        person.first_name,
+                        person.birthday,
+                        person.gender.to_string(),
+                        person.image_url,
+                        person.contact_url,
+                        person.color,
+                        person.notes,
+                        person.department,
+                        person.opt_out,
+                    ],
+                )
+                .map_err(|error| self.error(error))?;
+        }
+        transaction.commit().map_err(|error| self.error(error))?;
+        self.modified = source::modified(&self.path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn keeps_unreadable_rows_when_saving() {
+        let path = std::env::temp_dir().join(format!(
+            "birthday_display-test-{}.sqlite",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let connection = Connection::open(&path).unwrap();
+        connection.execute(CREATE, []).unwrap();
+        connection
+            .execute(
+                "INSERT INTO persons (last_name, first_name, birthday, gender, image_url) \
+                 VALUES ('Muster', 'Anna', '1990-03-10', 'f', NULL), \
+                 ('Muster', 'Ben', '1991-04-11', 'm', 'no url')",
+                [],
+            )
+            .unwrap();
+        drop(connection);
+
+        let mut database = SqliteDatabase::new(&path);
+        let mut persons = database.load(true).unwrap();
+        assert_eq!(persons.len(), 1);
+        assert_eq!(database.skipped(), 1);
+
+        persons[0].notes = Some(String::from("Blumen"));
+        persons.push(fixtures::person("Cem", persons[0].birthday));
+        database.save(&persons).unwrap();
+
+        let loaded = database.load(true).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(database.skipped(), 1);
+        let names: Vec<_> = loaded.iter().map(Person::name).collect();
+        assert_eq!(names, ["Anna Muster", "Cem Muster"]);
+        assert_eq!(loaded[0].notes.as_deref(), Some("Blumen"));
+    }
+}
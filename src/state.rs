@@ -0,0 +1,63 @@
+use crate::error::BirthdayError;
+use crate::source;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Runtime state kept in a small toml file so a restart continues where the program
+/// stopped instead of showing another day or notifying twice.
+///
+/// The display and the notification daemon may share the file, each only changes its
+/// own fields.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    /// The date that was pinned remotely, missing while today is shown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_date: Option<NaiveDate>,
+    /// The last day the day change hook of the script was called.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_day: Option<NaiveDate>,
+    /// The last day every notifier fired or gave up by its name.
+    #[serde(default)]
+    pub notified: BTreeMap<String, NaiveDate>,
+}
+
+impl State {
+    /// Reads the state from `path`, an empty one if the file is missing or broken.
+    pub fn load(path: &Path, quiet: bool) -> Self {
+        let result = match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).map_err(|error| error.to_string()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.to_string()),
+        };
+        result.unwrap_or_else(|error| {
+            if !quiet {
+                println!("error reading state file {}: {error}", path.display());
+            }
+            Self::default()
+        })
+    }
+
+    /// Reads the state from `path`, applies `change` and writes it back.
+    pub fn update(path: &Path, quiet: bool, change: impl FnOnce(&mut Self)) {
+        let mut state = Self::load(path, quiet);
+        change(&mut state);
+        if let Err(error) = state.save(path) {
+            if !quiet {
+                println!("error writing state file: {error}");
+            }
+        }
+    }
+
+    /// Replaces the file at `path` with the state so a crash never leaves a half
+    /// written file behind.
+    fn save(&self, path: &Path) -> Result<(), BirthdayError> {
+        let content = toml::to_string(self).map_err(|error| error.to_string())?;
+        source::replace_file(path, content.as_bytes())
+    }
+}
@@ -0,0 +1,152 @@
+use crate::person::Person;
+use crate::Message;
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDate};
+
+use iced::alignment::{Horizontal, Vertical};
+use iced::widget::canvas::{self, Cursor, Frame, Geometry, Path, Text};
+use iced::widget::{column, container, text, Canvas};
+use iced::{Alignment, Element, Length, Point, Rectangle, Size, Theme};
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+];
+
+/// Aggregated numbers about all loaded persons.
+#[derive(Debug, Default)]
+pub struct Statistics {
+    per_month: [usize; 12],
+    most_common_day: Option<((u32, u32), usize)>,
+    average_age: Option<f64>,
+    total: usize,
+}
+
+impl Statistics {
+    /// Computes the statistics for `persons` relative to `today`.
+    pub fn new<'a>(persons: impl IntoIterator<Item = &'a Person>, today: NaiveDate) -> Self {
+        let mut statistics = Self::default();
+        let mut per_day: HashMap<(u32, u32), usize> = HashMap::new();
+        let mut age_sum = 0u64;
+        let mut age_count = 0u64;
+
+        for person in persons {
+            statistics.total += 1;
+            statistics.per_month[person.birthday.month0() as usize] += 1;
+            *per_day
+                .entry((person.birthday.day(), person.birthday.month()))
+                .or_default() += 1;
+
+            if let Some(age) = today.years_since(person.birthday) {
+                age_sum += u64::from(age);
+                age_count += 1;
+            }
+        }
+
+        // prefer the earliest date in the year on ties to get a stable result
+        statistics.most_common_day = per_day
+            .into_iter()
+            .max_by_key(|&((day, month), count)| (count, std::cmp::Reverse((month, day))));
+        if age_count > 0 {
+            statistics.average_age = Some(age_sum as f64 / age_count as f64);
+        }
+
+        statistics
+    }
+
+    /// Generates the statistics screen for iced.
+    pub fn view<'a>(self) -> Element<'a, Message> {
+        let mut facts = column![text(format!("{} Personen geladen", self.total)).size(20)];
+
+        if let Some(((day, month), count)) = self.most_common_day {
+            facts = facts.push(
+                text(format!(
+                    "Häufigster Geburtstag: {day:02}.{month:02}. ({count} Personen)"
+                ))
+                .size(20),
+            );
+        }
+        if let Some(average_age) = self.average_age {
+            facts = facts.push(
+                text(format!("Durchschnittsalter: {average_age:.1} Jahre").replace('.', ","))
+                    .size(20),
+            );
+        }
+
+        let chart = Canvas::new(MonthChart {
+            per_month: self.per_month,
+        })
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        column![
+            text("Geburtstage pro Monat").size(30),
+            container(chart).width(Length::Fill).height(Length::Fill),
+            facts.spacing(10).align_items(Alignment::Center),
+        ]
+        .spacing(20)
+        .align_items(Alignment::Center)
+        .into()
+    }
+}
+
+/// Bar chart with the number of birthdays in every month.
+struct MonthChart {
+    per_month: [usize; 12],
+}
+
+impl canvas::Program<Message> for MonthChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let palette = theme.palette();
+        let mut frame = Frame::new(bounds.size());
+
+        let label_height = 30.0;
+        let slot_width = frame.width() / 12.0;
+        let bar_width = slot_width * 0.6;
+        let max_height = (frame.height() - 2.0 * label_height).max(0.0);
+        let max_count = self.per_month.iter().copied().max().unwrap_or(0).max(1);
+
+        for (index, (&count, name)) in self.per_month.iter().zip(MONTH_NAMES).enumerate() {
+            let center_x = slot_width * (index as f32 + 0.5);
+            let bar_height = max_height * count as f32 / max_count as f32;
+            let top = label_height + max_height - bar_height;
+
+            frame.fill(
+                &Path::rectangle(
+                    Point::new(center_x - bar_width / 2.0, top),
+                    Size::new(bar_width, bar_height),
+                ),
+                palette.primary,
+            );
+            frame.fill_text(Text {
+                content: count.to_string(),
+                position: Point::new(center_x, top),
+                color: palette.text,
+                size: 18.0,
+                horizontal_alignment: Horizontal::Center,
+                vertical_alignment: Vertical::Bottom,
+                ..Text::default()
+            });
+            frame.fill_text(Text {
+                content: name.to_string(),
+                position: Point::new(center_x, label_height + max_height + 5.0),
+                color: palette.text,
+                size: 18.0,
+                horizontal_alignment: Horizontal::Center,
+                vertical_alignment: Vertical::Top,
+                ..Text::default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,149 @@
+use iced_native::theme::Palette;
+use iced_native::widget::container;
+use iced_native::{Background, Color, Theme};
+
+/// Text color of errors shown in the gui.
+pub const ERROR: Color = Color::from_rgb(0.7, 0.0, 0.0);
+
+/// Black on white theme without colors for e-paper displays.
+pub fn eink() -> Theme {
+    Theme::custom(Palette {
+        background: Color::WHITE,
+        text: Color::BLACK,
+        primary: Color::BLACK,
+        success: Color::BLACK,
+        danger: Color::BLACK,
+    })
+}
+
+/// White and yellow on black theme for viewers with low vision.
+pub fn high_contrast() -> Theme {
+    Theme::custom(Palette {
+        background: Color::BLACK,
+        text: Color::WHITE,
+        primary: Color::from_rgb(1.0, 0.85, 0.0),
+        success: Color::from_rgb(0.4, 1.0, 0.4),
+        danger: Color::from_rgb(1.0, 0.5, 0.5),
+    })
+}
+
+/// The minimal contrast ratio of normal text required by WCAG level AA.
+const MIN_CONTRAST: f32 = 4.5;
+
+/// Returns the relative luminance of `color` as defined by WCAG.
+fn luminance(color: Color) -> f32 {
+    let channel = |value: f32| {
+        if value <= 0.03928 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(color.r) + 0.7152 * channel(color.g) + 0.0722 * channel(color.b)
+}
+
+/// Returns the contrast ratio of two colors between 1 and 21.
+pub fn contrast(a: Color, b: Color) -> f32 {
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Returns the error color of `theme`, falling back to its text color if the error
+/// color is hard to read on its background.
+pub fn error_color(theme: &Theme) -> Color {
+    let palette = theme.palette();
+    [ERROR, palette.danger]
+        .into_iter()
+        .find(|color| contrast(*color, palette.background) >= MIN_CONTRAST)
+        .unwrap_or(palette.text)
+}
+
+/// The colors that can be given by name instead of as `#rrggbb`.
+const COLOR_NAMES: [(&str, Color); 12] = [
+    ("black", Color::BLACK),
+    ("white", Color::WHITE),
+    ("gray", Color::from_rgb(0.5, 0.5, 0.5)),
+    ("red", Color::from_rgb(0.9, 0.1, 0.1)),
+    ("orange", Color::from_rgb(1.0, 0.55, 0.0)),
+    ("yellow", Color::from_rgb(1.0, 0.85, 0.0)),
+    ("green", Color::from_rgb(0.1, 0.65, 0.2)),
+    ("teal", Color::from_rgb(0.0, 0.55, 0.55)),
+    ("blue", Color::from_rgb(0.1, 0.35, 0.9)),
+    ("purple", Color::from_rgb(0.55, 0.2, 0.75)),
+    ("pink", Color::from_rgb(1.0, 0.45, 0.7)),
+    ("brown", Color::from_rgb(0.55, 0.35, 0.15)),
+];
+
+/// Parses a color written as `#rgb`, `#rrggbb` or one of [`COLOR_NAMES`].
+pub fn parse_color(value: &str) -> Result<Color, String> {
+    let value = value.trim();
+    if let Some((_, color)) = COLOR_NAMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+    {
+        return Ok(*color);
+    }
+
+    let invalid = || format!("invalid color \"{value}\", expected e.g. #ff8800 or orange");
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    let digits: Vec<u8> = hex
+        .chars()
+        .map(|digit| digit.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<_>>()
+        .ok_or_else(invalid)?;
+    let channels: Vec<u8> = match digits.as_slice() {
+        [r, g, b] => vec![r * 17, g * 17, b * 17],
+        [r1, r2, g1, g2, b1, b2] => vec![r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2],
+        _ => return Err(invalid()),
+    };
+    Ok(Color::from_rgb8(channels[0], channels[1], channels[2]))
+}
+
+/// A rounded border in the favorite color of a person around their card.
+pub struct Accent(pub Color);
+
+impl container::StyleSheet for Accent {
+    type Style = Theme;
+
+    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            border_radius: 10.0,
+            border_width: 4.0,
+            border_color: self.0,
+            ..container::Appearance::default()
+        }
+    }
+}
+
+/// Rounded label in the primary color of the theme, e.g. for the department of a person.
+pub fn chip(theme: &Theme) -> container::Appearance {
+    let palette = theme.palette();
+    container::Appearance {
+        text_color: Some(palette.background),
+        background: Some(Background::Color(palette.primary)),
+        border_radius: 10.0,
+        ..container::Appearance::default()
+    }
+}
+
+/// Outlined label in the primary color of the theme, quieter than [`chip`] so the cards
+/// of today stand out against the countdown of later ones.
+pub fn countdown(theme: &Theme) -> container::Appearance {
+    let palette = theme.palette();
+    container::Appearance {
+        text_color: Some(palette.primary),
+        border_radius: 10.0,
+        border_width: 2.0,
+        border_color: palette.primary,
+        ..container::Appearance::default()
+    }
+}
+
+/// Black background with dimmed text for the quiet hours.
+pub fn blank(_theme: &Theme) -> container::Appearance {
+    container::Appearance {
+        text_color: Some(Color::from_rgb(0.3, 0.3, 0.3)),
+        background: Some(Background::Color(Color::BLACK)),
+        ..container::Appearance::default()
+    }
+}
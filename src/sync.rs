@@ -0,0 +1,173 @@
+use crate::config::Config;
+use crate::error::BirthdayError;
+use crate::google::GoogleCalendar;
+use crate::outlook::OutlookCalendar;
+use crate::person::Person;
+use crate::runtime;
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use clap::Subcommand;
+
+/// The calendars the birthdays can be synchronized to.
+#[derive(Debug, Clone, Subcommand)]
+pub enum Target {
+    /// create a recurring event for every person in a Google Calendar
+    Google {
+        /// the id of the calendar, e.g. "primary" or "abc@group.calendar.google.com"
+        #[arg(long)]
+        calendar: String,
+
+        /// OAuth access token with the calendar.events scope,
+        /// e.g. from `gcloud auth print-access-token`
+        #[arg(long, env = "GOOGLE_ACCESS_TOKEN", hide_env_values = true)]
+        access_token: String,
+    },
+    /// create a recurring event for every person in an Outlook calendar with the
+    /// app registration of the "outlook" section of the config file
+    Outlook,
+}
+
+/// A yearly event for the birthday of a person.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BirthdayEvent {
+    /// Identifies the person across synchronizations.
+    pub key: String,
+    pub summary: String,
+    /// The day of birth where the recurrence starts.
+    pub date: NaiveDate,
+}
+
+impl BirthdayEvent {
+    pub fn new(person: &Person) -> Self {
+        Self {
+            key: format!(
+                "{}|{}|{}",
+                person.last_name,
+                person.first_name,
+                person.birthday.format("%Y-%m-%d")
+            ),
+            summary: format!("Geburtstag {}", person.name()),
+            date: person.birthday,
+        }
+    }
+}
+
+/// An event created by an earlier synchronization.
+#[derive(Debug, Clone)]
+pub struct RemoteEvent {
+    /// The id of the event in the calendar.
+    pub id: String,
+    pub event: BirthdayEvent,
+}
+
+/// A modification of the calendar.
+#[derive(Debug, Clone)]
+pub enum Change {
+    Create(BirthdayEvent),
+    Update(String, BirthdayEvent),
+    Delete(RemoteEvent),
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Create(event) => write!(f, "create \"{}\"", event.summary),
+            Change::Update(_, event) => write!(f, "update \"{}\"", event.summary),
+            Change::Delete(remote) => write!(f, "delete \"{}\"", remote.event.summary),
+        }
+    }
+}
+
+/// A calendar the birthday events can be synchronized to.
+pub trait Calendar {
+    /// Returns the events created by earlier synchronizations.
+    async fn events(&self) -> Result<Vec<RemoteEvent>, BirthdayError>;
+
+    /// Performs `change` in the calendar.
+    async fn apply(&self, change: &Change) -> Result<(), BirthdayError>;
+}
+
+/// Returns the changes that make the `existing` events match the `wanted` ones.
+pub fn plan(wanted: Vec<BirthdayEvent>, existing: Vec<RemoteEvent>) -> Vec<Change> {
+    let mut existing: HashMap<String, RemoteEvent> = existing
+        .into_iter()
+        .map(|remote| (remote.event.key.clone(), remote))
+        .collect();
+
+    let mut changes = Vec::new();
+    for event in wanted {
+        match existing.remove(&event.key) {
+            Some(remote) if remote.event == event => {}
+            Some(remote) => changes.push(Change::Update(remote.id, event)),
+            None => changes.push(Change::Create(event)),
+        }
+    }
+
+    // sort to report the deletions in a stable order
+    let mut removed: Vec<RemoteEvent> = existing.into_values().collect();
+    removed.sort_by(|a, b| a.event.key.cmp(&b.event.key));
+    changes.extend(removed.into_iter().map(Change::Delete));
+
+    changes
+}
+
+/// Makes the birthday events of `calendar` match `wanted`.
+async fn synchronize(
+    calendar: &impl Calendar,
+    wanted: Vec<BirthdayEvent>,
+    dry_run: bool,
+    quiet: bool,
+) -> Result<(), BirthdayError> {
+    let changes = plan(wanted, calendar.events().await?);
+    for change in changes {
+        if dry_run || !quiet {
+            println!("{change}");
+        }
+        if !dry_run {
+            calendar.apply(&change).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Synchronizes the birthdays of `persons` to `target` or only prints the changes on `dry_run`.
+pub fn run(
+    target: &Target,
+    persons: &[Person],
+    config: &Config,
+    dry_run: bool,
+    quiet: bool,
+) -> Result<(), BirthdayError> {
+    let mut wanted: Vec<BirthdayEvent> = persons
+        .iter()
+        .filter(|person| person.announced())
+        .map(BirthdayEvent::new)
+        .collect();
+    // the same person listed twice gets only one event
+    wanted.sort_by(|a, b| a.key.cmp(&b.key));
+    wanted.dedup_by(|a, b| a.key == b.key);
+
+    let runtime = runtime::current_thread()?;
+
+    runtime.block_on(async {
+        match target {
+            Target::Google {
+                calendar,
+                access_token,
+            } => {
+                let calendar = GoogleCalendar::new(calendar, access_token)?;
+                synchronize(&calendar, wanted, dry_run, quiet).await
+            }
+            Target::Outlook => {
+                let config = config
+                    .outlook
+                    .as_ref()
+                    .ok_or("the config file has no \"outlook\" section")?;
+                let calendar = OutlookCalendar::connect(config).await?;
+                synchronize(&calendar, wanted, dry_run, quiet).await
+            }
+        }
+    })
+}
@@ -0,0 +1,67 @@
+use crate::collation::SortKey;
+use crate::index::BirthdayIndex;
+use crate::person::Person;
+use crate::Message;
+
+use chrono::{Days, NaiveDate};
+
+use iced::time::Duration;
+use iced::widget::text;
+use iced::{Element, Length};
+use iced_native::alignment::Horizontal;
+
+/// The ticker lists the birthdays of this many days after today.
+const DAYS: u64 = 30;
+
+/// How often the ticker moves on by a letter.
+pub const STEP: Duration = Duration::from_millis(150);
+
+const SIZE: u16 = 20;
+
+const SEPARATOR: &str = "   ·   ";
+
+/// Returns the line of the birthdays of the [`DAYS`] days after `today`, empty if there
+/// are none.
+pub fn line(persons: &[Person], index: &BirthdayIndex, today: NaiveDate) -> String {
+    let (Some(from), Some(to)) = (
+        today.checked_add_days(Days::new(1)),
+        today.checked_add_days(Days::new(DAYS + 1)),
+    ) else {
+        return String::new();
+    };
+    let mut upcoming: Vec<(NaiveDate, &Person)> = index
+        .between(from, to)
+        .into_iter()
+        .map(|(date, position)| (date, &persons[position]))
+        .collect();
+    upcoming.sort_by_cached_key(|(date, person)| (*date, SortKey::new(&person.name())));
+
+    upcoming
+        .into_iter()
+        .map(|(date, person)| match date.years_since(person.birthday) {
+            Some(age) => format!("{} {} wird {age}", date.format("%d.%m."), person.name()),
+            None => format!("{} {}", date.format("%d.%m."), person.name()),
+        })
+        .collect::<Vec<String>>()
+        .join(SEPARATOR)
+}
+
+/// Shows `line` moved on by `step` letters, repeating it to fill a window `width` wide.
+pub fn view<'a>(line: &str, step: usize, width: f32) -> Element<'a, Message> {
+    let letters: Vec<char> = line.chars().chain(SEPARATOR.chars()).collect();
+    // the widths of the letters are unknown, so a bit less than fits on average is shown
+    // to keep the ticker on one line
+    let visible = (width / (f32::from(SIZE) * 0.45)) as usize;
+    let shown: String = letters
+        .iter()
+        .cycle()
+        .skip(step % letters.len())
+        .take(visible)
+        .collect();
+    // centered letters of different widths would jump with every step
+    text(shown)
+        .size(SIZE)
+        .width(Length::Fill)
+        .horizontal_alignment(Horizontal::Left)
+        .into()
+}
@@ -0,0 +1,51 @@
+use crate::collation::SortKey;
+use crate::index::BirthdayIndex;
+use crate::person::Person;
+use crate::Message;
+
+use chrono::{Days, NaiveDate};
+
+use iced::widget::{column, scrollable, text, Column};
+use iced::{Alignment, Element, Length};
+
+/// Generates the list of the birthdays in the `days` starting today.
+pub fn view<'a>(
+    persons: &'a [Person],
+    index: &BirthdayIndex,
+    today: NaiveDate,
+    days: u64,
+) -> Element<'a, Message> {
+    let end = today.checked_add_days(Days::new(days)).unwrap_or(today);
+    let mut upcoming: Vec<(NaiveDate, &Person)> = index
+        .between(today, end)
+        .into_iter()
+        .map(|(date, position)| (date, &persons[position]))
+        .collect();
+    upcoming.sort_by_cached_key(|(date, person)| (*date, SortKey::new(&person.name())));
+
+    let entries: Vec<Element<Message>> = upcoming
+        .into_iter()
+        .map(|(date, person)| {
+            let age = match date.years_since(person.birthday) {
+                Some(age) => format!(", wird {age}"),
+                None => String::new(),
+            };
+            text(format!("{}: {}{age}", date.format("%d.%m."), person.name()))
+                .size(25)
+                .into()
+        })
+        .collect();
+
+    let title = if entries.is_empty() {
+        format!("Keine Geburtstage in den nächsten {days} Tagen")
+    } else {
+        format!("Geburtstage der nächsten {days} Tage")
+    };
+    column![
+        text(title).size(30),
+        scrollable(Column::with_children(entries).spacing(10)).height(Length::Fill)
+    ]
+    .spacing(20)
+    .align_items(Alignment::Center)
+    .into()
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::Deserialize;
+
+/// How often the release url is asked for a new version after the start.
+pub const INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The part of a release in the json of the GitHub or Gitea api that is needed.
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+/// Returns the numbers of a version like "v1.2.3", `None` if it has other characters.
+fn numbers(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map(|number| number.parse().ok())
+        .collect()
+}
+
+/// Returns whether `version` is newer than `current`.
+fn newer(version: &str, current: &str) -> bool {
+    match (numbers(version), numbers(current)) {
+        (Some(mut version), Some(mut current)) => {
+            // 1.2 is the same version as 1.2.0
+            let len = version.len().max(current.len());
+            version.resize(len, 0);
+            current.resize(len, 0);
+            version > current
+        }
+        _ => false,
+    }
+}
+
+/// Asks `url` for the latest version and returns it if it is newer than this program.
+///
+/// The url either answers with the json of a release having a `tag_name` or with the
+/// version as plain text.
+pub async fn check(client: Client, url: String) -> Result<Option<String>, String> {
+    let body = async {
+        client
+            .get(&url)
+            .timeout(Duration::from_secs(30))
+            // the api of GitHub rejects requests without user agent
+            .header(reqwest::header::USER_AGENT, "birthday_display")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await
+    }
+    .await
+    .map_err(|error| format!("error checking for updates at {url}: {error}"))?;
+
+    let version = match serde_json::from_str::<Release>(&body) {
+        Ok(release) => release.tag_name,
+        Err(_) => body.trim().to_string(),
+    };
+    if numbers(&version).is_none() {
+        return Err(format!("{url} did not answer with a version"));
+    }
+    Ok(newer(&version, env!("CARGO_PKG_VERSION")).then_some(version))
+}
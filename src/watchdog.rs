@@ -0,0 +1,97 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::Local;
+
+/// Interval of the heartbeats if systemd does not ask for another one.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Receives heartbeats from the gui so a hung display can be restarted.
+pub enum Watchdog {
+    /// The time of the last heartbeat is written to the file.
+    File(PathBuf),
+    /// The watchdog of the systemd service is notified on the socket.
+    Systemd { socket: String, ready: bool },
+}
+
+impl Watchdog {
+    /// Writes heartbeats to `file` or notifies systemd if none is given.
+    pub fn new(file: Option<PathBuf>) -> Result<Self, String> {
+        match file {
+            Some(path) => Ok(Self::File(path)),
+            None => match env::var("NOTIFY_SOCKET") {
+                Ok(socket) => Ok(Self::Systemd {
+                    socket,
+                    ready: false,
+                }),
+                Err(_) => Err(String::from(
+                    "NOTIFY_SOCKET is not set, the program was not started by systemd",
+                )),
+            },
+        }
+    }
+
+    /// Returns how often heartbeats are expected, half of the timeout of systemd.
+    pub fn interval(&self) -> Duration {
+        let timeout = env::var("WATCHDOG_USEC")
+            .ok()
+            .and_then(|usec| usec.parse().ok())
+            .map(Duration::from_micros);
+
+        match (self, timeout) {
+            (Self::Systemd { .. }, Some(timeout)) => (timeout / 2).max(Duration::from_secs(1)),
+            _ => DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Reports that the gui is still responding.
+    pub fn heartbeat(&mut self) -> io::Result<()> {
+        match self {
+            Self::File(path) => fs::write(path, format!("{}\n", Local::now().to_rfc3339())),
+            Self::Systemd { socket, ready } => {
+                // the first heartbeat also completes the startup
+                let state = if *ready {
+                    "WATCHDOG=1"
+                } else {
+                    "READY=1\nWATCHDOG=1"
+                };
+                notify(socket, state)?;
+                *ready = true;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Sends `state` to the notification socket of systemd.
+#[cfg(unix)]
+fn notify(socket: &str, state: &str) -> io::Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    let datagram = UnixDatagram::unbound()?;
+    match socket.strip_prefix('@') {
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            use std::os::unix::net::SocketAddr;
+
+            let address = SocketAddr::from_abstract_name(name)?;
+            datagram.send_to_addr(state.as_bytes(), &address)?;
+        }
+        _ => {
+            datagram.send_to(state.as_bytes(), socket)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn notify(_socket: &str, _state: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "systemd is not available on this platform",
+    ))
+}
@@ -0,0 +1,126 @@
+use crate::{BirthdayDisplay, Cli, DataMessage, Message, Services, TimerMessage};
+
+use std::path::PathBuf;
+
+use reqwest::Url;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{MessageEvent, WebSocket};
+
+use iced::futures::channel::mpsc::{self, UnboundedReceiver};
+use iced::futures::{future, StreamExt};
+use iced::time::Instant;
+use iced::{subscription, Application, Command, Settings, Subscription};
+
+/// The data file that is used if the page has no `data` query parameter.
+const DEFAULT_DATA: &str = "birthdays.csv";
+
+/// Returns the url of the csv file given by the `data` query parameter of the page.
+fn data_url() -> Result<Url, String> {
+    let href = web_sys::window()
+        .ok_or("no browser window")?
+        .location()
+        .href()
+        .map_err(|error| format!("{error:?}"))?;
+    let page = Url::parse(&href).map_err(|error| error.to_string())?;
+
+    let data = page
+        .query_pairs()
+        .find(|(key, _)| key == "data")
+        .map(|(_, value)| value.into_owned())
+        .unwrap_or_else(|| String::from(DEFAULT_DATA));
+    page.join(&data).map_err(|error| error.to_string())
+}
+
+/// Generates a Command that downloads the csv file at `url`.
+pub fn fetch_persons(url: String) -> Command<Message> {
+    Command::perform(
+        async move {
+            let response = reqwest::get(url).await.map_err(|error| error.to_string())?;
+            let response = response
+                .error_for_status()
+                .map_err(|error| error.to_string())?;
+            let bytes = response.bytes().await.map_err(|error| error.to_string())?;
+            Ok(bytes.to_vec())
+        },
+        |result| Message::Data(DataMessage::PersonsReceived(result)),
+    )
+}
+
+/// Opens the websocket of `birthday_display serve` on the host of the page.
+fn connect_events() -> Result<UnboundedReceiver<String>, JsValue> {
+    let location = web_sys::window()
+        .ok_or_else(|| JsValue::from("no browser window"))?
+        .location();
+    let protocol = if location.protocol()? == "https:" {
+        "wss"
+    } else {
+        "ws"
+    };
+    let socket = WebSocket::new(&format!("{protocol}://{}/events", location.host()?))?;
+
+    let (sender, receiver) = mpsc::unbounded();
+    let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+        if let Some(text) = event.data().as_string() {
+            let _ = sender.unbounded_send(text);
+        }
+    });
+    socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    // the socket calls the closure as long as the page is open
+    on_message.forget();
+
+    Ok(receiver)
+}
+
+/// Produces messages for the events pushed by the server so the page does not need to poll.
+///
+/// Without the server nothing is received.
+pub fn server_events() -> Subscription<Message> {
+    struct ServerEvents;
+
+    subscription::unfold(
+        std::any::TypeId::of::<ServerEvents>(),
+        None,
+        |receiver: Option<UnboundedReceiver<String>>| async move {
+            let receiver = match receiver {
+                Some(receiver) => Ok(receiver),
+                None => connect_events(),
+            };
+            let mut receiver = match receiver {
+                Ok(receiver) => receiver,
+                Err(error) => {
+                    web_sys::console::error_1(&error);
+                    future::pending().await
+                }
+            };
+
+            let message = match receiver.next().await.as_deref() {
+                Some("day-change") => Some(Message::Timer(TimerMessage::UpdateDay(Instant::now()))),
+                Some("data-refresh") => Some(Message::Data(DataMessage::Reload)),
+                Some(_) => None,
+                None => future::pending().await,
+            };
+            (message, Some(receiver))
+        },
+    )
+}
+
+/// Runs the display in the browser tab with the persons loaded over http.
+pub fn run() {
+    let url = match data_url() {
+        Ok(url) => url,
+        Err(error) => {
+            web_sys::console::error_1(&error.into());
+            return;
+        }
+    };
+
+    let cli = Cli {
+        file: PathBuf::from(url.as_str()),
+        ..Default::default()
+    };
+    let settings = Settings::with_flags((cli, None, Services::default()));
+    if let Err(error) = BirthdayDisplay::run(settings) {
+        web_sys::console::error_1(&error.to_string().into());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::config::{Encryption, WeeklyConfig, WorkdaysConfig};
+use crate::error::BirthdayError;
+use crate::notify::{Notification, Notifier};
+
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+/// The number of days listed by the digest starting with the day it is sent.
+const DAYS: usize = 7;
+
+const SUBJECT: &str = "Geburtstage nächste Woche";
+
+const TEMPLATE: &str = "Geburtstage nächste Woche:\n{birthdays}";
+
+/// How long connecting to the relay and every answer of it may take.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Mails the birthdays of the coming week once a week, separate from the notifications
+/// of the day.
+pub struct WeeklyDigest {
+    config: WeeklyConfig,
+}
+
+impl WeeklyDigest {
+    /// Fails for credentials that would be sent without encryption.
+    pub fn new(config: &WeeklyConfig) -> Result<Self, BirthdayError> {
+        if config.username.is_some() && config.encryption == Encryption::None {
+            return Err(BirthdayError::Data(String::from(
+                "the weekly digest sends the password only with encryption = \"starttls\" \
+                 or \"tls\"",
+            )));
+        }
+        Ok(Self {
+            config: config.clone(),
+        })
+    }
+
+    /// Returns the mail of `notification` to all recipients.
+    fn message(&self, notification: &Notification<'_>) -> Result<Message, BirthdayError> {
+        let mailbox = |address: &str| {
+            address.parse::<Mailbox>().map_err(|error| {
+                BirthdayError::Data(format!("invalid address \"{address}\": {error}"))
+            })
+        };
+        let mut builder = Message::builder()
+            .from(mailbox(&self.config.from)?)
+            .subject(self.subject())
+            .header(ContentType::TEXT_PLAIN);
+        for recipient in &self.config.to {
+            builder = builder.to(mailbox(recipient)?);
+        }
+        builder
+            .body(self.body(notification))
+            .map_err(|error| BirthdayError::Data(format!("error creating the digest: {error}")))
+    }
+
+    /// Returns the connection to the relay with the encryption and credentials of the config.
+    fn transport(&self) -> Result<AsyncSmtpTransport<Tokio1Executor>, BirthdayError> {
+        let config = &self.config;
+        let error = |error: lettre::transport::smtp::Error| {
+            BirthdayError::Network(format!("error connecting to {}: {error}", config.host))
+        };
+        let (builder, port) = match config.encryption {
+            Encryption::None => (
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+                25,
+            ),
+            Encryption::Starttls => (
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                    .map_err(error)?,
+                587,
+            ),
+            Encryption::Tls => (
+                AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host).map_err(error)?,
+                465,
+            ),
+        };
+        let mut builder = builder
+            .port(config.port.unwrap_or(port))
+            .timeout(Some(TIMEOUT));
+        if let Some(username) = &config.username {
+            let password = config.password.clone().unwrap_or_default();
+            builder = builder.credentials(Credentials::new(username.clone(), password));
+        }
+        Ok(builder.build())
+    }
+
+    /// Returns the text of the mail listing the birthdays of `notification`.
+    fn body(&self, notification: &Notification<'_>) -> String {
+        let lines: Vec<String> = notification
+            .birthdays
+            .iter()
+            .map(|(date, person)| person.banner(*date, Some(notification.today)))
+            .collect();
+        self.config
+            .template
+            .as_deref()
+            .unwrap_or(TEMPLATE)
+            .replace("{birthdays}", &lines.join("\n"))
+    }
+
+    fn subject(&self) -> &str {
+        self.config.subject.as_deref().unwrap_or(SUBJECT)
+    }
+}
+
+impl Notifier for WeeklyDigest {
+    fn name(&self) -> String {
+        format!("weekly digest to {}", self.config.to.join(", "))
+    }
+
+    // the digest lists the real dates of the whole week on its day
+    fn dates(&self, today: NaiveDate, _workdays: Option<&WorkdaysConfig>) -> Vec<NaiveDate> {
+        if today.weekday() == self.config.weekday.unwrap_or(Weekday::Mon) {
+            today.iter_days().take(DAYS).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn preview(&self, notification: &Notification<'_>) -> String {
+        format!("{}\n{}", self.subject(), self.body(notification))
+    }
+
+    async fn send(&self, notification: &Notification<'_>) -> Result<(), BirthdayError> {
+        let message = self.message(notification)?;
+        self.transport()?.send(message).await.map_err(|error| {
+            BirthdayError::Network(format!("error sending to {}: {error}", self.config.host))
+        })?;
+        Ok(())
+    }
+}